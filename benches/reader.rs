@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use std::io::Cursor;
 
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use wavefst::util::{unpack_to_ascii, unpack_to_ascii_scalar};
 use wavefst::{
     ChainCompression, FstWriter, GeomEntry, Header, ReaderBuilder, ScopeType, SignalValue,
     TimeCompression, VarDir, VarType,
@@ -139,5 +140,19 @@ fn bench_placeholder(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_placeholder);
+fn bench_unpack_bits(c: &mut Criterion) {
+    let packed: Vec<u8> = (0..512u32).map(|idx| (idx * 37) as u8).collect();
+    let width = (packed.len() * 8) as u32;
+
+    let mut group = c.benchmark_group("unpack_packed_bits");
+    group.bench_function("dispatch", |b| {
+        b.iter(|| unpack_to_ascii(std::hint::black_box(&packed), width).unwrap())
+    });
+    group.bench_function("scalar", |b| {
+        b.iter(|| unpack_to_ascii_scalar(std::hint::black_box(&packed), width).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_placeholder, bench_unpack_bits);
 criterion_main!(benches);
@@ -3,17 +3,34 @@
 use std::io::{Cursor, SeekFrom};
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::sync::Arc;
 
 use tokio::fs::File;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{Mutex, MutexGuard};
 
-use crate::error::Result;
-use crate::reader::{FstReader, ReaderBuilder, ReaderOptions};
-use crate::writer::{ChainCompression, FstWriter, TimeCompression, WriterBuilder, WriterOptions};
+use crate::block::HierarchyCompression;
+use crate::compression::CustomCompressor;
+use crate::error::{Error, Result};
+use crate::io::{ReadSeek, SpoolBuffer};
+use crate::reader::{FstReader, ReaderBuilder, ReaderOptions, VcBlockIndexEntry, VcBlockMeta};
+use crate::types::FileType;
+use crate::writer::{
+    ChainCompression, DuplicateChangePolicy, FstWriter, TimeCompression, WriterBuilder,
+    WriterOptions,
+};
 
-/// Reader that loads an async source into memory and exposes the synchronous [`FstReader`] API.
-pub struct AsyncReader {
-    inner: FstReader<Cursor<Vec<u8>>>,
+/// Reader that exposes the synchronous [`FstReader`] API to async code.
+///
+/// Readers built with [`AsyncReader::from_reader`] or [`AsyncReader::from_file`] load the whole
+/// source into memory. [`AsyncReader::from_file_lazy`] and [`AsyncReader::from_seekable`] keep the
+/// source on disk instead and only pull the bytes of the blocks that are requested.
+///
+/// The synchronous reader is shared with the blocking tasks that run its IO, so a cancelled
+/// [`AsyncReader::block_index`] or [`AsyncReader::read_block_at`] future never loses it; access it
+/// through [`AsyncReader::reader`].
+pub struct AsyncReader<S: ReadSeek = Cursor<Vec<u8>>> {
+    inner: Arc<Mutex<FstReader<S>>>,
 }
 
 impl AsyncReader {
@@ -35,7 +52,9 @@ impl AsyncReader {
         source.read_to_end(&mut buffer).await?;
         let cursor = Cursor::new(buffer);
         let reader = ReaderBuilder::new(cursor).options(options).build()?;
-        Ok(Self { inner: reader })
+        Ok(Self {
+            inner: Arc::new(Mutex::new(reader)),
+        })
     }
 
     /// Opens a file via `tokio::fs::File` and constructs a reader with default options.
@@ -52,34 +71,78 @@ impl AsyncReader {
         let file = File::open(path).await?;
         Self::from_reader_with_options(file, options).await
     }
+}
 
-    /// Returns a shared reference to the underlying synchronous reader.
-    pub fn reader(&self) -> &FstReader<Cursor<Vec<u8>>> {
-        &self.inner
+impl AsyncReader<std::fs::File> {
+    /// Opens a file without loading it into memory; blocks are read on demand by seeking.
+    pub async fn from_file_lazy(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_file_lazy_with_options(path, ReaderOptions::default()).await
     }
 
-    /// Returns a mutable reference to the underlying synchronous reader.
-    pub fn reader_mut(&mut self) -> &mut FstReader<Cursor<Vec<u8>>> {
-        &mut self.inner
+    /// Like [`Self::from_file_lazy`], with explicit reader options.
+    pub async fn from_file_lazy_with_options(
+        path: impl AsRef<Path>,
+        options: ReaderOptions,
+    ) -> Result<Self> {
+        let file = File::open(path).await?.into_std().await;
+        Self::from_seekable(file, options).await
+    }
+}
+
+impl<S: ReadSeek + Send + 'static> AsyncReader<S> {
+    /// Wraps a seekable source without copying it into memory. The header and metadata are parsed
+    /// on tokio's blocking pool, as are the reads of [`Self::block_index`] and
+    /// [`Self::read_block_at`], which only touch the requested blocks.
+    pub async fn from_seekable(source: S, options: ReaderOptions) -> Result<Self> {
+        let inner = tokio::task::spawn_blocking(move || {
+            ReaderBuilder::new(source).options(options).build()
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(inner)),
+        })
     }
 
-    /// Consumes the async wrapper, yielding the synchronous reader.
-    pub fn into_reader(self) -> FstReader<Cursor<Vec<u8>>> {
-        self.inner
+    /// Returns the location and time range of every value-change block, reading only the block
+    /// prefixes on tokio's blocking pool. See [`FstReader::build_block_index`].
+    pub async fn block_index(&self) -> Result<Vec<VcBlockIndexEntry>> {
+        self.run_blocking(|reader| reader.build_block_index()).await
     }
-}
 
-impl Deref for AsyncReader {
-    type Target = FstReader<Cursor<Vec<u8>>>;
+    /// Fetches and decodes the single block whose tag starts at `offset` on tokio's blocking pool.
+    /// See [`FstReader::read_block_at`].
+    pub async fn read_block_at(&self, offset: u64) -> Result<VcBlockMeta> {
+        self.run_blocking(move |reader| reader.read_block_at(offset))
+            .await
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+    /// Runs `op` on tokio's blocking pool with the reader locked. The blocking task holds its own
+    /// handle to the reader, so dropping the returned future leaves the reader intact.
+    async fn run_blocking<T, F>(&self, op: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut FstReader<S>) -> Result<T> + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || op(&mut inner.blocking_lock()))
+            .await
+            .map_err(std::io::Error::other)?
     }
 }
 
-impl DerefMut for AsyncReader {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+impl<S: ReadSeek> AsyncReader<S> {
+    /// Locks the underlying synchronous reader, waiting for a blocking call still using it.
+    pub async fn reader(&self) -> MutexGuard<'_, FstReader<S>> {
+        self.inner.lock().await
+    }
+
+    /// Consumes the async wrapper, yielding the synchronous reader. Fails while a blocking call
+    /// whose future was dropped is still using the reader.
+    pub fn into_reader(self) -> Result<FstReader<S>> {
+        Arc::try_unwrap(self.inner)
+            .map(Mutex::into_inner)
+            .map_err(|_| Error::invalid("reader is still in use by a blocking call"))
     }
 }
 
@@ -128,7 +191,98 @@ impl<W> AsyncWriterBuilder<W> {
         self
     }
 
-    /// Sets an optional compression level hint.
+    /// Sets the header file type.
+    pub fn file_type(mut self, file_type: FileType) -> Self {
+        self.options.file_type = Some(file_type);
+        self
+    }
+
+    /// Sets the version string that will be recorded in the header.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.options.version = Some(version.into());
+        self
+    }
+
+    /// Sets the date string that will be recorded in the header.
+    pub fn date(mut self, date: impl Into<String>) -> Self {
+        self.options.date = Some(date.into());
+        self
+    }
+
+    /// Compresses chains with a user-supplied codec, storing its marker byte in each block.
+    pub fn custom_chain_compression(mut self, compressor: CustomCompressor) -> Self {
+        self.options.chain_compression = ChainCompression::Custom;
+        self.options.custom_chain_compressor = Some(compressor);
+        self
+    }
+
+    /// Enables or disables zlib compression of the geometry block.
+    pub fn compress_geometry(mut self, enabled: bool) -> Self {
+        self.options.compress_geometry = enabled;
+        self
+    }
+
+    /// Enables the run-length geometry encoding (a `wavefst` extension unreadable by libfst).
+    pub fn geometry_rle(mut self, enabled: bool) -> Self {
+        self.options.geometry_rle = enabled;
+        self
+    }
+
+    /// Allows value changes to be emitted before the header is written.
+    pub fn deferred_header(mut self, enabled: bool) -> Self {
+        self.options.deferred_header = enabled;
+        self
+    }
+
+    /// Rejects duplicate variable names within a scope.
+    pub fn forbid_duplicate_names(mut self, enabled: bool) -> Self {
+        self.options.forbid_duplicate_names = enabled;
+        self
+    }
+
+    /// Enables or disables the initial frame written with each value-change block.
+    pub fn emit_frame(mut self, enabled: bool) -> Self {
+        self.options.emit_frame = enabled;
+        self
+    }
+
+    /// Sets the bit state used for undriven signals in the initial frame.
+    pub fn undriven_fill(mut self, fill: char) -> Self {
+        self.options.undriven_fill = fill;
+        self
+    }
+
+    /// Selects the compression used for the hierarchy block.
+    pub fn hierarchy_compression(mut self, compression: HierarchyCompression) -> Self {
+        self.options.hierarchy_compression = compression;
+        self
+    }
+
+    /// Chooses how repeated changes for the same handle and timestamp are resolved.
+    pub fn duplicate_changes(mut self, policy: DuplicateChangePolicy) -> Self {
+        self.options.duplicate_changes = policy;
+        self
+    }
+
+    /// Flushes a value-change block automatically once `limit` changes are pending.
+    pub fn max_pending_changes(mut self, limit: Option<usize>) -> Self {
+        self.options.max_pending_changes = limit;
+        self
+    }
+
+    /// Flushes a value-change block automatically once its estimated memory reaches `limit` bytes.
+    pub fn max_block_memory(mut self, limit: Option<u64>) -> Self {
+        self.options.max_block_memory = limit;
+        self
+    }
+
+    /// Rejects NaN and infinite values emitted for real-valued handles.
+    pub fn reject_nonfinite_reals(mut self, value: bool) -> Self {
+        self.options.reject_nonfinite_reals = value;
+        self
+    }
+
+    /// Sets the compression level; see [`WriterBuilder::compression_level`] for the accepted range.
     pub fn compression_level(mut self, level: Option<u32>) -> Self {
         self.options.compression_level = level;
         self
@@ -139,7 +293,7 @@ impl<W> AsyncWriterBuilder<W> {
     where
         W: AsyncWrite + Unpin + Send,
     {
-        let inner = WriterBuilder::new(Cursor::new(Vec::new()))
+        let inner = WriterBuilder::new(SpoolBuffer::new())
             .options(self.options.clone())
             .build()?;
         Ok(AsyncWriter {
@@ -149,10 +303,11 @@ impl<W> AsyncWriterBuilder<W> {
     }
 }
 
-/// Writer that buffers into memory and flushes to an async sink on [`finish`](AsyncWriter::finish).
+/// Writer that buffers into memory and hands the bytes to an async sink on
+/// [`flush_async`](AsyncWriter::flush_async) and [`finish`](AsyncWriter::finish).
 pub struct AsyncWriter<W> {
     sink: W,
-    inner: FstWriter<Cursor<Vec<u8>>>,
+    inner: FstWriter<SpoolBuffer>,
 }
 
 impl<W> AsyncWriter<W> {
@@ -166,12 +321,31 @@ impl<W> AsyncWriter<W>
 where
     W: AsyncWrite + Unpin + Send,
 {
-    /// Flushes buffered data to the async sink, returning the sink on completion.
+    /// Writes every byte encoded so far to the async sink and releases it from memory.
+    ///
+    /// Pending value changes are not cut into a block; call [`FstWriter::flush`] first (or rely
+    /// on `max_pending_changes`/`max_block_memory`) to bound them. Once bytes have been drained,
+    /// `finish` can no longer rewrite the header, so its times and `vc_section_count` keep the
+    /// values passed to `write_header`. With the z-wrapper enabled nothing is drained until
+    /// `finish`.
+    pub async fn flush_async(&mut self) -> Result<()> {
+        let Some(spool) = self.inner.flushed_sink_mut()? else {
+            return Ok(());
+        };
+        let payload = spool.drain();
+        if !payload.is_empty() {
+            self.sink.write_all(&payload).await?;
+            self.sink.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the remaining buffered data to the async sink, returning the sink on completion.
     pub async fn finish(self) -> Result<W> {
         let AsyncWriter { sink, inner } = self;
-        let cursor = inner.finish()?;
+        let mut spool = inner.finish()?;
         let mut sink = sink;
-        let payload = cursor.into_inner();
+        let payload = spool.drain();
         if !payload.is_empty() {
             sink.write_all(&payload).await?;
         }
@@ -181,7 +355,7 @@ where
 }
 
 impl<W> Deref for AsyncWriter<W> {
-    type Target = FstWriter<Cursor<Vec<u8>>>;
+    type Target = FstWriter<SpoolBuffer>;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
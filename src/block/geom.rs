@@ -1,11 +1,20 @@
-use std::io::{Read, Write};
+use std::io::{BufReader, ErrorKind, Read, Write};
 
 #[cfg(feature = "gzip")]
 use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
 
 use crate::encoding::{decode_varint, encode_varint};
 use crate::error::{Error, Result};
-use crate::util::read_u64_be;
+use crate::util::{read_u64_be, read_varint_from_reader};
+
+/// Bit set in the geometry block's uncompressed-length word when the payload holds
+/// `(count, entry)` varint runs instead of one varint per handle. This is a `wavefst` extension:
+/// libfst-based readers cannot parse such blocks, so writers only emit it on request.
+pub const GEOMETRY_RLE_FLAG: u64 = 1 << 63;
+
+/// Raw geometry value marking a 4-byte [`GeomEntry::ShortReal`]. It lies just past the `u32`
+/// range of fixed widths, so it cannot collide with a libfst entry.
+pub const GEOMETRY_SHORT_REAL: u64 = 1 << 32;
 
 /// Describes the layout of a single signal as recorded in the geometry block.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -14,15 +23,50 @@ pub enum GeomEntry {
     Fixed(u32),
     /// IEEE-754 double precision real value (signalled by zero in the block stream).
     Real,
-    /// Variable-length payload (strings, enums, etc.).
+    /// IEEE-754 single precision real value stored in 4 bytes, signalled by
+    /// [`GEOMETRY_SHORT_REAL`]. This is a `wavefst` extension: libfst stores shortreals as
+    /// doubles, and its readers reject this marker.
+    ShortReal,
+    /// Variable-length payload (strings, enums, etc.). Every change is stored with a varint length
+    /// prefix, so fixed-width data must still be written through this length-prefixed form.
     Variable,
 }
 
 impl GeomEntry {
+    /// Returns the bit width of a fixed-width entry, or `None` for reals and variable-length
+    /// payloads.
+    pub fn bit_width(&self) -> Option<u32> {
+        match self {
+            GeomEntry::Fixed(width) => Some(*width),
+            GeomEntry::Real | GeomEntry::ShortReal | GeomEntry::Variable => None,
+        }
+    }
+
+    /// Returns `true` for single-bit signals.
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, GeomEntry::Fixed(1))
+    }
+
+    /// Returns `true` for fixed-width signals wider than one bit.
+    pub fn is_vector(&self) -> bool {
+        matches!(self, GeomEntry::Fixed(width) if *width > 1)
+    }
+
+    /// Returns `true` for real-valued signals of either precision.
+    pub fn is_real(&self) -> bool {
+        matches!(self, GeomEntry::Real | GeomEntry::ShortReal)
+    }
+
+    /// Returns `true` for variable-length signals.
+    pub fn is_variable(&self) -> bool {
+        matches!(self, GeomEntry::Variable)
+    }
+
     fn from_raw(value: u64) -> Result<Self> {
         match value {
             0 => Ok(GeomEntry::Real),
             0xFFFF_FFFF => Ok(GeomEntry::Variable),
+            GEOMETRY_SHORT_REAL => Ok(GeomEntry::ShortReal),
             len => {
                 let len32 = u32::try_from(len)
                     .map_err(|_| Error::invalid("geometry entry length exceeds u32 range"))?;
@@ -40,6 +84,7 @@ impl GeomEntry {
         match self {
             GeomEntry::Fixed(len) => *len as u64,
             GeomEntry::Real => 0,
+            GeomEntry::ShortReal => GEOMETRY_SHORT_REAL,
             GeomEntry::Variable => 0xFFFF_FFFF,
         }
     }
@@ -76,6 +121,15 @@ impl GeomInfo {
         })
     }
 
+    /// Coalesces consecutive equal entries into `(count, entry)` runs, the inverse of
+    /// [`GeomInfo::from_runs`].
+    pub fn to_runs(&self) -> Vec<(u32, GeomEntry)> {
+        self.entries
+            .chunk_by(|a, b| a == b)
+            .map(|chunk| (chunk.len() as u32, chunk[0].clone()))
+            .collect()
+    }
+
     /// Returns the recorded entry for the provided 1-based handle, if available.
     pub fn entry(&self, handle: u32) -> Option<&GeomEntry> {
         if handle == 0 {
@@ -92,6 +146,16 @@ impl GeomInfo {
             .map(|(idx, entry)| (idx as u32 + 1, entry))
     }
 
+    /// Returns the summed width of every fixed-width entry; reals and variable-length entries
+    /// contribute nothing.
+    pub fn total_bits(&self) -> u64 {
+        self.entries
+            .iter()
+            .filter_map(GeomEntry::bit_width)
+            .map(u64::from)
+            .sum()
+    }
+
     /// Decodes a geometry section from the provided reader. The `section_length` must be the raw
     /// value stored in the file (including the 8-byte length word itself).
     pub fn decode_block<R: Read>(reader: &mut R, section_length: u64) -> Result<Self> {
@@ -110,7 +174,9 @@ impl GeomInfo {
             ));
         }
 
-        let uncompressed_len = read_u64_be(reader)?;
+        let length_word = read_u64_be(reader)?;
+        let run_length = length_word & GEOMETRY_RLE_FLAG != 0;
+        let uncompressed_len = length_word & !GEOMETRY_RLE_FLAG;
         let max_handle = read_u64_be(reader)?;
         let compressed_len = payload_len
             .checked_sub(16)
@@ -157,10 +223,21 @@ impl GeomInfo {
 
         let mut entries = Vec::with_capacity(max_handle_usize);
         let mut slice = raw.as_slice();
-        for _ in 0..max_handle_usize {
-            let value = decode_varint(&mut slice)?;
-            let entry = GeomEntry::from_raw(value)?;
-            entries.push(entry);
+        if run_length {
+            while entries.len() < max_handle_usize {
+                let count = usize::try_from(decode_varint(&mut slice)?)
+                    .ok()
+                    .filter(|count| *count > 0 && *count <= max_handle_usize - entries.len())
+                    .ok_or_else(|| Error::decode("geometry run length out of range"))?;
+                let entry = GeomEntry::from_raw(decode_varint(&mut slice)?)?;
+                entries.resize(entries.len() + count, entry);
+            }
+        } else {
+            for _ in 0..max_handle_usize {
+                let value = decode_varint(&mut slice)?;
+                let entry = GeomEntry::from_raw(value)?;
+                entries.push(entry);
+            }
         }
 
         if !slice.is_empty() {
@@ -173,20 +250,117 @@ impl GeomInfo {
         })
     }
 
+    /// Decodes a geometry block like [`GeomInfo::decode_block`], but reads the varints straight
+    /// from the (inflated) payload stream instead of buffering the compressed and raw payloads
+    /// first, so peak memory is bounded by the decoded entries.
+    pub fn decode_block_streaming<R: Read>(reader: &mut R, section_length: u64) -> Result<Self> {
+        let payload_len = section_length
+            .checked_sub(8)
+            .filter(|len| *len >= 16)
+            .ok_or_else(|| Error::invalid("geometry section shorter than required metadata"))?;
+
+        let length_word = read_u64_be(reader)?;
+        let run_length = length_word & GEOMETRY_RLE_FLAG != 0;
+        let uncompressed_len = length_word & !GEOMETRY_RLE_FLAG;
+        let max_handle = read_u64_be(reader)?;
+        let compressed_len = payload_len - 16;
+        let max_handle_usize = usize::try_from(max_handle)
+            .map_err(|_| Error::invalid("geometry max handle exceeds usize"))?;
+
+        let mut payload = reader.take(compressed_len);
+        let entries = if compressed_len == uncompressed_len {
+            decode_entries_streaming(&mut payload, run_length, max_handle_usize, uncompressed_len)?
+        } else {
+            #[cfg(feature = "gzip")]
+            {
+                let mut decoder = ZlibDecoder::new(&mut payload);
+                let entries = decode_entries_streaming(
+                    &mut decoder,
+                    run_length,
+                    max_handle_usize,
+                    uncompressed_len,
+                )?;
+                if decoder.read(&mut [0u8; 1])? != 0 {
+                    return Err(Error::decode(
+                        "geometry decompression length mismatch with header",
+                    ));
+                }
+                entries
+            }
+            #[cfg(not(feature = "gzip"))]
+            {
+                return Err(Error::unsupported(
+                    "geometry block requires zlib decompression; recompile with the `gzip` feature",
+                ));
+            }
+        };
+        std::io::copy(&mut payload, &mut std::io::sink())?;
+
+        Ok(Self {
+            max_handle,
+            entries,
+        })
+    }
+
     /// Encodes the geometry information into an FST block payload. When `compress` is `true`, the
-    /// encoder attempts zlib compression and falls back to the raw stream if compression is
-    /// ineffective.
-    pub fn encode_block(&self, compress: bool) -> Result<EncodedGeometry> {
+    /// encoder attempts zlib compression at `compression_level` (default 4) and falls back to the
+    /// raw stream if compression is ineffective.
+    pub fn encode_block(
+        &self,
+        compress: bool,
+        compression_level: Option<u32>,
+    ) -> Result<EncodedGeometry> {
+        self.encode_payload(
+            self.per_handle_payload(),
+            false,
+            compress,
+            compression_level,
+        )
+    }
+
+    /// Like [`Self::encode_block`], but stores consecutive identical entries as `(count, entry)`
+    /// runs flagged with [`GEOMETRY_RLE_FLAG`]. The per-handle layout is kept whenever the runs
+    /// would not be shorter, so the result is only unreadable by libfst when it actually saves
+    /// space.
+    pub fn encode_block_rle(
+        &self,
+        compress: bool,
+        compression_level: Option<u32>,
+    ) -> Result<EncodedGeometry> {
+        let mut runs = Vec::new();
+        for (count, entry) in self.to_runs() {
+            encode_varint(u64::from(count), &mut runs);
+            encode_varint(entry.to_raw(), &mut runs);
+        }
+        let per_handle = self.per_handle_payload();
+        if runs.len() < per_handle.len() {
+            self.encode_payload(runs, true, compress, compression_level)
+        } else {
+            self.encode_payload(per_handle, false, compress, compression_level)
+        }
+    }
+
+    fn per_handle_payload(&self) -> Vec<u8> {
         let mut raw = Vec::with_capacity(self.entries.len() * 2);
         for entry in &self.entries {
             encode_varint(entry.to_raw(), &mut raw);
         }
+        raw
+    }
 
+    fn encode_payload(
+        &self,
+        raw: Vec<u8>,
+        run_length: bool,
+        compress: bool,
+        compression_level: Option<u32>,
+    ) -> Result<EncodedGeometry> {
         let uncompressed_len = raw.len() as u64;
         let (data, used_compression) = if compress {
             #[cfg(feature = "gzip")]
             {
-                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(4));
+                let level = compression_level.map(|lvl| lvl.min(9)).unwrap_or(4);
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(level));
                 encoder.write_all(&raw)?;
                 let compressed = encoder.finish()?;
                 if compressed.len() < raw.len() {
@@ -197,6 +371,7 @@ impl GeomInfo {
             }
             #[cfg(not(feature = "gzip"))]
             {
+                let _ = compression_level;
                 return Err(Error::unsupported(
                     "geometry compression requires the `gzip` feature",
                 ));
@@ -213,6 +388,7 @@ impl GeomInfo {
             max_handle: self.max_handle,
             data,
             compressed: used_compression,
+            run_length,
         })
     }
 }
@@ -230,15 +406,68 @@ pub struct EncodedGeometry {
     pub data: Vec<u8>,
     /// Indicates whether `data` was produced by compression.
     pub compressed: bool,
+    /// Indicates whether the payload holds run-length entries (see [`GEOMETRY_RLE_FLAG`]).
+    pub run_length: bool,
 }
 
 impl EncodedGeometry {
     /// Writes the encoded geometry payload (excluding block type) to the provided writer.
     pub fn write_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<()> {
         writer.write_all(&self.section_length.to_be_bytes())?;
-        writer.write_all(&self.uncompressed_len.to_be_bytes())?;
+        let length_word = if self.run_length {
+            self.uncompressed_len | GEOMETRY_RLE_FLAG
+        } else {
+            self.uncompressed_len
+        };
+        writer.write_all(&length_word.to_be_bytes())?;
         writer.write_all(&self.max_handle.to_be_bytes())?;
         writer.write_all(&self.data)?;
         Ok(())
     }
 }
+
+/// Reads `max_handle` geometry entries from a stream holding exactly `uncompressed_len` bytes.
+fn decode_entries_streaming<R: Read>(
+    stream: &mut R,
+    run_length: bool,
+    max_handle: usize,
+    uncompressed_len: u64,
+) -> Result<Vec<GeomEntry>> {
+    let mut stream = BufReader::new(stream.take(uncompressed_len));
+    let mut consumed = 0u64;
+    let mut next_varint = |stream: &mut BufReader<_>| -> Result<u64> {
+        match read_varint_from_reader(stream) {
+            Ok((value, len)) => {
+                consumed += len as u64;
+                Ok(value)
+            }
+            Err(Error::Io(err)) if err.kind() == ErrorKind::UnexpectedEof => {
+                Err(Error::decode("geometry payload shorter than its entries"))
+            }
+            Err(err) => Err(err),
+        }
+    };
+
+    let mut entries = Vec::with_capacity(max_handle);
+    if run_length {
+        while entries.len() < max_handle {
+            let count = usize::try_from(next_varint(&mut stream)?)
+                .ok()
+                .filter(|count| *count > 0 && *count <= max_handle - entries.len())
+                .ok_or_else(|| Error::decode("geometry run length out of range"))?;
+            let entry = GeomEntry::from_raw(next_varint(&mut stream)?)?;
+            entries.resize(entries.len() + count, entry);
+        }
+    } else {
+        for _ in 0..max_handle {
+            entries.push(GeomEntry::from_raw(next_varint(&mut stream)?)?);
+        }
+    }
+
+    if consumed != uncompressed_len {
+        return Err(Error::decode(
+            "geometry payload length mismatch with decoded entries",
+        ));
+    }
+    Ok(entries)
+}
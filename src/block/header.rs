@@ -1,8 +1,8 @@
 use std::convert::TryFrom;
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::error::{Error, Result};
-use crate::types::BlockType;
+use crate::types::{BlockType, Endianness, FileType};
 use crate::util::{read_cstring, read_f64_be, read_u64_be, validate_endian};
 
 /// Fixed sizes of textual header fields, as defined by the FST specification.
@@ -35,10 +35,12 @@ pub struct Header {
     pub version: String,
     /// Producer supplied date string (null terminated within the 119-byte buffer).
     pub date: String,
-    /// File type marker (e.g. Verilog, VHDL, mixed).
+    /// File type marker (e.g. Verilog, VHDL, mixed); see [`Header::file_type_enum`].
     pub file_type: u8,
     /// Simulation time zero offset stored in the header.
     pub time_zero: u64,
+    /// Byte order of real values, detected from the `endian_test` marker.
+    pub endianness: Endianness,
 }
 
 impl Default for Header {
@@ -57,11 +59,17 @@ impl Default for Header {
             date: String::from(""),
             file_type: 0,
             time_zero: 0,
+            endianness: Endianness::native(),
         }
     }
 }
 
 impl Header {
+    /// Interprets the file-type byte, returning `None` for values outside the FST range.
+    pub fn file_type_enum(&self) -> Option<FileType> {
+        FileType::try_from(self.file_type).ok()
+    }
+
     /// Reads and parses the header block from the provided reader.
     pub fn read<R: Read + Seek>(reader: &mut R) -> Result<Self> {
         let mut block_type = [0u8; 1];
@@ -80,7 +88,7 @@ impl Header {
         let start_time = read_u64_be(reader)?;
         let end_time = read_u64_be(reader)?;
         let endian_test = read_f64_be(reader)?;
-        validate_endian(endian_test)?;
+        let endianness = validate_endian(endian_test)?;
         let memory_used = read_u64_be(reader)?;
         let scope_count = read_u64_be(reader)?;
         let var_count = read_u64_be(reader)?;
@@ -113,11 +121,51 @@ impl Header {
             date,
             file_type,
             time_zero,
+            endianness,
         })
     }
 
+    /// Serializes the header block, including its block tag, in the 329-byte layout read by
+    /// [`Self::read`]. The endian marker is written in [`Self::endianness`] order; textual fields
+    /// longer than their fixed buffers are truncated.
+    pub fn write_to<W: Write + ?Sized>(&self, out: &mut W) -> Result<()> {
+        out.write_all(&[BlockType::Header as u8])?;
+        out.write_all(&self.section_length.to_be_bytes())?;
+        out.write_all(&self.start_time.to_be_bytes())?;
+        out.write_all(&self.end_time.to_be_bytes())?;
+        out.write_all(&self.endianness.f64_to_bytes(std::f64::consts::E))?;
+        out.write_all(&self.memory_used.to_be_bytes())?;
+        out.write_all(&self.scope_count.to_be_bytes())?;
+        out.write_all(&self.var_count.to_be_bytes())?;
+        out.write_all(&self.max_handle.to_be_bytes())?;
+        out.write_all(&self.vc_section_count.to_be_bytes())?;
+        out.write_all(&[self.timescale_exponent as u8])?;
+
+        let mut version = [0u8; VERSION_FIELD_LEN];
+        let mut date = [0u8; DATE_FIELD_LEN];
+        write_cstring(&mut version, &self.version);
+        write_cstring(&mut date, &self.date);
+        out.write_all(&version)?;
+        out.write_all(&date)?;
+
+        out.write_all(&[self.file_type])?;
+        out.write_all(&self.time_zero.to_be_bytes())?;
+        Ok(())
+    }
+
     /// Returns the timescale as 10^exponent seconds.
     pub fn timescale_factor(&self) -> f64 {
         10f64.powi(self.timescale_exponent as i32)
     }
 }
+
+/// Copies `value` into the NUL-terminated field `buf`, truncating at a character boundary so
+/// the stored text stays valid UTF-8.
+fn write_cstring(buf: &mut [u8], value: &str) {
+    let mut len = value.len().min(buf.len().saturating_sub(1));
+    while !value.is_char_boundary(len) {
+        len -= 1;
+    }
+    buf[..len].copy_from_slice(&value.as_bytes()[..len]);
+    buf[len] = 0;
+}
@@ -31,6 +31,31 @@ pub enum HierarchyItem {
     Var { var_index: usize },
 }
 
+/// Borrowed view of one hierarchy token, yielded by [`HierarchyBlock::walk`].
+#[derive(Debug, Clone, Copy)]
+pub enum HierarchyEvent<'a> {
+    EnterScope(&'a ScopeEntry),
+    LeaveScope,
+    Variable(&'a VarEntry),
+    Attribute(&'a AttributeEntry),
+    AttributeEnd,
+}
+
+/// Attribute type tag for miscellaneous attributes (`FST_AT_MISC`).
+pub const ATTR_TYPE_MISC: u8 = 0;
+/// Miscellaneous subtype carrying an enum value table (`FST_MT_ENUMTABLE`).
+pub const MISC_TYPE_ENUM_TABLE: u8 = 6;
+
+/// Enum value table declared through an `FST_MT_ENUMTABLE` attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumDef {
+    /// Table handle stored in the attribute argument; variables reference the table by it.
+    pub handle: u64,
+    pub name: String,
+    /// `(literal, value)` pairs in declaration order.
+    pub members: Vec<(String, String)>,
+}
+
 /// Describes a scope (module, architecture, etc.).
 #[derive(Debug, Clone)]
 pub struct ScopeEntry {
@@ -63,6 +88,21 @@ pub struct VarEntry {
     pub is_alias: bool,
 }
 
+impl VarEntry {
+    /// Reports whether the variable's type holds a two's-complement integer, for use with
+    /// [`SignalValue::as_i64`](crate::SignalValue::as_i64).
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self.var_type,
+            VarType::VcdInteger
+                | VarType::SvInt
+                | VarType::SvShortInt
+                | VarType::SvLongInt
+                | VarType::SvByte
+        )
+    }
+}
+
 impl HierarchyBlock {
     /// Decodes a hierarchy block, decompressing the payload based on the block type and parsing the
     /// token stream into structured data.
@@ -209,7 +249,112 @@ impl HierarchyBlock {
         }
     }
 
+    /// Streams the hierarchy in document order, resolving each entry of `items` to the scope,
+    /// variable, or attribute it refers to. Tokens with out-of-range indices are skipped.
+    pub fn walk(&self) -> impl Iterator<Item = HierarchyEvent<'_>> + '_ {
+        self.items.iter().filter_map(|item| match *item {
+            HierarchyItem::ScopeBegin { scope_index } => {
+                self.scopes.get(scope_index).map(HierarchyEvent::EnterScope)
+            }
+            HierarchyItem::ScopeEnd => Some(HierarchyEvent::LeaveScope),
+            HierarchyItem::AttributeBegin { attribute_index } => self
+                .attributes
+                .get(attribute_index)
+                .map(HierarchyEvent::Attribute),
+            HierarchyItem::AttributeEnd => Some(HierarchyEvent::AttributeEnd),
+            HierarchyItem::Var { var_index } => {
+                self.variables.get(var_index).map(HierarchyEvent::Variable)
+            }
+        })
+    }
+
+    /// Iterates the attributes declared directly inside the scope at `scope_index`, in document
+    /// order. Attributes of nested scopes are not included.
+    pub fn attributes_for_scope(
+        &self,
+        scope_index: usize,
+    ) -> impl Iterator<Item = &AttributeEntry> + '_ {
+        self.attributes
+            .iter()
+            .filter(move |attr| attr.scope == Some(scope_index))
+    }
+
+    /// Iterates the attributes declared outside of any scope, in document order.
+    pub fn root_attributes(&self) -> impl Iterator<Item = &AttributeEntry> + '_ {
+        self.attributes.iter().filter(|attr| attr.scope.is_none())
+    }
+
+    /// Parses every enum table attribute in the block. Table references (enum attributes with an
+    /// empty name) and malformed tables are skipped.
+    pub fn enum_definitions(&self) -> Vec<EnumDef> {
+        self.attributes
+            .iter()
+            .filter(|attr| attr.attr_type == ATTR_TYPE_MISC && attr.subtype == MISC_TYPE_ENUM_TABLE)
+            .filter_map(|attr| parse_enum_table(&attr.name, attr.argument))
+            .collect()
+    }
+
+    /// Returns the dot-separated path of the scope at `scope_index`, following `parent` links up
+    /// to the root.
+    pub fn scope_path(&self, scope_index: usize) -> Option<String> {
+        let mut names = Vec::new();
+        let mut current = Some(scope_index);
+        while let Some(idx) = current {
+            if names.len() > self.scopes.len() {
+                return None;
+            }
+            let scope = self.scopes.get(idx)?;
+            names.push(scope.name.as_str());
+            current = scope.parent;
+        }
+        names.reverse();
+        Some(names.join("."))
+    }
+
+    /// Returns the fully-qualified, dot-separated path of a variable.
+    pub fn variable_path(&self, var: &VarEntry) -> Option<String> {
+        match var.scope {
+            Some(scope_index) => {
+                let scope = self.scope_path(scope_index)?;
+                Some(format!("{scope}.{}", var.name))
+            }
+            None => Some(var.name.clone()),
+        }
+    }
+
+    /// Resolves a dot-separated path such as `top.sub.clk` to the handle of the matching
+    /// variable. Alias declarations resolve to the handle recorded for them.
+    pub fn resolve_path(&self, path: &str) -> Option<u32> {
+        self.variables
+            .iter()
+            .find(|var| self.variable_path(var).as_deref() == Some(path))
+            .map(|var| var.handle)
+    }
+
+    /// Returns the path of the variable that declares `handle`, preferring the canonical
+    /// declaration over any aliases sharing the handle.
+    pub fn handle_path(&self, handle: u32) -> Option<String> {
+        let mut matching = self.variables.iter().filter(|var| var.handle == handle);
+        let first = matching.next()?;
+        let declared = if first.is_alias {
+            self.variables
+                .iter()
+                .find(|var| var.handle == handle && !var.is_alias)
+                .unwrap_or(first)
+        } else {
+            first
+        };
+        self.variable_path(declared)
+    }
+
+    /// Parses a decompressed hierarchy stream. Errors report the offset of the record that failed.
     fn parse_stream(data: &[u8]) -> Result<Self> {
+        let mut record_start = 0usize;
+        Self::parse_records(data, &mut record_start)
+            .map_err(|err| err.at_offset(record_start as u64))
+    }
+
+    fn parse_records(data: &[u8], record_start: &mut usize) -> Result<Self> {
         let mut offset = 0usize;
         let mut scopes = Vec::new();
         let mut variables = Vec::new();
@@ -219,6 +364,7 @@ impl HierarchyBlock {
         let mut current_handle: u32 = 0;
 
         while offset < data.len() {
+            *record_start = offset;
             let tag = data[offset];
             offset += 1;
 
@@ -314,6 +460,7 @@ impl HierarchyBlock {
             items.push(HierarchyItem::Var { var_index });
         }
 
+        *record_start = offset;
         if !scope_stack.is_empty() {
             return Err(Error::decode(
                 "hierarchy stream ended with unterminated scopes",
@@ -373,6 +520,107 @@ impl HierarchyBlock {
     }
 }
 
+/// Formats an enum table the way libfst does: the table name, the member count, every literal,
+/// then every value, separated by spaces with each token escaped.
+pub(crate) fn encode_enum_table(name: &str, members: &[(String, String)]) -> String {
+    let mut out = escape_attr_token(name);
+    out.push(' ');
+    out.push_str(&members.len().to_string());
+    for token in members
+        .iter()
+        .map(|(literal, _)| literal)
+        .chain(members.iter().map(|(_, value)| value))
+    {
+        out.push(' ');
+        out.push_str(&escape_attr_token(token));
+    }
+    out
+}
+
+fn parse_enum_table(text: &str, handle: u64) -> Option<EnumDef> {
+    let mut tokens = text.split(' ');
+    let name = unescape_attr_token(tokens.next().filter(|name| !name.is_empty())?)?;
+    let count: usize = tokens.next()?.parse().ok()?;
+    let tokens = tokens
+        .map(unescape_attr_token)
+        .collect::<Option<Vec<_>>>()?;
+    if tokens.len() != count.checked_mul(2)? {
+        return None;
+    }
+    let (literals, values) = tokens.split_at(count);
+    Some(EnumDef {
+        handle,
+        name,
+        members: literals
+            .iter()
+            .cloned()
+            .zip(values.iter().cloned())
+            .collect(),
+    })
+}
+
+/// Escapes whitespace, quotes, backslashes, and non-printable bytes as libfst's
+/// `fstUtilityBinToEsc` does, so the token contains no spaces.
+fn escape_attr_token(token: &str) -> String {
+    let mut out = String::with_capacity(token.len());
+    for byte in token.bytes() {
+        match byte {
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            0x0c => out.push_str("\\f"),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x0b => out.push_str("\\v"),
+            b'\'' | b'"' | b'\\' | b'?' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            b'!'..=b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    out
+}
+
+/// Reverses [`escape_attr_token`], also accepting `\xHH` escapes.
+fn unescape_attr_token(token: &str) -> Option<String> {
+    let bytes = token.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] != b'\\' {
+            out.push(bytes[idx]);
+            idx += 1;
+            continue;
+        }
+        let escape = *bytes.get(idx + 1)?;
+        idx += 2;
+        let decoded = match escape {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => 0x0b,
+            b'x' | b'X' => {
+                let digits = std::str::from_utf8(bytes.get(idx..idx + 2)?).ok()?;
+                idx += 2;
+                u8::from_str_radix(digits, 16).ok()?
+            }
+            b'0'..=b'7' => {
+                let digits = std::str::from_utf8(bytes.get(idx - 1..idx + 2)?).ok()?;
+                idx += 2;
+                u8::from_str_radix(digits, 8).ok()?
+            }
+            other => other,
+        };
+        out.push(decoded);
+    }
+    String::from_utf8(out).ok()
+}
+
 /// Compression strategy for hierarchy blocks.
 #[derive(Debug, Clone, Copy)]
 pub enum HierarchyCompression {
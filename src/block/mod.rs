@@ -8,13 +8,18 @@ mod time;
 mod vc;
 
 pub use blackout::{BlackoutBlock, BlackoutEvent};
-pub use geom::{GeomEntry, GeomInfo};
+pub use geom::{GEOMETRY_RLE_FLAG, GEOMETRY_SHORT_REAL, GeomEntry, GeomInfo};
 pub use header::{DATE_FIELD_LEN, Header, VERSION_FIELD_LEN};
+pub(crate) use hier::encode_enum_table;
 pub use hier::{
-    AttributeEntry, HierarchyBlock, HierarchyCompression, HierarchyItem, ScopeEntry, VarEntry,
+    ATTR_TYPE_MISC, AttributeEntry, EnumDef, HierarchyBlock, HierarchyCompression, HierarchyEvent,
+    HierarchyItem, MISC_TYPE_ENUM_TABLE, ScopeEntry, VarEntry,
 };
 pub use time::TimeSection;
+pub(crate) use vc::decode_chain_index_entries_at;
 pub use vc::{
-    ChainIndexEntry, FrameEncoding, FrameSection, PackMarker, TimeEncoding, TimeTable, VcBlock,
-    encode_chain_index, encode_chain_payload, encode_frame_section, encode_time_section,
+    ChainIndexEntry, FrameEncoding, FrameSection, PackMarker, TIME_SECTION_LZ4_TAG, TimeEncoding,
+    TimeTable, VcBlock, decode_chain_index_entries, encode_chain_index,
+    encode_chain_index_dyn_alias2, encode_chain_payload, encode_chain_payload_with,
+    encode_frame_section, encode_time_section,
 };
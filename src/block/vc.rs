@@ -1,18 +1,25 @@
 #[cfg(feature = "fastlz")]
 use fastlz_sys::fastlz_compress;
 #[cfg(feature = "gzip")]
-use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+use flate2::{Compression, write::ZlibEncoder};
 #[cfg(feature = "lz4")]
 use lz4_flex::block::compress as lz4_compress;
 #[cfg(feature = "fastlz")]
 use std::ffi::c_void;
 #[cfg(feature = "gzip")]
-use std::io::{Read, Write};
+use std::io::Write;
 
+use std::borrow::Cow;
+
+use super::geom::{GeomEntry, GeomInfo};
 use super::time::TimeSection;
-use crate::encoding::{decode_varint_with_len, encode_varint};
+use crate::compression::{Compressor, decompressor_for};
+use crate::encoding::{
+    decode_n_varints, decode_sign_extended_varint, decode_varint_with_len,
+    encode_sign_extended_varint, encode_varint,
+};
 use crate::error::{Error, Result};
-use crate::types::PackType;
+use crate::types::{BlockType, Endianness, PackType, SignalValue};
 
 /// Associates a compression marker byte with a semantic [`PackType`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -85,22 +92,7 @@ impl FrameSection {
             }
             bytes
         } else {
-            #[cfg(feature = "gzip")]
-            {
-                let mut decoder = ZlibDecoder::new(&bytes[..]);
-                let mut decoded = Vec::with_capacity(expected_uncompressed);
-                decoder.read_to_end(&mut decoded)?;
-                if decoded.len() != expected_uncompressed {
-                    return Err(Error::decode("frame decompression length mismatch"));
-                }
-                decoded
-            }
-            #[cfg(not(feature = "gzip"))]
-            {
-                return Err(Error::unsupported(
-                    "frame preamble requires zlib support; enable the `gzip` feature",
-                ));
-            }
+            decompress_section(PackType::Zlib, &bytes, expected_uncompressed, "frame")?
         };
 
         Ok(Self {
@@ -113,12 +105,64 @@ impl FrameSection {
     pub fn as_slice(&self) -> &[u8] {
         &self.data
     }
+
+    /// Interprets the frame bytes using `geom`, returning the initial value of every handle the
+    /// frame covers. `Fixed(1)` handles decode to [`SignalValue::Bit`], wider ones to
+    /// [`SignalValue::Vector`] (or [`SignalValue::Bytes`] when not UTF-8), and `Real` handles to
+    /// [`SignalValue::Real`] read in `endianness` (see [`Header::endianness`](super::Header::endianness)).
+    /// `Variable` handles occupy no frame bytes and are omitted.
+    pub fn decode_values(
+        &self,
+        geom: &GeomInfo,
+        endianness: Endianness,
+    ) -> Result<Vec<(u32, SignalValue<'static>)>> {
+        let mut values = Vec::new();
+        if self.data.is_empty() {
+            return Ok(values);
+        }
+        let mut offset = 0usize;
+        for (handle, entry) in geom.handles() {
+            if u64::from(handle) > self.max_handle {
+                break;
+            }
+            let len = match entry {
+                GeomEntry::Fixed(width) => *width as usize,
+                GeomEntry::Real => 8,
+                GeomEntry::ShortReal => 4,
+                GeomEntry::Variable => continue,
+            };
+            let bytes = self.data.get(offset..offset + len).ok_or_else(|| {
+                Error::decode(format!("frame section truncated at handle {handle}"))
+            })?;
+            offset += len;
+            let value = match entry {
+                GeomEntry::Fixed(1) => SignalValue::Bit(bytes[0] as char),
+                GeomEntry::Real => {
+                    let mut buf = [0u8; 8];
+                    buf.copy_from_slice(bytes);
+                    SignalValue::Real(endianness.f64_from_bytes(buf))
+                }
+                GeomEntry::ShortReal => {
+                    let mut buf = [0u8; 4];
+                    buf.copy_from_slice(bytes);
+                    SignalValue::Real(f64::from(endianness.f32_from_bytes(buf)))
+                }
+                _ => match std::str::from_utf8(bytes) {
+                    Ok(text) => SignalValue::Vector(Cow::Owned(text.to_owned())),
+                    Err(_) => SignalValue::Bytes(Cow::Owned(bytes.to_vec())),
+                },
+            };
+            values.push((handle, value));
+        }
+        Ok(values)
+    }
 }
 
 /// Expanded time-table derived from the compressed time section.
 #[derive(Debug, Clone)]
 pub struct TimeTable {
-    /// Raw delta values decoded from the time section.
+    /// Raw delta values decoded from the time section. Empty when the table was decoded with
+    /// [`TimeTable::decode_timestamps_only`].
     pub deltas: Vec<u64>,
     /// Absolute timestamps reconstructed from the deltas.
     pub timestamps: Vec<u64>,
@@ -127,43 +171,9 @@ pub struct TimeTable {
 impl TimeTable {
     /// Decodes the time section payload into cumulative timestamps.
     pub fn decode(section: &TimeSection, bytes: Vec<u8>) -> Result<Self> {
-        let expected = usize::try_from(section.uncompressed_len)
-            .map_err(|_| Error::invalid("time table too large"))?;
-        let raw = if section.compressed_len == section.uncompressed_len {
-            if bytes.len() != expected {
-                return Err(Error::decode("time section length mismatch"));
-            }
-            bytes
-        } else {
-            #[cfg(feature = "gzip")]
-            {
-                let mut decoder = ZlibDecoder::new(&bytes[..]);
-                let mut decoded = Vec::with_capacity(expected);
-                decoder.read_to_end(&mut decoded)?;
-                if decoded.len() != expected {
-                    return Err(Error::decode("time section decompression mismatch"));
-                }
-                decoded
-            }
-            #[cfg(not(feature = "gzip"))]
-            {
-                return Err(Error::unsupported(
-                    "time section requires zlib support; enable the `gzip` feature",
-                ));
-            }
-        };
-
-        let mut deltas = Vec::with_capacity(section.item_count as usize);
-        let mut offset = 0usize;
-        while offset < raw.len() && deltas.len() < section.item_count as usize {
-            let (value, consumed) = decode_varint_with_len(&raw[offset..])?;
-            deltas.push(value);
-            offset += consumed;
-        }
-
-        if deltas.len() != section.item_count as usize {
-            return Err(Error::decode("time section item count mismatch"));
-        }
+        let raw = Self::inflate(section, bytes)?;
+        let item_count = Self::item_count(section)?;
+        let (deltas, _) = decode_n_varints(&raw, item_count)?;
 
         let mut timestamps = Vec::with_capacity(deltas.len());
         let mut acc = 0u64;
@@ -176,6 +186,72 @@ impl TimeTable {
 
         Ok(Self { deltas, timestamps })
     }
+
+    /// Like [`Self::decode`], but only keeps the absolute timestamps; `deltas` is left empty to
+    /// halve the memory of large tables.
+    pub fn decode_timestamps_only(section: &TimeSection, bytes: Vec<u8>) -> Result<Self> {
+        let raw = Self::inflate(section, bytes)?;
+        let item_count = Self::item_count(section)?;
+
+        let mut timestamps = Vec::with_capacity(item_count);
+        let mut slice = raw.as_slice();
+        let mut acc = 0u64;
+        for _ in 0..item_count {
+            let (delta, consumed) = decode_varint_with_len(slice)?;
+            slice = &slice[consumed..];
+            acc = acc
+                .checked_add(delta)
+                .ok_or_else(|| Error::decode("time delta accumulation overflow"))?;
+            timestamps.push(acc);
+        }
+
+        Ok(Self {
+            deltas: Vec::new(),
+            timestamps,
+        })
+    }
+
+    /// Returns the absolute timestamps of the block, in order.
+    pub fn timestamps(&self) -> &[u64] {
+        &self.timestamps
+    }
+
+    fn inflate(section: &TimeSection, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        let expected = usize::try_from(section.uncompressed_len)
+            .map_err(|_| Error::invalid("time table too large"))?;
+        if section.compressed_len == section.uncompressed_len {
+            if bytes.len() != expected {
+                return Err(Error::decode("time section length mismatch"));
+            }
+            Ok(bytes)
+        } else if let Some(lz4) = bytes.strip_prefix(&[TIME_SECTION_LZ4_TAG]) {
+            decompress_section(PackType::Lz4, lz4, expected, "time section")
+        } else {
+            decompress_section(PackType::Zlib, &bytes, expected, "time section")
+        }
+    }
+
+    fn item_count(section: &TimeSection) -> Result<usize> {
+        usize::try_from(section.item_count)
+            .map_err(|_| Error::decode("time section item count exceeds usize"))
+    }
+}
+
+/// Inflates a frame or time-section payload through the same backends the chain reader uses.
+fn decompress_section(
+    pack: PackType,
+    input: &[u8],
+    expected: usize,
+    what: &str,
+) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(expected);
+    decompressor_for(pack)?.decompress_exact(input, expected, &mut out)?;
+    if out.len() != expected {
+        return Err(Error::decode(format!(
+            "{what} decompression length mismatch"
+        )));
+    }
+    Ok(out)
 }
 
 /// Encoded frame payload along with metadata required by the block header.
@@ -242,11 +318,22 @@ pub struct TimeEncoding {
     pub item_count: u64,
 }
 
-/// Compresses the time table when requested, returning the serialized payload.
+/// Leading byte of an LZ4-compressed time section.
+///
+/// The on-disk format only distinguishes compressed from raw time sections by
+/// `compressed_len != uncompressed_len`, and the reference implementation always assumes zlib in
+/// the compressed case. LZ4 payloads are therefore prefixed with this tag (the LZ4 chain pack
+/// marker, `'4'`). It can never start a zlib stream: the first byte of a zlib header carries the
+/// deflate method `8` in its low nibble, whereas `'4'` is `0x34`. The tag is counted in
+/// `compressed_len`.
+pub const TIME_SECTION_LZ4_TAG: u8 = b'4';
+
+/// Compresses the time table with `pack_type` when beneficial, returning the serialized payload.
+/// Only [`PackType::None`], [`PackType::Zlib`], and [`PackType::Lz4`] are accepted.
 pub fn encode_time_section(
     time_raw: Vec<u8>,
     item_count: u64,
-    compress: bool,
+    pack_type: PackType,
     compression_level: Option<u32>,
 ) -> Result<TimeEncoding> {
     let uncompressed_len = u64::try_from(time_raw.len())
@@ -260,28 +347,53 @@ pub fn encode_time_section(
         });
     }
 
-    if compress {
-        #[cfg(feature = "gzip")]
-        {
-            let compressed = zlib_compress(&time_raw, compression_level)?;
-            if compressed.len() < time_raw.len() {
-                let compressed_len = u64::try_from(compressed.len())
-                    .map_err(|_| Error::invalid("compressed time section too large"))?;
-                return Ok(TimeEncoding {
-                    payload: compressed,
-                    uncompressed_len,
-                    compressed_len,
-                    item_count,
-                });
+    let compressed: Option<Vec<u8>> = match pack_type {
+        PackType::None => None,
+        PackType::Zlib => {
+            #[cfg(feature = "gzip")]
+            {
+                Some(zlib_compress(&time_raw, compression_level)?)
+            }
+            #[cfg(not(feature = "gzip"))]
+            {
+                let _ = compression_level;
+                return Err(Error::unsupported(
+                    "time section compression requires the `gzip` feature",
+                ));
             }
         }
-        #[cfg(not(feature = "gzip"))]
-        {
-            let _ = compression_level;
-            return Err(Error::unsupported(
-                "time section compression requires the `gzip` feature",
-            ));
+        PackType::Lz4 => {
+            #[cfg(feature = "lz4")]
+            {
+                let mut tagged = vec![TIME_SECTION_LZ4_TAG];
+                tagged.extend_from_slice(&lz4_compress(&time_raw));
+                Some(tagged)
+            }
+            #[cfg(not(feature = "lz4"))]
+            {
+                return Err(Error::unsupported(
+                    "LZ4 time section compression requires the `lz4` feature",
+                ));
+            }
+        }
+        other => {
+            return Err(Error::unsupported(format!(
+                "time sections cannot be compressed with {other:?}"
+            )));
         }
+    };
+
+    if let Some(compressed) = compressed
+        && compressed.len() < time_raw.len()
+    {
+        let compressed_len = u64::try_from(compressed.len())
+            .map_err(|_| Error::invalid("compressed time section too large"))?;
+        return Ok(TimeEncoding {
+            payload: compressed,
+            uncompressed_len,
+            compressed_len,
+            item_count,
+        });
     }
 
     Ok(TimeEncoding {
@@ -355,9 +467,49 @@ pub fn encode_chain_payload(
                 Ok((0, data))
             }
         }
+        PackType::Custom(marker) => Err(Error::unsupported(format!(
+            "custom pack marker {marker:02x} requires a registered compressor"
+        ))),
+        PackType::Zstd => {
+            #[cfg(not(feature = "zstd"))]
+            {
+                Err(Error::unsupported(
+                    "zstd compression requires the `zstd` feature",
+                ))
+            }
+            #[cfg(feature = "zstd")]
+            {
+                let level = compression_level.map(|v| v.min(22) as i32).unwrap_or(3);
+                let compressed = zstd::bulk::compress(&data, level)?;
+                if compressed.len() < data.len() {
+                    return Ok((raw_len, compressed));
+                }
+                Ok((0, data))
+            }
+        }
     }
 }
 
+/// Encodes a chain payload through a user-supplied [`Compressor`], keeping the raw bytes when the
+/// compressed form is not smaller.
+pub fn encode_chain_payload_with(
+    compressor: &mut dyn Compressor,
+    data: Vec<u8>,
+) -> Result<(u64, Vec<u8>)> {
+    let raw_len = u64::try_from(data.len())
+        .map_err(|_| Error::invalid("chain payload exceeds supported length"))?;
+    if data.is_empty() {
+        return Ok((0, data));
+    }
+    let mut compressed = Vec::new();
+    compressor.compress(&data, &mut compressed)?;
+    compressor.flush(&mut compressed)?;
+    if compressed.len() < data.len() {
+        return Ok((raw_len, compressed));
+    }
+    Ok((0, data))
+}
+
 /// Entry describing the chain index layout for a single handle.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChainIndexEntry {
@@ -429,6 +581,167 @@ pub fn encode_chain_index(entries: &[ChainIndexEntry]) -> Result<Vec<u8>> {
     Ok(index_bytes)
 }
 
+/// Serializes the chain index table in the `FST_BL_VCDATA_DYN_ALIAS2` layout: each data entry is
+/// a sign-extended varint `(delta << 1) | 1` of its offset, each alias a `(-target << 1) | 1`,
+/// and an alias repeating the previous alias target a bare `1`. Runs of empty handles are stored
+/// as unsigned `run << 1` varints, as in the legacy layout.
+pub fn encode_chain_index_dyn_alias2(entries: &[ChainIndexEntry]) -> Result<Vec<u8>> {
+    const PACK_MARKER_PREFIX: u64 = 1;
+
+    let mut index_bytes = Vec::new();
+    let mut empty_run = 0u64;
+    let mut last_offset = 0u64;
+    let mut last_alias: Option<u32> = None;
+
+    for entry in entries {
+        if !matches!(entry, ChainIndexEntry::Empty) && empty_run > 0 {
+            encode_varint(empty_run << 1, &mut index_bytes);
+            empty_run = 0;
+        }
+        match entry {
+            ChainIndexEntry::Empty => empty_run += 1,
+            ChainIndexEntry::Data { offset } => {
+                let absolute = PACK_MARKER_PREFIX
+                    .checked_add(*offset)
+                    .ok_or_else(|| Error::invalid("chain offset overflowed pack marker base"))?;
+                let delta = absolute
+                    .checked_sub(last_offset)
+                    .filter(|delta| *delta > 0)
+                    .and_then(|delta| i64::try_from(delta).ok())
+                    .filter(|delta| *delta <= i64::MAX >> 1)
+                    .ok_or_else(|| Error::invalid("chain offsets must be strictly increasing"))?;
+                encode_sign_extended_varint((delta << 1) | 1, &mut index_bytes);
+                last_offset = absolute;
+                last_alias = None;
+            }
+            ChainIndexEntry::Alias { target } => {
+                if *target == 0 {
+                    return Err(Error::invalid("alias handle must be greater than zero"));
+                }
+                let shval = if last_alias == Some(*target) {
+                    0
+                } else {
+                    -i64::from(*target)
+                };
+                encode_sign_extended_varint((shval << 1) | 1, &mut index_bytes);
+                last_alias = Some(*target);
+            }
+        }
+    }
+
+    if empty_run > 0 {
+        encode_varint(empty_run << 1, &mut index_bytes);
+    }
+
+    Ok(index_bytes)
+}
+
+/// Parses a chain index table written by [`encode_chain_index`] or
+/// [`encode_chain_index_dyn_alias2`]. `VcDataDynAlias2` uses signed deltas with run-length alias
+/// repeats; `VcData` and `VcDataDynAlias` share the unsigned layout, where `0, n` aliases the
+/// entry to handle `n` (as libfst's reader does for both tags). Data offsets are returned
+/// relative to the chain buffer, as passed to the encoders.
+pub fn decode_chain_index_entries(
+    block_type: BlockType,
+    bytes: &[u8],
+) -> Result<Vec<ChainIndexEntry>> {
+    decode_chain_index_entries_at(block_type, bytes, 0)
+}
+
+/// Like [`decode_chain_index_entries`], with errors located at `base` plus the offset of the
+/// entry that failed within `bytes`.
+pub(crate) fn decode_chain_index_entries_at(
+    block_type: BlockType,
+    bytes: &[u8],
+    base: u64,
+) -> Result<Vec<ChainIndexEntry>> {
+    let mut entry_start = 0usize;
+    decode_entries(block_type, bytes, &mut entry_start)
+        .map_err(|err| err.at_offset(base + entry_start as u64))
+}
+
+fn decode_entries(
+    block_type: BlockType,
+    bytes: &[u8],
+    entry_start: &mut usize,
+) -> Result<Vec<ChainIndexEntry>> {
+    const PACK_MARKER_PREFIX: u64 = 1;
+
+    let data_entry = |absolute: u64| -> Result<ChainIndexEntry> {
+        let offset = absolute
+            .checked_sub(PACK_MARKER_PREFIX)
+            .ok_or_else(|| Error::decode("chain offset precedes pack marker"))?;
+        Ok(ChainIndexEntry::Data { offset })
+    };
+    let alias_entry = |target: u64| -> Result<ChainIndexEntry> {
+        let target = u32::try_from(target)
+            .ok()
+            .filter(|target| *target > 0)
+            .ok_or_else(|| Error::decode("invalid alias target"))?;
+        Ok(ChainIndexEntry::Alias { target })
+    };
+
+    let mut entries = Vec::new();
+    let mut slice = bytes;
+    let mut last_offset = 0u64;
+    let mut last_alias_target: Option<u64> = None;
+
+    while !slice.is_empty() {
+        *entry_start = bytes.len() - slice.len();
+        if block_type == BlockType::VcDataDynAlias2 && (slice[0] & 0x01) != 0 {
+            let raw = decode_sign_extended_varint(&mut slice)?;
+            let shval = raw >> 1;
+
+            if shval > 0 {
+                last_offset = last_offset
+                    .checked_add(shval as u64)
+                    .ok_or_else(|| Error::decode("chain index overflow"))?;
+                entries.push(data_entry(last_offset)?);
+                last_alias_target = None;
+            } else if shval < 0 {
+                let target = shval.unsigned_abs();
+                entries.push(alias_entry(target)?);
+                last_alias_target = Some(target);
+            } else if let Some(target) = last_alias_target {
+                entries.push(alias_entry(target)?);
+            } else {
+                entries.push(ChainIndexEntry::Empty);
+            }
+            continue;
+        }
+
+        let (value, consumed) = decode_varint_with_len(slice)?;
+        slice = &slice[consumed..];
+
+        if value == 0 {
+            let (alias, alias_consumed) = decode_varint_with_len(slice)?;
+            slice = &slice[alias_consumed..];
+            if alias == 0 {
+                entries.push(ChainIndexEntry::Empty);
+                last_alias_target = None;
+            } else {
+                entries.push(alias_entry(alias)?);
+                last_alias_target = Some(alias);
+            }
+            continue;
+        }
+
+        if (value & 1) == 0 {
+            let repeat = (value >> 1) as usize;
+            entries.extend(std::iter::repeat_n(ChainIndexEntry::Empty, repeat));
+            continue;
+        }
+
+        last_offset = last_offset
+            .checked_add(value >> 1)
+            .ok_or_else(|| Error::decode("chain index overflow"))?;
+        entries.push(data_entry(last_offset)?);
+        last_alias_target = None;
+    }
+
+    Ok(entries)
+}
+
 #[cfg(feature = "gzip")]
 fn zlib_compress(input: &[u8], level: Option<u32>) -> Result<Vec<u8>> {
     let lvl = level.map(|v| v.min(9)).unwrap_or(6);
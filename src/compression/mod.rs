@@ -2,7 +2,11 @@
 
 //! Compression backends used by value change and hierarchy blocks.
 
-use crate::error::Result;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::types::PackType;
 
 /// Trait implemented by compression algorithms used when writing FST data.
 pub trait Compressor {
@@ -15,6 +19,96 @@ pub trait Compressor {
 /// Trait implemented by decompression algorithms used when reading FST data.
 pub trait Decompressor {
     fn decompress(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<()>;
+
+    /// Decompresses a payload whose decoded length is recorded alongside it, as for chains.
+    /// Codecs whose streams do not carry their own length override this; the default defers to
+    /// [`Self::decompress`]. Callers still verify the resulting length.
+    fn decompress_exact(
+        &mut self,
+        input: &[u8],
+        expected_len: usize,
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        let _ = expected_len;
+        self.decompress(input, output)
+    }
+}
+
+type CompressorFn = dyn Fn() -> Box<dyn Compressor> + Send + Sync;
+type DecompressorFn = dyn Fn() -> Box<dyn Decompressor> + Send + Sync;
+
+/// User-supplied chain compressor stored under a custom pack marker byte.
+#[derive(Clone)]
+pub struct CustomCompressor {
+    marker: u8,
+    factory: Arc<CompressorFn>,
+}
+
+impl CustomCompressor {
+    /// Registers `factory` under `marker`. The marker must not collide with a built-in one.
+    pub fn new<F>(marker: u8, factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn Compressor> + Send + Sync + 'static,
+    {
+        Self {
+            marker,
+            factory: Arc::new(factory),
+        }
+    }
+
+    pub fn marker(&self) -> u8 {
+        self.marker
+    }
+
+    /// Creates a fresh compressor instance for one chain.
+    pub fn create(&self) -> Box<dyn Compressor> {
+        (self.factory)()
+    }
+}
+
+impl fmt::Debug for CustomCompressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomCompressor")
+            .field("marker", &self.marker)
+            .finish_non_exhaustive()
+    }
+}
+
+/// User-supplied chain decompressor dispatched by pack marker byte.
+#[derive(Clone)]
+pub struct CustomDecompressor {
+    marker: u8,
+    factory: Arc<DecompressorFn>,
+}
+
+impl CustomDecompressor {
+    /// Registers `factory` for blocks whose pack marker equals `marker`.
+    pub fn new<F>(marker: u8, factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn Decompressor> + Send + Sync + 'static,
+    {
+        Self {
+            marker,
+            factory: Arc::new(factory),
+        }
+    }
+
+    pub fn marker(&self) -> u8 {
+        self.marker
+    }
+
+    /// Creates a fresh decompressor instance for one chain.
+    pub fn create(&self) -> Box<dyn Decompressor> {
+        (self.factory)()
+    }
+}
+
+impl fmt::Debug for CustomDecompressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomDecompressor")
+            .field("marker", &self.marker)
+            .finish_non_exhaustive()
+    }
 }
 
 /// No-op compressor used when compression is disabled.
@@ -88,7 +182,7 @@ pub use gzip::{CompressorImpl as ZlibCompressor, DecompressorImpl as ZlibDecompr
 mod lz4 {
     use super::{Compressor, Decompressor};
     use crate::error::{Error, Result};
-    use lz4_flex::block::{compress_prepend_size, decompress_size_prepended};
+    use lz4_flex::block::{compress_prepend_size, decompress, decompress_size_prepended};
 
     #[derive(Debug, Default)]
     pub struct Lz4Compressor;
@@ -113,8 +207,176 @@ mod lz4 {
         }
     }
 
+    /// Raw LZ4 blocks without a size prefix, as stored in FST chains; the decoded length must be
+    /// supplied through [`Decompressor::decompress_exact`].
+    #[derive(Debug, Default)]
+    pub struct Lz4BlockDecompressor;
+
+    impl Decompressor for Lz4BlockDecompressor {
+        fn decompress(&mut self, _input: &[u8], _output: &mut Vec<u8>) -> Result<()> {
+            Err(Error::unsupported(
+                "raw lz4 blocks require their decoded length",
+            ))
+        }
+
+        fn decompress_exact(
+            &mut self,
+            input: &[u8],
+            expected_len: usize,
+            output: &mut Vec<u8>,
+        ) -> Result<()> {
+            let decompressed =
+                decompress(input, expected_len).map_err(|err| Error::decode(err.to_string()))?;
+            output.extend_from_slice(&decompressed);
+            Ok(())
+        }
+    }
+
     pub use {Lz4Compressor as CompressorImpl, Lz4Decompressor as DecompressorImpl};
 }
 
 #[cfg(feature = "lz4")]
-pub use lz4::{CompressorImpl as Lz4Compressor, DecompressorImpl as Lz4Decompressor};
+pub use lz4::{
+    CompressorImpl as Lz4Compressor, DecompressorImpl as Lz4Decompressor, Lz4BlockDecompressor,
+};
+
+#[cfg(feature = "fastlz")]
+mod fastlz {
+    use super::Decompressor;
+    use crate::error::{Error, Result};
+    use fastlz_sys::fastlz_decompress;
+
+    /// FastLZ payloads; the decoded length must be supplied through
+    /// [`Decompressor::decompress_exact`].
+    #[derive(Debug, Default)]
+    pub struct FastLzDecompressor;
+
+    impl Decompressor for FastLzDecompressor {
+        fn decompress(&mut self, _input: &[u8], _output: &mut Vec<u8>) -> Result<()> {
+            Err(Error::unsupported(
+                "fastlz payloads require their decoded length",
+            ))
+        }
+
+        fn decompress_exact(
+            &mut self,
+            input: &[u8],
+            expected_len: usize,
+            output: &mut Vec<u8>,
+        ) -> Result<()> {
+            let input_len = i32::try_from(input.len())
+                .map_err(|_| Error::decode("fastlz input length exceeds i32 range"))?;
+            let expected_len_i32 = i32::try_from(expected_len)
+                .map_err(|_| Error::decode("fastlz output length exceeds i32 range"))?;
+            let mut out = vec![0u8; expected_len];
+            let written = unsafe {
+                fastlz_decompress(
+                    input.as_ptr() as *const _,
+                    input_len,
+                    out.as_mut_ptr() as *mut _,
+                    expected_len_i32,
+                )
+            };
+            if written <= 0 {
+                return Err(Error::decode("fastlz decompression failed"));
+            }
+            let written = usize::try_from(written)
+                .map_err(|_| Error::decode("fastlz decoded length exceeds addressable memory"))?;
+            output.extend_from_slice(&out[..written.min(expected_len)]);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "fastlz")]
+pub use fastlz::FastLzDecompressor;
+
+#[cfg(feature = "zstd")]
+mod zstd_codec {
+    use super::Decompressor;
+    use crate::error::{Error, Result};
+
+    #[derive(Debug, Default)]
+    pub struct ZstdDecompressor;
+
+    impl Decompressor for ZstdDecompressor {
+        fn decompress(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+            output.extend_from_slice(&zstd::stream::decode_all(input)?);
+            Ok(())
+        }
+
+        fn decompress_exact(
+            &mut self,
+            input: &[u8],
+            expected_len: usize,
+            output: &mut Vec<u8>,
+        ) -> Result<()> {
+            let decompressed = zstd::bulk::decompress(input, expected_len)
+                .map_err(|err| Error::decode(err.to_string()))?;
+            output.extend_from_slice(&decompressed);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "zstd")]
+pub use zstd_codec::ZstdDecompressor;
+
+/// Returns the built-in decompressor for chains stored under `pack`. Codecs compiled out by a
+/// disabled feature, and [`PackType::Custom`] markers, are reported as unsupported.
+pub fn decompressor_for(pack: PackType) -> Result<Box<dyn Decompressor>> {
+    match pack {
+        PackType::None => Ok(Box::new(NullDecompressor)),
+        PackType::Zlib => {
+            #[cfg(feature = "gzip")]
+            {
+                Ok(Box::new(ZlibDecompressor))
+            }
+            #[cfg(not(feature = "gzip"))]
+            {
+                Err(Error::unsupported(
+                    "zlib decompression requires the `gzip` feature",
+                ))
+            }
+        }
+        PackType::Lz4 => {
+            #[cfg(feature = "lz4")]
+            {
+                Ok(Box::new(Lz4BlockDecompressor))
+            }
+            #[cfg(not(feature = "lz4"))]
+            {
+                Err(Error::unsupported(
+                    "lz4 decompression requires the `lz4` feature",
+                ))
+            }
+        }
+        PackType::FastLz => {
+            #[cfg(feature = "fastlz")]
+            {
+                Ok(Box::new(FastLzDecompressor))
+            }
+            #[cfg(not(feature = "fastlz"))]
+            {
+                Err(Error::unsupported(
+                    "fastlz decompression requires the `fastlz` feature",
+                ))
+            }
+        }
+        PackType::Zstd => {
+            #[cfg(feature = "zstd")]
+            {
+                Ok(Box::new(ZstdDecompressor))
+            }
+            #[cfg(not(feature = "zstd"))]
+            {
+                Err(Error::unsupported(
+                    "zstd decompression requires the `zstd` feature",
+                ))
+            }
+        }
+        PackType::Custom(marker) => Err(Error::unsupported(format!(
+            "custom pack marker {marker:02x} requires a registered decompressor"
+        ))),
+    }
+}
@@ -0,0 +1,34 @@
+/// Returns the byte index and in-byte shift of bit `index` in a packed buffer. With `msb_first`,
+/// bit 0 lands in the most significant bit of byte 0 (the order FST uses for two-state vectors);
+/// otherwise it lands in the least significant bit.
+#[inline]
+pub(crate) fn bit_position(index: usize, msb_first: bool) -> (usize, u32) {
+    let offset = (index % 8) as u32;
+    let shift = if msb_first { 7 - offset } else { offset };
+    (index / 8, shift)
+}
+
+/// Packs `bits` eight per byte into `bits.len().div_ceil(8)` bytes, padding the final byte with
+/// zeros. Packing with `msb_first` produces the payload layout of
+/// [`SignalValue::PackedBits`](crate::types::SignalValue::PackedBits).
+pub fn pack_bits(bits: &[bool], msb_first: bool) -> Vec<u8> {
+    let mut out = vec![0u8; bits.len().div_ceil(8)];
+    for (index, _) in bits.iter().enumerate().filter(|(_, bit)| **bit) {
+        let (byte, shift) = bit_position(index, msb_first);
+        out[byte] |= 1 << shift;
+    }
+    out
+}
+
+/// Expands the first `width` bits of `bytes`, the inverse of [`pack_bits`] with the same
+/// `msb_first`. Bits past the end of `bytes` read as `false`.
+pub fn unpack_bits(bytes: &[u8], width: u32, msb_first: bool) -> Vec<bool> {
+    (0..width as usize)
+        .map(|index| {
+            let (byte, shift) = bit_position(index, msb_first);
+            bytes
+                .get(byte)
+                .is_some_and(|value| (value >> shift) & 1 == 1)
+        })
+        .collect()
+}
@@ -1,7 +1,15 @@
 //! Encoding helpers (varints, zig-zag encoding, etc.).
 
+mod bits;
 mod varint;
 mod varint_signed;
 
-pub use varint::{VARINT_MAX_LEN, decode_varint, decode_varint_with_len, encode_varint};
-pub use varint_signed::{decode_svarint, encode_svarint};
+pub(crate) use bits::bit_position;
+pub use bits::{pack_bits, unpack_bits};
+pub use varint::{
+    VARINT_MAX_LEN, decode_n_varints, decode_varint, decode_varint_with_len, decode_varints_into,
+    encode_varint, varint_len,
+};
+pub use varint_signed::{
+    decode_sign_extended_varint, decode_svarint, encode_sign_extended_varint, encode_svarint,
+};
@@ -20,6 +20,12 @@ pub fn encode_varint(mut value: u64, out: &mut Vec<u8>) -> usize {
     out.len() - start_len
 }
 
+/// Returns the number of bytes [`encode_varint`] would emit for `value`.
+pub fn varint_len(value: u64) -> usize {
+    let bits = 64 - (value | 1).leading_zeros() as usize;
+    bits.div_ceil(7)
+}
+
 /// Decodes a u64 varint from the provided byte slice, advancing the slice on success.
 pub fn decode_varint(input: &mut &[u8]) -> Result<u64> {
     let mut value = 0u64;
@@ -55,3 +61,45 @@ pub fn decode_varint_with_len(input: &[u8]) -> Result<(u64, usize)> {
     }
     Err(Error::decode("varint exceeds maximum length"))
 }
+
+/// Decodes consecutive varints until `input` is exhausted, appending them to `out`, and returns
+/// the number of bytes consumed. A varint cut off by the end of `input` is an error.
+pub fn decode_varints_into(input: &[u8], out: &mut Vec<u64>) -> Result<usize> {
+    let mut offset = 0usize;
+    while let Some(&byte) = input.get(offset) {
+        if byte & 0x80 == 0 {
+            out.push(u64::from(byte));
+            offset += 1;
+            continue;
+        }
+        let (value, consumed) = decode_varint_with_len(&input[offset..])?;
+        out.push(value);
+        offset += consumed;
+    }
+    Ok(offset)
+}
+
+/// Decodes exactly `n` varints from the start of `input`, returning them with the number of bytes
+/// consumed. Trailing bytes are left untouched; running out of input before `n` values is an error.
+pub fn decode_n_varints(input: &[u8], n: usize) -> Result<(Vec<u64>, usize)> {
+    // Every varint occupies at least one byte, so `input.len()` bounds the allocation.
+    let mut values = Vec::with_capacity(n.min(input.len()));
+    let mut offset = 0usize;
+    while values.len() < n {
+        let Some(&byte) = input.get(offset) else {
+            return Err(Error::decode(format!(
+                "expected {n} varints, input ended after {}",
+                values.len()
+            )));
+        };
+        if byte & 0x80 == 0 {
+            values.push(u64::from(byte));
+            offset += 1;
+            continue;
+        }
+        let (value, consumed) = decode_varint_with_len(&input[offset..])?;
+        values.push(value);
+        offset += consumed;
+    }
+    Ok((values, offset))
+}
@@ -1,5 +1,5 @@
 use crate::encoding::varint::{decode_varint, encode_varint};
-use crate::error::Result;
+use crate::error::{Error, Result};
 
 /// Encodes a signed integer using ZigZag + varint encoding.
 pub fn encode_svarint(value: i64, out: &mut Vec<u8>) -> usize {
@@ -14,3 +14,46 @@ pub fn decode_svarint(input: &mut &[u8]) -> Result<i64> {
     let sign = (raw & 1) as i64;
     Ok(magnitude ^ -sign)
 }
+
+/// Encodes a signed integer the way libfst's `fstWriterSVarint` does: little-endian base-128
+/// groups, stopping once the remaining bits are pure sign extension of bit 6 of the last group.
+pub fn encode_sign_extended_varint(mut value: i64, out: &mut Vec<u8>) -> usize {
+    let start = out.len();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return out.len() - start;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Decodes a signed varint as written by libfst (`fstGetSVarint64`): little-endian base-128
+/// groups whose final group is sign-extended from bit 6. This is the encoding used by
+/// `FST_BL_VCDATA_DYN_ALIAS2` chain indices and is unrelated to the ZigZag form above.
+pub fn decode_sign_extended_varint(input: &mut &[u8]) -> Result<i64> {
+    let mut value = 0i64;
+    let mut shift = 0u32;
+    loop {
+        let Some((&byte, rest)) = input.split_first() else {
+            return Err(Error::decode(
+                "unexpected end of input while decoding signed varint",
+            ));
+        };
+        *input = rest;
+        if shift >= 64 {
+            return Err(Error::decode("signed varint exceeds maximum length"));
+        }
+        value |= i64::from(byte & 0x7f) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                value |= -1i64 << shift;
+            }
+            return Ok(value);
+        }
+    }
+}
@@ -22,6 +22,16 @@ pub enum Error {
     /// A generic decoding failure.
     #[error("decode error: {0}")]
     Decode(String),
+
+    /// A decoding failure located at `offset`: an absolute file position for value-change blocks,
+    /// or a position inside the decompressed stream for hierarchy records.
+    #[error("decode error at offset {offset}: {message}")]
+    DecodeAt {
+        /// Description of the failure.
+        message: String,
+        /// Byte offset of the record or field that failed to decode.
+        offset: u64,
+    },
 }
 
 impl Error {
@@ -36,4 +46,30 @@ impl Error {
     pub(crate) fn decode<T: fmt::Display>(msg: T) -> Self {
         Self::Decode(msg.to_string())
     }
+
+    pub(crate) fn decode_at<T: fmt::Display>(msg: T, offset: u64) -> Self {
+        Self::DecodeAt {
+            message: msg.to_string(),
+            offset,
+        }
+    }
+
+    /// Attaches `offset` to data errors that do not carry a position yet. I/O, unsupported, and
+    /// already located errors are returned unchanged.
+    pub(crate) fn at_offset(self, offset: u64) -> Self {
+        match self {
+            Self::InvalidData(message) | Self::Decode(message) => {
+                Self::DecodeAt { message, offset }
+            }
+            other => other,
+        }
+    }
+
+    /// Returns the byte offset at which decoding failed, when known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::DecodeAt { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
 }
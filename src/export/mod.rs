@@ -0,0 +1,3 @@
+//! Converters that re-emit parsed FST traces in other waveform formats.
+
+pub mod vcd;
@@ -0,0 +1,249 @@
+//! Value Change Dump (IEEE 1364 §18) export.
+
+use std::collections::HashSet;
+use std::io::Write;
+
+use crate::block::{HierarchyBlock, HierarchyItem, VarEntry};
+use crate::error::{Error, Result};
+use crate::io::ReadSeek;
+use crate::reader::FstReader;
+use crate::types::{ScopeType, SignalValue, VarType};
+use crate::util::unpack_to_ascii;
+
+/// Writes the remaining contents of `reader` to `out` as a VCD file.
+///
+/// The declaration section is rebuilt from the hierarchy block and followed by a `$dumpvars`
+/// section holding each signal's value at the first block's begin time, as reported by
+/// [`FstReader::initial_values`]. Every value change still ahead of the reader is then emitted in
+/// timestamp order, leaving out the changes already covered by `$dumpvars`. Aliases share the
+/// identifier code of their canonical signal, so only canonical changes are written.
+pub fn write_vcd<R: ReadSeek, W: Write>(reader: &mut FstReader<R>, out: &mut W) -> Result<()> {
+    // libfst places the hierarchy after the value-change blocks; the index scan loads any
+    // trailing metadata and restores the stream position.
+    let index = reader.build_block_index()?;
+    let initial = reader.initial_values()?;
+    let hierarchy = reader
+        .hierarchy()
+        .ok_or_else(|| Error::invalid("VCD export requires a hierarchy block"))?
+        .clone();
+    let header = reader.header();
+
+    if !header.date.is_empty() {
+        writeln!(out, "$date\n\t{}\n$end", header.date)?;
+    }
+    if !header.version.is_empty() {
+        writeln!(out, "$version\n\t{}\n$end", header.version)?;
+    }
+    writeln!(
+        out,
+        "$timescale\n\t{}\n$end",
+        timescale(header.timescale_exponent)
+    )?;
+    write_declarations(&hierarchy, out)?;
+    writeln!(out, "$enddefinitions $end")?;
+
+    let mut last_time = None;
+    let mut dumped = HashSet::new();
+    if let Some(first) = index.first() {
+        let time_zero = if reader.options().apply_time_zero {
+            header.time_zero
+        } else {
+            0
+        };
+        let begin_time = first.begin_time.saturating_add(time_zero);
+        let declared: HashSet<u32> = hierarchy
+            .variables
+            .iter()
+            .map(|var| var.alias_of.unwrap_or(var.handle))
+            .collect();
+        writeln!(out, "#{begin_time}\n$dumpvars")?;
+        for (handle, value) in initial
+            .iter()
+            .filter(|(handle, _)| declared.contains(handle))
+        {
+            write_value(out, value, &identifier_code(*handle))?;
+            dumped.insert(*handle);
+        }
+        writeln!(out, "$end")?;
+        last_time = Some(begin_time);
+    }
+    let dumped_time = last_time;
+
+    for change in reader.all_value_changes()? {
+        let change = change?;
+        if change.alias_of.is_some()
+            || (Some(change.timestamp) == dumped_time && dumped.contains(&change.handle))
+        {
+            continue;
+        }
+        if last_time != Some(change.timestamp) {
+            writeln!(out, "#{}", change.timestamp)?;
+            last_time = Some(change.timestamp);
+        }
+        write_value(out, &change.value, &identifier_code(change.handle))?;
+    }
+    Ok(())
+}
+
+fn write_declarations<W: Write>(hierarchy: &HierarchyBlock, out: &mut W) -> Result<()> {
+    for item in &hierarchy.items {
+        match item {
+            HierarchyItem::ScopeBegin { scope_index } => {
+                let scope = &hierarchy.scopes[*scope_index];
+                writeln!(
+                    out,
+                    "$scope {} {} $end",
+                    scope_keyword(scope.scope_type),
+                    scope.name
+                )?;
+            }
+            HierarchyItem::ScopeEnd => writeln!(out, "$upscope $end")?,
+            HierarchyItem::Var { var_index } => {
+                write_var(out, &hierarchy.variables[*var_index])?;
+            }
+            HierarchyItem::AttributeBegin { .. } | HierarchyItem::AttributeEnd => {}
+        }
+    }
+    Ok(())
+}
+
+fn write_var<W: Write>(out: &mut W, var: &VarEntry) -> Result<()> {
+    let width = var.length.unwrap_or(match var.var_type {
+        VarType::VcdReal | VarType::VcdRealParameter | VarType::VcdRealtime => 64,
+        VarType::SvShortReal => 32,
+        _ => 1,
+    });
+    let canonical = var.alias_of.unwrap_or(var.handle);
+    writeln!(
+        out,
+        "$var {} {} {} {} $end",
+        var_keyword(var.var_type),
+        width,
+        identifier_code(canonical),
+        var.name
+    )?;
+    Ok(())
+}
+
+fn write_value<W: Write>(out: &mut W, value: &SignalValue<'_>, id: &str) -> Result<()> {
+    match value {
+        SignalValue::Bit(ch) => writeln!(out, "{ch}{id}")?,
+        SignalValue::Vector(bits) => writeln!(out, "b{bits} {id}")?,
+        SignalValue::PackedBits { width, bits } => {
+            let text = unpack_to_ascii(bits, *width)
+                .ok_or_else(|| Error::decode("packed vector payload too short"))?;
+            out.write_all(b"b")?;
+            out.write_all(&text)?;
+            writeln!(out, " {id}")?;
+        }
+        SignalValue::Real(v) => writeln!(out, "r{v} {id}")?,
+        SignalValue::Bytes(bytes) => {
+            let mut text = String::with_capacity(bytes.len());
+            for &byte in bytes.iter() {
+                if byte.is_ascii_graphic() && byte != b'\\' {
+                    text.push(byte as char);
+                } else {
+                    text.push_str(&format!("\\x{byte:02x}"));
+                }
+            }
+            writeln!(out, "s{text} {id}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Maps a 1-based handle onto the printable `!`..`~` identifier alphabet.
+fn identifier_code(handle: u32) -> String {
+    let mut value = handle.saturating_sub(1);
+    let mut code = String::new();
+    loop {
+        code.push((b'!' + (value % 94) as u8) as char);
+        value /= 94;
+        if value == 0 {
+            break;
+        }
+        value -= 1;
+    }
+    code
+}
+
+fn timescale(exponent: i8) -> String {
+    let exponent = i32::from(exponent);
+    let unit_exponent = (exponent.div_euclid(3) * 3).clamp(-15, 0);
+    let unit = match unit_exponent {
+        0 => "s",
+        -3 => "ms",
+        -6 => "us",
+        -9 => "ns",
+        -12 => "ps",
+        _ => "fs",
+    };
+    let magnitude = 10u64.pow((exponent - unit_exponent).clamp(0, 18) as u32);
+    format!("{magnitude}{unit}")
+}
+
+pub(crate) fn scope_keyword(scope_type: ScopeType) -> &'static str {
+    match scope_type {
+        ScopeType::VcdModule => "module",
+        ScopeType::VcdTask => "task",
+        ScopeType::VcdFunction => "function",
+        ScopeType::VcdBegin => "begin",
+        ScopeType::VcdFork => "fork",
+        ScopeType::VcdGenerate => "generate",
+        ScopeType::VcdStruct => "struct",
+        ScopeType::VcdUnion => "union",
+        ScopeType::VcdClass => "class",
+        ScopeType::VcdInterface => "interface",
+        ScopeType::VcdPackage => "package",
+        ScopeType::VcdProgram => "program",
+        ScopeType::VhdlArchitecture => "vhdl_architecture",
+        ScopeType::VhdlProcedure => "vhdl_procedure",
+        ScopeType::VhdlFunction => "vhdl_function",
+        ScopeType::VhdlRecord => "vhdl_record",
+        ScopeType::VhdlProcess => "vhdl_process",
+        ScopeType::VhdlBlock => "vhdl_block",
+        ScopeType::VhdlForGenerate => "vhdl_for_generate",
+        ScopeType::VhdlIfGenerate => "vhdl_if_generate",
+        ScopeType::VhdlGenerate => "vhdl_generate",
+        ScopeType::VhdlPackage => "vhdl_package",
+        ScopeType::GenAttrBegin
+        | ScopeType::GenAttrEnd
+        | ScopeType::VcdScope
+        | ScopeType::VcdUpscope => "module",
+    }
+}
+
+pub(crate) fn var_keyword(var_type: VarType) -> &'static str {
+    match var_type {
+        VarType::VcdEvent => "event",
+        VarType::VcdInteger => "integer",
+        VarType::VcdParameter => "parameter",
+        VarType::VcdReal => "real",
+        VarType::VcdRealParameter => "real_parameter",
+        VarType::VcdReg => "reg",
+        VarType::VcdSupply0 => "supply0",
+        VarType::VcdSupply1 => "supply1",
+        VarType::VcdTime => "time",
+        VarType::VcdTri => "tri",
+        VarType::VcdTriand => "triand",
+        VarType::VcdTrior => "trior",
+        VarType::VcdTrireg => "trireg",
+        VarType::VcdTri0 => "tri0",
+        VarType::VcdTri1 => "tri1",
+        VarType::VcdWand => "wand",
+        VarType::VcdWire => "wire",
+        VarType::VcdWor => "wor",
+        VarType::VcdPort => "port",
+        VarType::VcdSparseArray => "sparray",
+        VarType::VcdRealtime => "realtime",
+        VarType::GenString => "string",
+        VarType::SvBit => "bit",
+        VarType::SvLogic => "logic",
+        VarType::SvInt => "int",
+        VarType::SvShortInt => "shortint",
+        VarType::SvLongInt => "longint",
+        VarType::SvByte => "byte",
+        VarType::SvEnum => "enum",
+        VarType::SvShortReal => "shortreal",
+    }
+}
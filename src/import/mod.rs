@@ -0,0 +1,3 @@
+//! Converters that build FST traces from other waveform formats.
+
+pub mod vcd;
@@ -0,0 +1,271 @@
+//! Value Change Dump (IEEE 1364 §18) import.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::io::BufRead;
+
+use crate::block::{GeomEntry, Header};
+use crate::error::{Error, Result};
+use crate::export::vcd::{scope_keyword, var_keyword};
+use crate::io::WriteSeek;
+use crate::types::{ScopeType, SignalValue, VarDir, VarType};
+use crate::writer::FstWriter;
+
+/// Parses a VCD stream and replays it into `writer`.
+///
+/// Declarations are turned into scopes and variables (repeated identifier codes become aliases),
+/// the header is written once `$enddefinitions` is reached, and the remaining value changes are
+/// streamed into [`FstWriter::emit_change`]. `$dumpoff`/`$dumpon` are recorded as blackout events.
+/// The writer is left open so the caller decides when to [`FstWriter::finish`] it.
+pub fn read_vcd<R: BufRead, W: WriteSeek>(input: R, writer: &mut FstWriter<W>) -> Result<()> {
+    let mut tokens = Tokens::new(input);
+    let signals = read_declarations(&mut tokens, writer)?;
+    read_changes(&mut tokens, writer, &signals)
+}
+
+struct Signal {
+    handle: u32,
+    geometry: GeomEntry,
+}
+
+fn read_declarations<R: BufRead, W: WriteSeek>(
+    tokens: &mut Tokens<R>,
+    writer: &mut FstWriter<W>,
+) -> Result<HashMap<String, Signal>> {
+    let mut header = Header::default();
+    let mut signals: HashMap<String, Signal> = HashMap::new();
+
+    loop {
+        let token = tokens
+            .next()?
+            .ok_or_else(|| Error::decode("VCD ended before `$enddefinitions`"))?;
+        match token.as_str() {
+            "$date" => header.date = tokens.until_end()?.join(" "),
+            "$version" => header.version = tokens.until_end()?.join(" "),
+            "$timescale" => {
+                let exponent = parse_timescale(&tokens.until_end()?.concat())?;
+                writer.set_timescale_exponent(exponent)?;
+            }
+            "$scope" => {
+                let fields = tokens.until_end()?;
+                let [kind, name] = fields.as_slice() else {
+                    return Err(Error::decode("malformed `$scope` declaration"));
+                };
+                writer.begin_scope(parse_scope_type(kind)?, name.clone(), None)?;
+            }
+            "$upscope" => {
+                tokens.until_end()?;
+                writer.end_scope()?;
+            }
+            "$var" => {
+                let fields = tokens.until_end()?;
+                let [kind, width, code, name @ ..] = fields.as_slice() else {
+                    return Err(Error::decode("malformed `$var` declaration"));
+                };
+                if name.is_empty() {
+                    return Err(Error::decode("`$var` declaration is missing a reference"));
+                }
+                let var_type = parse_var_type(kind)?;
+                let name = name.join(" ");
+                if let Some(existing) = signals.get(code) {
+                    writer.add_alias(var_type, VarDir::Implicit, name, existing.handle)?;
+                    continue;
+                }
+                let geometry = match var_type {
+                    VarType::VcdReal
+                    | VarType::VcdRealParameter
+                    | VarType::VcdRealtime
+                    | VarType::SvShortReal => GeomEntry::Real,
+                    VarType::GenString => GeomEntry::Variable,
+                    _ => {
+                        let width = width.parse::<u32>().map_err(|_| {
+                            Error::decode(format!("invalid `$var` width `{width}`"))
+                        })?;
+                        GeomEntry::Fixed(width)
+                    }
+                };
+                let handle =
+                    writer.add_variable(var_type, VarDir::Implicit, name, geometry.clone())?;
+                signals.insert(code.clone(), Signal { handle, geometry });
+            }
+            "$enddefinitions" => {
+                tokens.until_end()?;
+                writer.write_header(header)?;
+                return Ok(signals);
+            }
+            keyword if keyword.starts_with('$') => {
+                tokens.until_end()?;
+            }
+            other => {
+                return Err(Error::decode(format!(
+                    "unexpected token `{other}` in VCD declarations"
+                )));
+            }
+        }
+    }
+}
+
+fn read_changes<R: BufRead, W: WriteSeek>(
+    tokens: &mut Tokens<R>,
+    writer: &mut FstWriter<W>,
+    signals: &HashMap<String, Signal>,
+) -> Result<()> {
+    let mut time = 0u64;
+    while let Some(token) = tokens.next()? {
+        let mut chars = token.chars();
+        let Some(first) = chars.next() else {
+            continue;
+        };
+        match first {
+            '#' => {
+                time = chars
+                    .as_str()
+                    .parse()
+                    .map_err(|_| Error::decode(format!("invalid VCD timestamp `{token}`")))?;
+            }
+            '$' => match token.as_str() {
+                "$dumpoff" => writer.add_blackout(false, time)?,
+                "$dumpon" => writer.add_blackout(true, time)?,
+                "$comment" => {
+                    tokens.until_end()?;
+                }
+                // `$dumpvars`/`$dumpall` only bracket ordinary value lines.
+                _ => {}
+            },
+            'b' | 'B' | 'r' | 'R' | 's' | 'S' => {
+                let code = tokens
+                    .next()?
+                    .ok_or_else(|| Error::decode("VCD ended inside a value change"))?;
+                let signal = lookup(signals, &code)?;
+                let payload = chars.as_str();
+                let value = match first.to_ascii_lowercase() {
+                    'b' => vector_value(payload, &signal.geometry),
+                    'r' => {
+                        let real = payload.parse::<f64>().map_err(|_| {
+                            Error::decode(format!("invalid real value `{payload}`"))
+                        })?;
+                        SignalValue::Real(real)
+                    }
+                    _ => SignalValue::Bytes(Cow::Owned(payload.as_bytes().to_vec())),
+                };
+                writer.emit_change(time, signal.handle, value)?;
+            }
+            _ => {
+                let signal = lookup(signals, chars.as_str())?;
+                let value = match &signal.geometry {
+                    GeomEntry::Fixed(1) => SignalValue::Bit(first.to_ascii_lowercase()),
+                    geometry => vector_value(&first.to_string(), geometry),
+                };
+                writer.emit_change(time, signal.handle, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn lookup<'s>(signals: &'s HashMap<String, Signal>, code: &str) -> Result<&'s Signal> {
+    signals
+        .get(code)
+        .ok_or_else(|| Error::decode(format!("value change for undeclared identifier `{code}`")))
+}
+
+/// Resizes a VCD vector to the declared width. Shorter values are left-extended with `0`, or with
+/// their leading digit when it is `x`/`z`; longer values keep their least-significant bits.
+fn vector_value(bits: &str, geometry: &GeomEntry) -> SignalValue<'static> {
+    let bits = bits.to_ascii_lowercase();
+    let GeomEntry::Fixed(width) = geometry else {
+        return SignalValue::Vector(Cow::Owned(bits));
+    };
+    let width = *width as usize;
+    let text = if bits.len() >= width {
+        bits[bits.len() - width..].to_string()
+    } else {
+        let fill = if bits.starts_with(['x', 'z']) {
+            bits.as_bytes()[0] as char
+        } else {
+            '0'
+        };
+        let mut padded: String = std::iter::repeat_n(fill, width - bits.len()).collect();
+        padded.push_str(&bits);
+        padded
+    };
+    SignalValue::Vector(Cow::Owned(text))
+}
+
+fn parse_timescale(text: &str) -> Result<i8> {
+    let split = text
+        .find(|ch: char| !ch.is_ascii_digit())
+        .ok_or_else(|| Error::decode(format!("invalid VCD timescale `{text}`")))?;
+    let (magnitude, unit) = text.split_at(split);
+    let magnitude_exponent = match magnitude {
+        "1" => 0,
+        "10" => 1,
+        "100" => 2,
+        _ => return Err(Error::decode(format!("invalid VCD timescale `{text}`"))),
+    };
+    let unit_exponent = match unit {
+        "s" => 0,
+        "ms" => -3,
+        "us" => -6,
+        "ns" => -9,
+        "ps" => -12,
+        "fs" => -15,
+        _ => return Err(Error::decode(format!("invalid VCD timescale `{text}`"))),
+    };
+    Ok(unit_exponent + magnitude_exponent)
+}
+
+fn parse_scope_type(keyword: &str) -> Result<ScopeType> {
+    (0..=u8::MAX)
+        .filter_map(|raw| ScopeType::try_from(raw).ok())
+        .find(|scope_type| scope_keyword(*scope_type) == keyword)
+        .ok_or_else(|| Error::unsupported(format!("unknown VCD scope type `{keyword}`")))
+}
+
+fn parse_var_type(keyword: &str) -> Result<VarType> {
+    (0..=u8::MAX)
+        .filter_map(|raw| VarType::try_from(raw).ok())
+        .find(|var_type| var_keyword(*var_type) == keyword)
+        .ok_or_else(|| Error::unsupported(format!("unknown VCD variable type `{keyword}`")))
+}
+
+/// Whitespace-separated token stream over a buffered reader.
+struct Tokens<R> {
+    input: R,
+    line: String,
+    pending: VecDeque<String>,
+}
+
+impl<R: BufRead> Tokens<R> {
+    fn new(input: R) -> Self {
+        Self {
+            input,
+            line: String::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<String>> {
+        while self.pending.is_empty() {
+            self.line.clear();
+            if self.input.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            self.pending
+                .extend(self.line.split_whitespace().map(str::to_owned));
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    /// Collects the tokens up to (and consuming) the next `$end`.
+    fn until_end(&mut self) -> Result<Vec<String>> {
+        let mut fields = Vec::new();
+        loop {
+            match self.next()? {
+                Some(token) if token == "$end" => return Ok(fields),
+                Some(token) => fields.push(token),
+                None => return Err(Error::decode("VCD ended before `$end`")),
+            }
+        }
+    }
+}
@@ -1,8 +1,16 @@
 #![allow(missing_docs)]
 
+#[cfg(feature = "mmap")]
+use std::fs::File;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+
 #[cfg(feature = "mmap")]
 use memmap2::Mmap;
 
+#[cfg(feature = "mmap")]
+use crate::error::Result;
+
 /// Wrapper around a memory mapped file region.
 #[cfg(feature = "mmap")]
 #[derive(Debug)]
@@ -16,7 +24,24 @@ impl MemoryMap {
         Self { mmap }
     }
 
+    /// Maps the file at `path` read-only. The file must not be truncated while mapped.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only; callers are responsible for not modifying the file
+        // concurrently, as documented on `open`.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Self::new(mmap))
+    }
+
     pub fn as_slice(&self) -> &[u8] {
         &self.mmap
     }
 }
+
+/// Allows `Cursor<MemoryMap>` to serve as a `Read + Seek` source over the mapped bytes.
+#[cfg(feature = "mmap")]
+impl AsRef<[u8]> for MemoryMap {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
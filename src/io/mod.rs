@@ -2,16 +2,20 @@
 
 //! I/O backends used by the reader and writer implementations.
 
-use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 
 use crate::error::{Error, Result};
 
 #[cfg(feature = "mmap")]
 mod mmap;
+mod sequential;
+mod spool;
 mod streaming;
 
 #[cfg(feature = "mmap")]
 pub use mmap::MemoryMap;
+pub use sequential::SequentialWriter;
+pub use spool::SpoolBuffer;
 pub use streaming::{BufferedReader, BufferedWriter};
 
 /// Trait alias for objects that implement `Read + Seek`.
@@ -22,24 +26,97 @@ impl<T: Read + Seek> ReadSeek for T {}
 pub trait WriteSeek: Write + Seek {}
 impl<T: Write + Seek> WriteSeek for T {}
 
+/// Byte stream consumed by the reader: either the caller's buffered source or an in-memory copy of
+/// a decompressed `FST_BL_ZWRAPPER` payload.
+pub enum ReaderStream<R: ReadSeek> {
+    /// Buffered view over the caller-supplied source.
+    Buffered(BufReader<R>),
+    /// Decompressed file contents; the original source is retained for `into_inner`.
+    Inflated { source: R, data: Cursor<Vec<u8>> },
+}
+
+impl<R: ReadSeek> Read for ReaderStream<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            ReaderStream::Buffered(reader) => reader.read(buf),
+            ReaderStream::Inflated { data, .. } => data.read(buf),
+        }
+    }
+}
+
+impl<R: ReadSeek> BufRead for ReaderStream<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        match self {
+            ReaderStream::Buffered(reader) => reader.fill_buf(),
+            ReaderStream::Inflated { data, .. } => data.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            ReaderStream::Buffered(reader) => reader.consume(amt),
+            ReaderStream::Inflated { data, .. } => data.consume(amt),
+        }
+    }
+}
+
+impl<R: ReadSeek> Seek for ReaderStream<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            ReaderStream::Buffered(reader) => reader.seek(pos),
+            ReaderStream::Inflated { data, .. } => data.seek(pos),
+        }
+    }
+
+    fn stream_position(&mut self) -> std::io::Result<u64> {
+        match self {
+            ReaderStream::Buffered(reader) => reader.stream_position(),
+            ReaderStream::Inflated { data, .. } => data.stream_position(),
+        }
+    }
+}
+
 /// Default buffered reader backend.
 pub struct ReaderBackend<R: ReadSeek> {
-    inner: BufReader<R>,
+    inner: ReaderStream<R>,
 }
 
 impl<R: ReadSeek> ReaderBackend<R> {
     pub fn new(inner: R) -> Self {
+        Self::from_buffered(BufReader::new(inner))
+    }
+
+    /// Wraps an already buffered source, preserving any bytes it has buffered.
+    pub fn from_buffered(reader: BufReader<R>) -> Self {
         Self {
-            inner: BufReader::new(inner),
+            inner: ReaderStream::Buffered(reader),
         }
     }
 
-    pub fn get_mut(&mut self) -> &mut BufReader<R> {
+    /// Serves reads from `data` (typically an inflated z-wrapper payload) instead of `source`.
+    pub fn inflated(source: R, data: Vec<u8>) -> Self {
+        Self {
+            inner: ReaderStream::Inflated {
+                source,
+                data: Cursor::new(data),
+            },
+        }
+    }
+
+    /// Returns `true` when reads are served from an inflated in-memory copy.
+    pub fn is_inflated(&self) -> bool {
+        matches!(self.inner, ReaderStream::Inflated { .. })
+    }
+
+    pub fn get_mut(&mut self) -> &mut ReaderStream<R> {
         &mut self.inner
     }
 
     pub fn into_inner(self) -> R {
-        self.inner.into_inner()
+        match self.inner {
+            ReaderStream::Buffered(reader) => reader.into_inner(),
+            ReaderStream::Inflated { source, .. } => source,
+        }
     }
 }
 
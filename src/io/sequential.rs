@@ -0,0 +1,67 @@
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// Adapter that lets append-only sinks (pipes, sockets, stdout) satisfy the writer's `Seek` bound.
+///
+/// The adapter tracks the number of bytes written so position queries succeed, but any seek that
+/// would move the cursor fails with [`io::ErrorKind::Unsupported`]. Writer features that rewrite
+/// earlier sections of the file are therefore unavailable when streaming through this type.
+#[derive(Debug)]
+pub struct SequentialWriter<W: Write> {
+    inner: W,
+    position: u64,
+}
+
+impl<W: Write> SequentialWriter<W> {
+    /// Wraps the provided sink, starting the logical position at zero.
+    pub fn new(inner: W) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns a shared reference to the wrapped sink.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the adapter, yielding the wrapped sink.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for SequentialWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.position += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Seek for SequentialWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(_) => None,
+        };
+        match target {
+            Some(offset) if offset == self.position => Ok(self.position),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "sequential writers cannot seek away from the current position",
+            )),
+        }
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
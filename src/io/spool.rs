@@ -0,0 +1,93 @@
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// In-memory sink whose leading bytes can be drained to another destination while writing
+/// continues.
+///
+/// Offsets keep counting from the start of the stream after a drain, so the writer's positions
+/// stay valid. Seeking back into bytes that have already been drained fails with
+/// [`io::ErrorKind::Unsupported`], which makes the writer skip header rewrites the same way it
+/// does for [`super::SequentialWriter`].
+#[derive(Debug, Default)]
+pub struct SpoolBuffer {
+    drained: u64,
+    data: Vec<u8>,
+    position: u64,
+}
+
+impl SpoolBuffer {
+    /// Creates an empty spool positioned at offset zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bytes handed out by [`Self::drain`] so far.
+    pub fn drained(&self) -> u64 {
+        self.drained
+    }
+
+    /// Returns the bytes still held in memory.
+    pub fn pending(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Removes and returns every buffered byte. Later seeks may not move before the new start.
+    pub fn drain(&mut self) -> Vec<u8> {
+        self.drained += self.data.len() as u64;
+        std::mem::take(&mut self.data)
+    }
+
+    fn end(&self) -> u64 {
+        self.drained + self.data.len() as u64
+    }
+}
+
+impl Write for SpoolBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = self
+            .position
+            .checked_sub(self.drained)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "cannot write into bytes already drained from the spool",
+                )
+            })?;
+        if start > self.data.len() {
+            self.data.resize(start, 0);
+        }
+        let overlap = buf.len().min(self.data.len() - start);
+        self.data[start..start + overlap].copy_from_slice(&buf[..overlap]);
+        self.data.extend_from_slice(&buf[overlap..]);
+        self.position += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for SpoolBuffer {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.end().checked_add_signed(delta),
+        };
+        match target {
+            Some(offset) if offset >= self.drained => {
+                self.position = offset;
+                Ok(offset)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "cannot seek into bytes already drained from the spool",
+            )),
+        }
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
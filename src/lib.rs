@@ -9,6 +9,10 @@ pub mod compression;
 pub mod encoding;
 /// Shared error and result types.
 pub mod error;
+/// Converters from parsed FST traces into other waveform formats.
+pub mod export;
+/// Converters from other waveform formats into FST traces.
+pub mod import;
 /// I/O backends (buffered and memory-mapped).
 pub mod io;
 /// Streaming reader front-end for FST files.
@@ -30,18 +34,25 @@ mod simd;
 #[cfg(feature = "async")]
 pub use async_support::{AsyncReader, AsyncWriter, AsyncWriterBuilder, read_all as async_read_all};
 pub use block::{
-    BlackoutBlock, BlackoutEvent, GeomEntry, GeomInfo, Header, HierarchyBlock, ScopeEntry,
-    TimeSection, VarEntry, VcBlock,
+    BlackoutBlock, BlackoutEvent, EnumDef, GeomEntry, GeomInfo, Header, HierarchyBlock,
+    HierarchyCompression, HierarchyEvent, ScopeEntry, TimeSection, VarEntry, VcBlock,
+};
+pub use compression::{
+    Compressor, CustomCompressor, CustomDecompressor, Decompressor, NullCompressor,
+    NullDecompressor,
 };
-pub use compression::{Compressor, Decompressor, NullCompressor, NullDecompressor};
 pub use error::{Error, Result};
-pub use reader::{ChainIndex, ChainSlot, FstReader, ReaderBuilder, ReaderOptions, VcBlockMeta};
+pub use reader::{
+    ChainIndex, ChainSlot, FstReader, ReaderBuilder, ReaderOptions, TraceSummary,
+    VcBlockIndexEntry, VcBlockMeta, VerifyFailure, VerifyReport,
+};
 #[cfg(feature = "serde")]
 pub use serde_support::{
     AttributeNode, HierarchySnapshot, OwnedSignalValue, OwnedValueChange, ScopeNode, VariableNode,
-    collect_value_changes, snapshot_hierarchy,
+    collect_value_changes, rebuild_hierarchy, snapshot_hierarchy,
 };
 pub use types::*;
 pub use writer::{
-    ChainCompression, FstWriter, ScopeId, TimeCompression, WriterBuilder, WriterOptions,
+    BlockStats, ChainCompression, DuplicateChangePolicy, FstWriter, ScopeId, TimeCompression,
+    WriterBuilder, WriterOptions,
 };
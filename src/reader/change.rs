@@ -1,12 +1,14 @@
 use std::borrow::Cow;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt;
 use std::str;
 
 use crate::block::{GeomEntry, GeomInfo};
 use crate::encoding::decode_varint_with_len;
 use crate::error::{Error, Result};
 use crate::reader::vc::{ChainIndex, ChainPayload, VcBlockMeta};
-use crate::types::SignalValue;
+use crate::types::{Endianness, LogicState, SignalValue};
+use crate::util::unpack_to_ascii;
 
 const FST_RCV_STR: [char; 8] = ['x', 'z', 'h', 'u', 'w', 'l', '-', '?'];
 
@@ -16,6 +18,7 @@ enum SignalKind {
     Vector { width: u32 },
     VarLen,
     Real,
+    ShortReal,
 }
 
 impl SignalKind {
@@ -34,6 +37,7 @@ impl SignalKind {
                 }
             }
             GeomEntry::Real => Ok(SignalKind::Real),
+            GeomEntry::ShortReal => Ok(SignalKind::ShortReal),
             GeomEntry::Variable => Ok(SignalKind::VarLen),
         }
     }
@@ -42,17 +46,25 @@ impl SignalKind {
 #[derive(Debug)]
 struct ChainCursor<'a> {
     handle: u32,
+    /// `false` when the handle is decoded only to feed requested aliases.
+    emit: bool,
     kind: SignalKind,
+    endianness: Endianness,
+    /// Expand packed two-state vectors into ASCII [`SignalValue::Vector`]s.
+    expand_packed: bool,
     data: &'a [u8],
     offset: usize,
     current_time_index: usize,
 }
 
 impl<'a> ChainCursor<'a> {
-    fn new(handle: u32, kind: SignalKind, data: &'a [u8]) -> Self {
+    fn new(handle: u32, kind: SignalKind, endianness: Endianness, data: &'a [u8]) -> Self {
         Self {
             handle,
+            emit: true,
             kind,
+            endianness,
+            expand_packed: false,
             data,
             offset: 0,
             current_time_index: 0,
@@ -138,6 +150,12 @@ impl<'a> ChainCursor<'a> {
                     }
                     let bits = &self.data[self.offset..end];
                     self.offset = end;
+                    if self.expand_packed {
+                        let text = unpack_to_ascii(bits, width)
+                            .and_then(|ascii| String::from_utf8(ascii).ok())
+                            .ok_or_else(|| Error::decode("packed vector payload too short"))?;
+                        return Ok(Some(SignalValue::Vector(Cow::Owned(text))));
+                    }
                     Ok(Some(SignalValue::PackedBits {
                         width,
                         bits: Cow::Borrowed(bits),
@@ -158,43 +176,67 @@ impl<'a> ChainCursor<'a> {
                     }
                 }
             }
-            SignalKind::Real => {
+            SignalKind::Real | SignalKind::ShortReal => {
+                // Reals are always stored as a full 8-byte double (libfst sets the low marker bit
+                // for every real change); the packed two-state form cannot carry a double.
+                // Shortreals follow the same scheme with a 4-byte float.
                 if (marker & 1) == 0 {
-                    let end = self
-                        .offset
-                        .checked_add(1)
-                        .ok_or_else(|| Error::decode("packed real payload overflow"))?;
-                    if end > self.data.len() {
-                        return Err(Error::decode("packed real payload exceeds chain bounds"));
-                    }
-                    let bits = &self.data[self.offset..end];
-                    self.offset = end;
-                    Ok(Some(SignalValue::PackedBits {
-                        width: 8,
-                        bits: Cow::Borrowed(bits),
-                    }))
-                } else {
-                    let end = self
-                        .offset
-                        .checked_add(8)
-                        .ok_or_else(|| Error::decode("real payload overflow"))?;
-                    if end > self.data.len() {
-                        return Err(Error::decode("real payload exceeds chain bounds"));
-                    }
-                    let mut buf = [0u8; 8];
-                    buf.copy_from_slice(&self.data[self.offset..end]);
-                    self.offset = end;
-                    let value = if cfg!(target_endian = "little") {
-                        f64::from_bits(u64::from_le_bytes(buf))
-                    } else {
-                        f64::from_bits(u64::from_be_bytes(buf))
-                    };
-                    Ok(Some(SignalValue::Real(value)))
+                    return Err(Error::decode(format!(
+                        "real change for handle {} uses the packed encoding",
+                        self.handle
+                    )));
                 }
+                let len = if matches!(self.kind, SignalKind::Real) {
+                    8
+                } else {
+                    4
+                };
+                let end = self
+                    .offset
+                    .checked_add(len)
+                    .ok_or_else(|| Error::decode("real payload overflow"))?;
+                let bytes = self
+                    .data
+                    .get(self.offset..end)
+                    .ok_or_else(|| Error::decode("real payload exceeds chain bounds"))?;
+                self.offset = end;
+                Ok(Some(SignalValue::Real(decode_real(bytes, self.endianness))))
             }
         }
     }
 
+    /// Advances past the next chain entry without decoding its value. Returns `false` once the
+    /// chain is exhausted.
+    fn skip_value(&mut self) -> Result<bool> {
+        if self.offset >= self.data.len() {
+            return Ok(false);
+        }
+        let (marker, consumed) = decode_varint_with_len(&self.data[self.offset..])?;
+        self.offset += consumed;
+        let payload_len = match self.kind {
+            SignalKind::Bit => 0,
+            SignalKind::VarLen => {
+                let (len, consumed_len) = decode_varint_with_len(&self.data[self.offset..])?;
+                self.offset += consumed_len;
+                usize::try_from(len)
+                    .map_err(|_| Error::decode("variable-length payload exceeds usize"))?
+            }
+            SignalKind::Vector { width } if (marker & 1) == 0 => {
+                (width as usize).div_ceil(8).max(1)
+            }
+            SignalKind::Vector { width } => width as usize,
+            SignalKind::Real => 8,
+            SignalKind::ShortReal => 4,
+        };
+        let end = self
+            .offset
+            .checked_add(payload_len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| Error::decode("chain entry exceeds chain bounds"))?;
+        self.offset = end;
+        Ok(true)
+    }
+
     fn compute_delta(&self, marker: u64) -> Result<usize> {
         let delta = match self.kind {
             SignalKind::Bit => {
@@ -202,9 +244,10 @@ impl<'a> ChainCursor<'a> {
                 let shift = 2usize << flag;
                 (marker >> shift) as usize
             }
-            SignalKind::Vector { .. } | SignalKind::VarLen | SignalKind::Real => {
-                (marker >> 1) as usize
-            }
+            SignalKind::Vector { .. }
+            | SignalKind::VarLen
+            | SignalKind::Real
+            | SignalKind::ShortReal => (marker >> 1) as usize,
         };
         Ok(delta)
     }
@@ -218,6 +261,30 @@ pub struct ValueChange<'a> {
     pub value: SignalValue<'a>,
 }
 
+impl ValueChange<'_> {
+    /// Converts the change into an owned representation that no longer borrows block data.
+    pub fn into_owned(self) -> ValueChange<'static> {
+        ValueChange {
+            timestamp: self.timestamp,
+            handle: self.handle,
+            alias_of: self.alias_of,
+            value: self.value.into_owned(),
+        }
+    }
+
+    /// Returns `true` when the value carries only `0`/`1` bits (see [`SignalValue::logic_state`]).
+    pub fn is_two_state(&self) -> bool {
+        self.value.logic_state() == LogicState::TwoState
+    }
+}
+
+/// Renders the change as `#<time> <handle> = <value>`.
+impl fmt::Display for ValueChange<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {} = {}", self.timestamp, self.handle, self.value)
+    }
+}
+
 pub struct VcBlockChanges<'a> {
     block: &'a VcBlockMeta,
     cursors: Vec<ChainCursor<'a>>,
@@ -227,6 +294,9 @@ pub struct VcBlockChanges<'a> {
     alias_map: Vec<Vec<u32>>,
     time_index: usize,
     time_zero: u64,
+    /// Changes still owed by the cursors currently in `schedule` or `current_handles`: each will
+    /// yield at least its own change (when emitted) plus one per alias it feeds.
+    scheduled_yield: usize,
 }
 
 impl<'a> VcBlockChanges<'a> {
@@ -236,8 +306,32 @@ impl<'a> VcBlockChanges<'a> {
         alias_index: &'a ChainIndex,
         time_zero: u64,
     ) -> Result<Self> {
+        Self::with_filter(block, geom, alias_index, time_zero, None)
+    }
+
+    /// Builds an iterator restricted to `filter` when given: only requested handles and requested
+    /// aliases are emitted, and only the chains needed to produce them are decoded.
+    pub fn with_filter(
+        block: &'a VcBlockMeta,
+        geom: &'a GeomInfo,
+        alias_index: &'a ChainIndex,
+        time_zero: u64,
+        filter: Option<&HashSet<u32>>,
+    ) -> Result<Self> {
+        let mut alias_map = vec![Vec::new(); alias_index.slots.len() + 1];
+        for (slot_idx, slot_opt) in alias_index.slots.iter().enumerate() {
+            let Some(slot) = slot_opt else {
+                continue;
+            };
+            let alias = (slot_idx + 1) as u32;
+            if let Some(canon) = slot.alias_of
+                && filter.is_none_or(|set| set.contains(&alias))
+            {
+                alias_map[canon as usize].push(alias);
+            }
+        }
+
         let mut cursors = Vec::new();
-        let mut handle_to_cursor = vec![None; block.chains.len()];
 
         for (idx, chain_opt) in block.chains.iter().enumerate() {
             let Some(chain) = chain_opt else {
@@ -247,6 +341,13 @@ impl<'a> VcBlockChanges<'a> {
                 continue;
             }
             let handle = (idx + 1) as u32;
+            let emit = filter.is_none_or(|set| set.contains(&handle));
+            let feeds_alias = alias_map
+                .get(handle as usize)
+                .is_some_and(|aliases| !aliases.is_empty());
+            if !emit && !feeds_alias {
+                continue;
+            }
             let geom_entry = geom.entry(handle).ok_or_else(|| {
                 Error::invalid(format!("missing geometry entry for handle {handle}"))
             })?;
@@ -255,19 +356,22 @@ impl<'a> VcBlockChanges<'a> {
                 ChainPayload::Borrowed { range } => &block.chain_buffer[range.clone()],
                 ChainPayload::Owned(buffer) => buffer.as_slice(),
             };
-            handle_to_cursor[idx] = Some(cursors.len());
-            cursors.push(ChainCursor::new(handle, kind, data));
+            let mut cursor = ChainCursor::new(handle, kind, block.endianness, data);
+            cursor.emit = emit;
+            cursors.push(cursor);
         }
 
         let time_len = block.time_table.timestamps.len();
         let mut schedule = vec![Vec::new(); time_len];
 
+        let mut scheduled_yield = 0usize;
         for (idx, cursor) in cursors.iter().enumerate() {
             if let Some(delta) = cursor.peek_delta()? {
                 if delta >= time_len {
                     return Err(Error::decode("initial chain delta exceeds time table"));
                 }
                 schedule[delta].push(idx);
+                scheduled_yield += cursor_yield(cursor, &alias_map);
             }
         }
 
@@ -275,16 +379,6 @@ impl<'a> VcBlockChanges<'a> {
             handles.sort_unstable_by_key(|idx| cursors[*idx].handle);
         }
 
-        let mut alias_map = vec![Vec::new(); alias_index.slots.len() + 1];
-        for (slot_idx, slot_opt) in alias_index.slots.iter().enumerate() {
-            let Some(slot) = slot_opt else {
-                continue;
-            };
-            if let Some(canon) = slot.alias_of {
-                alias_map[canon as usize].push((slot_idx + 1) as u32);
-            }
-        }
-
         Ok(Self {
             block,
             cursors,
@@ -294,6 +388,7 @@ impl<'a> VcBlockChanges<'a> {
             alias_map,
             time_index: 0,
             time_zero,
+            scheduled_yield,
         })
     }
 
@@ -322,6 +417,8 @@ impl<'a> VcBlockChanges<'a> {
                 .checked_add(self.time_zero)
                 .ok_or_else(|| Error::decode("timestamp overflow"))?;
 
+            let weight = cursor_yield(&self.cursors[cursor_idx], &self.alias_map);
+            self.scheduled_yield -= weight;
             let cursor = &mut self.cursors[cursor_idx];
             let Some(value) = cursor.read_value(self.time_index)? else {
                 continue;
@@ -336,9 +433,11 @@ impl<'a> VcBlockChanges<'a> {
                     return Err(Error::decode("chain delta exceeds time table"));
                 }
                 self.schedule[next_time].push(cursor_idx);
+                self.scheduled_yield += weight;
             }
 
             let handle = cursor.handle;
+            let emit = cursor.emit;
             if let Some(aliases) = self.alias_map.get(handle as usize) {
                 for &alias in aliases {
                     self.pending_aliases.push_back(ValueChange {
@@ -349,6 +448,9 @@ impl<'a> VcBlockChanges<'a> {
                     });
                 }
             }
+            if !emit {
+                continue;
+            }
 
             return Ok(Some(ValueChange {
                 timestamp,
@@ -360,6 +462,195 @@ impl<'a> VcBlockChanges<'a> {
     }
 }
 
+impl<'a> VcBlockChanges<'a> {
+    /// When `enabled`, two-state vectors are yielded as ASCII [`SignalValue::Vector`]s instead of
+    /// borrowed [`SignalValue::PackedBits`]. Must be called before iteration starts.
+    pub fn expand_packed_bits(mut self, enabled: bool) -> Self {
+        for cursor in &mut self.cursors {
+            cursor.expand_packed = enabled;
+        }
+        self
+    }
+
+    /// Restricts the iterator to absolute timestamps in `[start, end]`. Every change is still
+    /// decoded so chain state stays consistent; for each handle whose last change before `start`
+    /// is not superseded by a change at exactly `start`, that value is emitted once as a
+    /// synthetic event stamped `start`.
+    ///
+    /// Only this block's changes are carried: a handle last set in an earlier block gets no event
+    /// at `start` unless its value is supplied through [`WindowedChanges::with_prior_values`].
+    pub fn within(self, start: u64, end: u64) -> WindowedChanges<'a> {
+        WindowedChanges {
+            inner: self,
+            start,
+            end,
+            prior: BTreeMap::new(),
+            primed: false,
+            buffered: VecDeque::new(),
+            done: start > end,
+        }
+    }
+
+    /// Converts every yielded change into an owned [`ValueChange<'static>`], so the results can be
+    /// collected and kept after the block (and the reader) is dropped.
+    pub fn owned(self) -> OwnedChanges<'a> {
+        OwnedChanges { inner: self }
+    }
+
+    /// Collects the changes of each distinct timestamp, canonical handles and aliases alike, into
+    /// one `(timestamp, changes)` group. Groups arrive in time order.
+    pub fn grouped(self) -> GroupedChanges<'a> {
+        GroupedChanges {
+            inner: self,
+            carry: None,
+        }
+    }
+}
+
+/// Iterator returned by [`VcBlockChanges::grouped`].
+pub struct GroupedChanges<'a> {
+    inner: VcBlockChanges<'a>,
+    carry: Option<ValueChange<'a>>,
+}
+
+impl<'a> Iterator for GroupedChanges<'a> {
+    type Item = Result<(u64, Vec<ValueChange<'a>>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = match self.carry.take() {
+            Some(change) => change,
+            None => match self.inner.next()? {
+                Ok(change) => change,
+                Err(err) => return Some(Err(err)),
+            },
+        };
+        let timestamp = first.timestamp;
+        let mut group = vec![first];
+        for change in self.inner.by_ref() {
+            let change = match change {
+                Ok(change) => change,
+                Err(err) => return Some(Err(err)),
+            };
+            if change.timestamp != timestamp {
+                self.carry = Some(change);
+                break;
+            }
+            group.push(change);
+        }
+        Some(Ok((timestamp, group)))
+    }
+}
+
+/// Iterator returned by [`VcBlockChanges::owned`].
+pub struct OwnedChanges<'a> {
+    inner: VcBlockChanges<'a>,
+}
+
+impl Iterator for OwnedChanges<'_> {
+    type Item = Result<ValueChange<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|change| change.map(ValueChange::into_owned))
+    }
+}
+
+/// Iterator returned by [`VcBlockChanges::within`].
+pub struct WindowedChanges<'a> {
+    inner: VcBlockChanges<'a>,
+    start: u64,
+    end: u64,
+    prior: BTreeMap<u32, SignalValue<'a>>,
+    primed: bool,
+    buffered: VecDeque<ValueChange<'a>>,
+    done: bool,
+}
+
+impl<'a> WindowedChanges<'a> {
+    /// Seeds the carried-over values with each handle's state before the block, e.g. from
+    /// [`FstReader::value_at`](crate::reader::FstReader::value_at) at the previous block's end.
+    /// Changes in this block before `start` override the seeds; the remaining ones are emitted at
+    /// `start` with `alias_of` unset. Must be called before iteration starts.
+    pub fn with_prior_values(
+        mut self,
+        values: impl IntoIterator<Item = (u32, SignalValue<'a>)>,
+    ) -> Self {
+        self.prior.extend(values);
+        self
+    }
+
+    /// Consumes every change up to and including `start`, queueing the carried-over values
+    /// followed by the changes that happen exactly at `start`.
+    fn prime(&mut self) -> Result<()> {
+        self.primed = true;
+        let start = self.start;
+        let mut carried: BTreeMap<u32, ValueChange<'a>> = std::mem::take(&mut self.prior)
+            .into_iter()
+            .map(|(handle, value)| {
+                let change = ValueChange {
+                    timestamp: start,
+                    handle,
+                    alias_of: None,
+                    value,
+                };
+                (handle, change)
+            })
+            .collect();
+        let mut at_start = Vec::new();
+        for change in self.inner.by_ref() {
+            let change = change?;
+            if change.timestamp < self.start {
+                carried.insert(change.handle, change);
+            } else if change.timestamp == self.start {
+                carried.remove(&change.handle);
+                at_start.push(change);
+            } else {
+                at_start.push(change);
+                break;
+            }
+        }
+        self.buffered
+            .extend(carried.into_values().map(|mut change| {
+                change.timestamp = start;
+                change
+            }));
+        self.buffered.extend(at_start);
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for WindowedChanges<'a> {
+    type Item = Result<ValueChange<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if !self.primed
+            && let Err(err) = self.prime()
+        {
+            self.done = true;
+            return Some(Err(err));
+        }
+        let change = match self.buffered.pop_front() {
+            Some(change) => change,
+            None => match self.inner.next()? {
+                Ok(change) => change,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            },
+        };
+        if change.timestamp > self.end {
+            self.done = true;
+            return None;
+        }
+        Some(Ok(change))
+    }
+}
+
 impl<'a> Iterator for VcBlockChanges<'a> {
     type Item = Result<ValueChange<'a>>;
 
@@ -370,6 +661,152 @@ impl<'a> Iterator for VcBlockChanges<'a> {
             Err(err) => Some(Err(err)),
         }
     }
+
+    /// The lower bound counts one change per scheduled chain entry and alias it feeds; chains'
+    /// later entries are not known until they are reached.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.scheduled_yield + self.pending_aliases.len(), None)
+    }
+}
+
+/// Changes a scheduled cursor is guaranteed to yield for its next chain entry.
+fn cursor_yield(cursor: &ChainCursor<'_>, alias_map: &[Vec<u32>]) -> usize {
+    let aliases = alias_map
+        .get(cursor.handle as usize)
+        .map_or(0, |aliases| aliases.len());
+    usize::from(cursor.emit) + aliases
+}
+
+/// What a single block says about a handle's value at some time.
+pub(crate) enum BlockValue {
+    /// The last chain entry at or before the time.
+    Changed(SignalValue<'static>),
+    /// The block has no chain entries for the handle, so its frame holds the value throughout.
+    Unchanged,
+    /// The handle changes in this block, but only after the time.
+    ChangesLater,
+}
+
+/// Looks up the canonical `handle` at absolute `time` in the chain of `block`.
+pub(crate) fn value_at_in_block(
+    block: &VcBlockMeta,
+    geom: &GeomInfo,
+    handle: u32,
+    time: u64,
+    time_zero: u64,
+) -> Result<BlockValue> {
+    let geom_entry = geom
+        .entry(handle)
+        .ok_or_else(|| Error::invalid(format!("missing geometry entry for handle {handle}")))?;
+    let kind = SignalKind::from_geom(geom_entry, handle)?;
+
+    let Some(chain) = block
+        .chains
+        .get((handle - 1) as usize)
+        .and_then(|chain| chain.as_ref())
+    else {
+        return Ok(BlockValue::Unchanged);
+    };
+    let data = match &chain.payload {
+        ChainPayload::Borrowed { range } => &block.chain_buffer[range.clone()],
+        ChainPayload::Owned(buffer) => buffer.as_slice(),
+    };
+    let timestamps = &block.time_table.timestamps;
+    let mut cursor = ChainCursor::new(handle, kind, block.endianness, data);
+    let mut latest = None;
+    let mut has_entries = false;
+    let mut time_index = 0usize;
+    while let Some(delta) = cursor.peek_delta()? {
+        has_entries = true;
+        time_index = time_index
+            .checked_add(delta)
+            .ok_or_else(|| Error::decode("chain delta overflow"))?;
+        let timestamp = timestamps
+            .get(time_index)
+            .ok_or_else(|| Error::decode("chain delta exceeds time table"))?
+            .checked_add(time_zero)
+            .ok_or_else(|| Error::decode("timestamp overflow"))?;
+        if timestamp > time {
+            break;
+        }
+        latest = cursor.read_value(time_index)?;
+    }
+    Ok(match latest {
+        Some(value) => BlockValue::Changed(value.into_owned()),
+        None if has_entries => BlockValue::ChangesLater,
+        None => BlockValue::Unchanged,
+    })
+}
+
+/// Returns the canonical `handle`'s value recorded in the frame of `block`, if the frame covers
+/// it.
+pub(crate) fn block_frame_value(
+    block: &VcBlockMeta,
+    geom: &GeomInfo,
+    handle: u32,
+) -> Result<Option<SignalValue<'static>>> {
+    let geom_entry = geom
+        .entry(handle)
+        .ok_or_else(|| Error::invalid(format!("missing geometry entry for handle {handle}")))?;
+    let kind = SignalKind::from_geom(geom_entry, handle)?;
+    frame_value(block, geom, handle, kind)
+}
+
+/// Extracts `handle`'s initial value from the block frame, if the frame covers it.
+fn frame_value(
+    block: &VcBlockMeta,
+    geom: &GeomInfo,
+    handle: u32,
+    kind: SignalKind,
+) -> Result<Option<SignalValue<'static>>> {
+    if u64::from(handle) > block.frame.max_handle {
+        return Ok(None);
+    }
+    let mut offset = 0usize;
+    for (other, entry) in geom.handles() {
+        if other == handle {
+            break;
+        }
+        offset += match entry {
+            GeomEntry::Fixed(width) => *width as usize,
+            GeomEntry::Real => 8,
+            GeomEntry::ShortReal => 4,
+            GeomEntry::Variable => 0,
+        };
+    }
+    let frame = block.frame.as_slice();
+    let value = match kind {
+        SignalKind::Bit => frame
+            .get(offset)
+            .map(|byte| SignalValue::Bit(*byte as char)),
+        SignalKind::Vector { width } => {
+            frame
+                .get(offset..offset + width as usize)
+                .map(|bytes| match str::from_utf8(bytes) {
+                    Ok(text) => SignalValue::Vector(Cow::Owned(text.to_owned())),
+                    Err(_) => SignalValue::Bytes(Cow::Owned(bytes.to_vec())),
+                })
+        }
+        SignalKind::Real => frame
+            .get(offset..offset + 8)
+            .map(|bytes| SignalValue::Real(decode_real(bytes, block.endianness))),
+        SignalKind::ShortReal => frame
+            .get(offset..offset + 4)
+            .map(|bytes| SignalValue::Real(decode_real(bytes, block.endianness))),
+        SignalKind::VarLen => None,
+    };
+    Ok(value)
+}
+
+/// Decodes an 8-byte double or a 4-byte float (widened to `f64`) stored in `endianness` order.
+fn decode_real(bytes: &[u8], endianness: Endianness) -> f64 {
+    match bytes.try_into() {
+        Ok(buf) => endianness.f64_from_bytes(buf),
+        Err(_) => {
+            let buf = bytes.try_into().expect("reals are 8 or 4 bytes");
+            f64::from(endianness.f32_from_bytes(buf))
+        }
+    }
 }
 
 pub fn build_changes<'a>(
@@ -384,4 +821,53 @@ impl VcBlockMeta {
     pub fn changes<'a>(&'a self, geom: &'a GeomInfo, time_zero: u64) -> Result<VcBlockChanges<'a>> {
         build_changes(self, geom, time_zero)
     }
+
+    /// Returns how many changes [`Self::changes`] yields for this block, aliases included. Each
+    /// chain is walked entry by entry without decoding values, so this is cheaper than iterating.
+    pub fn change_count(&self, geom: &GeomInfo) -> Result<usize> {
+        let mut fanout = vec![1usize; self.chains.len() + 1];
+        for slot in self.index.slots.iter().flatten() {
+            if let Some(canon) = slot.alias_of
+                && let Some(count) = fanout.get_mut(canon as usize)
+            {
+                *count += 1;
+            }
+        }
+
+        let mut total = 0usize;
+        for (idx, chain_opt) in self.chains.iter().enumerate() {
+            let Some(chain) = chain_opt else {
+                continue;
+            };
+            if chain.alias_of.is_some() {
+                continue;
+            }
+            let handle = (idx + 1) as u32;
+            let geom_entry = geom.entry(handle).ok_or_else(|| {
+                Error::invalid(format!("missing geometry entry for handle {handle}"))
+            })?;
+            let kind = SignalKind::from_geom(geom_entry, handle)?;
+            let data = match &chain.payload {
+                ChainPayload::Borrowed { range } => &self.chain_buffer[range.clone()],
+                ChainPayload::Owned(buffer) => buffer.as_slice(),
+            };
+            let mut cursor = ChainCursor::new(handle, kind, self.endianness, data);
+            let mut entries = 0usize;
+            while cursor.skip_value()? {
+                entries += 1;
+            }
+            total += entries * fanout[handle as usize];
+        }
+        Ok(total)
+    }
+
+    /// Iterates only the changes of `handles`; aliases are included when they are in the set.
+    pub fn changes_filtered<'a>(
+        &'a self,
+        geom: &'a GeomInfo,
+        time_zero: u64,
+        handles: &HashSet<u32>,
+    ) -> Result<VcBlockChanges<'a>> {
+        VcBlockChanges::with_filter(self, geom, &self.index, time_zero, Some(handles))
+    }
 }
@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+
+use crate::error::{Error, Result};
+use crate::io::ReadSeek;
+use crate::reader::{FstReader, ValueChange};
+
+/// Iterator over every value change in a trace, spanning all remaining value-change blocks.
+///
+/// One block is decoded at a time; its changes are buffered as owned values and the next block is
+/// loaded once the buffer drains.
+pub struct AllChanges<'a, R: ReadSeek> {
+    reader: &'a mut FstReader<R>,
+    buffered: VecDeque<ValueChange<'static>>,
+    last_timestamp: Option<u64>,
+    exhausted: bool,
+}
+
+impl<'a, R: ReadSeek> AllChanges<'a, R> {
+    pub(crate) fn new(reader: &'a mut FstReader<R>) -> Self {
+        Self {
+            reader,
+            buffered: VecDeque::new(),
+            last_timestamp: None,
+            exhausted: false,
+        }
+    }
+
+    /// Decodes blocks until at least one change is buffered or the stream ends.
+    fn refill(&mut self) -> Result<()> {
+        while self.buffered.is_empty() && !self.exhausted {
+            let Some(block) = self.reader.next_value_changes()? else {
+                self.exhausted = true;
+                break;
+            };
+            for change in block {
+                self.buffered.push_back(change?.into_owned());
+            }
+        }
+        Ok(())
+    }
+
+    fn next_change(&mut self) -> Result<Option<ValueChange<'static>>> {
+        if self.buffered.is_empty() {
+            self.refill()?;
+        }
+        let Some(change) = self.buffered.pop_front() else {
+            return Ok(None);
+        };
+        if let Some(last) = self.last_timestamp
+            && change.timestamp < last
+        {
+            return Err(Error::decode(format!(
+                "value-change blocks are out of order: timestamp {} follows {last}",
+                change.timestamp
+            )));
+        }
+        self.last_timestamp = Some(change.timestamp);
+        Ok(Some(change))
+    }
+}
+
+impl<R: ReadSeek> Iterator for AllChanges<'_, R> {
+    type Item = Result<ValueChange<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted && self.buffered.is_empty() {
+            return None;
+        }
+        match self.next_change() {
+            Ok(Some(change)) => Some(Ok(change)),
+            Ok(None) => None,
+            Err(err) => {
+                self.exhausted = true;
+                self.buffered.clear();
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -2,36 +2,107 @@
 
 //! High-level streaming reader for FST files.
 
-use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor, ErrorKind, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
 
-use crate::block::{BlackoutBlock, GeomInfo, Header, HierarchyBlock};
+#[cfg(feature = "gzip")]
+use flate2::read::GzDecoder;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::block::{
+    BlackoutBlock, ChainIndexEntry, GeomEntry, GeomInfo, Header, HierarchyBlock, TimeTable,
+};
+use crate::compression::CustomDecompressor;
 use crate::error::{Error, Result};
+#[cfg(feature = "mmap")]
+use crate::io::MemoryMap;
 use crate::io::{ReadSeek, ReaderBackend};
-use crate::types::BlockType;
-use crate::util::{read_u64_be, skip_bytes};
+use crate::types::{BlockType, SignalValue};
+use crate::util::{read_u64_be, read_varint_from_reader, skip_bytes};
 
 mod vc;
-use vc::parse_vc_block;
-pub use vc::{ChainIndex, ChainSlot, VcBlockMeta};
+pub use vc::{ChainIndex, ChainSlot, VcBlockIndexEntry, VcBlockMeta, VerifyFailure, VerifyReport};
+use vc::{parse_time_table, parse_vc_block, parse_vc_block_filtered, read_chain_index_entries};
 
 mod change;
-pub use change::{ValueChange, VcBlockChanges, build_changes};
+use change::{BlockValue, block_frame_value, value_at_in_block};
+pub use change::{
+    GroupedChanges, OwnedChanges, ValueChange, VcBlockChanges, WindowedChanges, build_changes,
+};
+
+mod merged;
+pub use merged::AllChanges;
+
+/// Default for [`ReaderOptions::prefetch_limit`].
+pub const DEFAULT_PREFETCH_LIMIT: u64 = 1 << 20;
 
 /// Controls how the [`FstReader`] parses data.
 #[derive(Debug, Clone)]
 pub struct ReaderOptions {
     /// When `true`, geometry blocks are loaded eagerly as soon as they appear.
     pub eager_geometry: bool,
+    /// Decompressors for value-change blocks whose pack marker is not a built-in one.
+    pub custom_chain_decompressors: Vec<CustomDecompressor>,
+    /// When `true`, a value-change block that runs past the end of the stream (e.g. a dump cut
+    /// short by a crash) ends iteration instead of failing [`FstReader::next_vc_block`].
+    pub tolerate_truncation: bool,
+    /// When `true`, value-change iterators yield two-state vectors as ASCII
+    /// [`SignalValue::Vector`]s rather than [`SignalValue::PackedBits`].
+    pub expand_packed_bits: bool,
+    /// Rejects value-change blocks whose header claims more than this many bytes of decode
+    /// memory (`required_memory`), before any of their payload is decoded.
+    pub max_block_memory: Option<u64>,
+    /// When `true` (the default), the header's `time_zero` is added to every timestamp the
+    /// reader reports. Disable it to work with the raw times stored in the blocks.
+    pub apply_time_zero: bool,
+    /// Value-change blocks whose payload is at most this many bytes are read into memory in one
+    /// go and parsed from there, sparing the seeks between the block prefix, its trailer, index,
+    /// and chains. Larger blocks (or every block, when `None`) are parsed straight from the
+    /// source.
+    pub prefetch_limit: Option<u64>,
+    /// When `true`, value-change blocks must be laid out exactly: the chains, chain index, and
+    /// time table have to tile the payload with no gaps or overlaps, and the index must hold one
+    /// entry per handle the block declares. Lenient parsing (the default) decodes whatever the
+    /// recorded offsets point at.
+    pub strict: bool,
 }
 
 impl Default for ReaderOptions {
     fn default() -> Self {
         Self {
             eager_geometry: true,
+            custom_chain_decompressors: Vec::new(),
+            tolerate_truncation: false,
+            expand_packed_bits: false,
+            max_block_memory: None,
+            apply_time_zero: true,
+            prefetch_limit: Some(DEFAULT_PREFETCH_LIMIT),
+            strict: false,
         }
     }
 }
 
+/// Overview of a trace, as returned by [`FstReader::summary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceSummary {
+    pub scope_count: u64,
+    /// Declared variables, aliases included.
+    pub var_count: u64,
+    /// Variables that share another variable's handle, or `None` before the hierarchy is loaded:
+    /// the header cannot tell aliases apart when, as in this crate's writer, they get handles of
+    /// their own.
+    pub alias_count: Option<u64>,
+    pub max_handle: u64,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub timescale_exponent: i8,
+    pub vc_section_count: u64,
+}
+
 /// Builder used to configure and construct a [`FstReader`].
 pub struct ReaderBuilder<R: ReadSeek> {
     source: R,
@@ -59,6 +130,48 @@ impl<R: ReadSeek> ReaderBuilder<R> {
         self
     }
 
+    /// Treats a value-change block truncated by the end of the stream as the end of the data.
+    pub fn tolerate_truncation(mut self, value: bool) -> Self {
+        self.options.tolerate_truncation = value;
+        self
+    }
+
+    /// Yields two-state vectors as ASCII [`SignalValue::Vector`]s instead of packed bits.
+    pub fn expand_packed_bits(mut self, value: bool) -> Self {
+        self.options.expand_packed_bits = value;
+        self
+    }
+
+    /// Rejects value-change blocks that declare more than `limit` bytes of decode memory.
+    pub fn max_block_memory(mut self, limit: Option<u64>) -> Self {
+        self.options.max_block_memory = limit;
+        self
+    }
+
+    /// Sets the largest value-change payload that is read into memory before parsing.
+    pub fn prefetch_limit(mut self, limit: Option<u64>) -> Self {
+        self.options.prefetch_limit = limit;
+        self
+    }
+
+    /// Rejects value-change blocks whose sections do not exactly partition the payload.
+    pub fn strict(mut self, value: bool) -> Self {
+        self.options.strict = value;
+        self
+    }
+
+    /// Controls whether the header's `time_zero` offset is added to reported timestamps.
+    pub fn apply_time_zero(mut self, value: bool) -> Self {
+        self.options.apply_time_zero = value;
+        self
+    }
+
+    /// Registers a decompressor for chains stored under a custom pack marker.
+    pub fn register_chain_decompressor(mut self, decompressor: CustomDecompressor) -> Self {
+        self.options.custom_chain_decompressors.push(decompressor);
+        self
+    }
+
     /// Consumes the builder, constructing the reader.
     pub fn build(self) -> Result<FstReader<R>> {
         FstReader::with_backend(self.source, self.options)
@@ -74,11 +187,14 @@ pub struct FstReader<R: ReadSeek> {
     blackout: Option<BlackoutBlock>,
     hierarchy: Option<HierarchyBlock>,
     current_vc_block: Option<VcBlockMeta>,
+    /// Value-change block index, built by the first scan that needs it.
+    block_index: Option<Arc<[VcBlockIndexEntry]>>,
+    data_start: u64,
 }
 
 impl<R: ReadSeek> FstReader<R> {
     fn with_backend(source: R, options: ReaderOptions) -> Result<Self> {
-        let mut backend = ReaderBackend::new(source);
+        let mut backend = open_backend(source)?;
         let header = Header::read(backend.get_mut())?;
         let mut reader = Self {
             backend,
@@ -88,8 +204,11 @@ impl<R: ReadSeek> FstReader<R> {
             blackout: None,
             hierarchy: None,
             current_vc_block: None,
+            block_index: None,
+            data_start: 0,
         };
         reader.parse_preamble()?;
+        reader.data_start = reader.backend.get_mut().stream_position()?;
         Ok(reader)
     }
 
@@ -97,12 +216,77 @@ impl<R: ReadSeek> FstReader<R> {
     pub fn builder(source: R) -> ReaderBuilder<R> {
         ReaderBuilder::new(source)
     }
+}
+
+impl FstReader<BufReader<File>> {
+    /// Opens the file at `path` with default options through a buffered file reader.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with_options(path, ReaderOptions::default())
+    }
+
+    /// Opens the file at `path` through a buffered file reader using `options`.
+    pub fn open_with_options(path: impl AsRef<Path>, options: ReaderOptions) -> Result<Self> {
+        let file = File::open(path)?;
+        ReaderBuilder::new(BufReader::new(file))
+            .options(options)
+            .build()
+    }
+}
+
+impl FstReader<Cursor<Vec<u8>>> {
+    /// Reads a trace already held in memory, taking ownership of the buffer.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        ReaderBuilder::new(Cursor::new(data)).build()
+    }
+}
+
+impl<'a> FstReader<Cursor<&'a [u8]>> {
+    /// Reads a trace from a borrowed buffer without copying it.
+    pub fn from_slice(data: &'a [u8]) -> Result<Self> {
+        ReaderBuilder::new(Cursor::new(data)).build()
+    }
+}
 
+#[cfg(feature = "mmap")]
+impl FstReader<Cursor<MemoryMap>> {
+    /// Opens the file at `path` through a read-only memory map instead of buffered file reads.
+    pub fn from_mmap(path: impl AsRef<Path>) -> Result<Self> {
+        let map = MemoryMap::open(path)?;
+        ReaderBuilder::new(Cursor::new(map)).build()
+    }
+}
+
+impl<R: ReadSeek> FstReader<R> {
     /// Returns the parsed header metadata.
     pub fn header(&self) -> &Header {
         &self.header
     }
 
+    /// Summarises the trace without decoding any value changes. Counts come from the hierarchy
+    /// once it has been loaded and from the header otherwise, which leaves the alias count
+    /// unknown.
+    pub fn summary(&self) -> TraceSummary {
+        let header = &self.header;
+        let (scope_count, var_count, alias_count) = match &self.hierarchy {
+            Some(hier) => (
+                hier.scopes.len() as u64,
+                hier.variables.len() as u64,
+                Some(hier.variables.iter().filter(|var| var.is_alias).count() as u64),
+            ),
+            None => (header.scope_count, header.var_count, None),
+        };
+        TraceSummary {
+            scope_count,
+            var_count,
+            alias_count,
+            max_handle: header.max_handle,
+            start_time: header.start_time,
+            end_time: header.end_time,
+            timescale_exponent: header.timescale_exponent,
+            vc_section_count: header.vc_section_count,
+        }
+    }
+
     /// Returns the reader options used to configure this reader.
     pub fn options(&self) -> &ReaderOptions {
         &self.options
@@ -123,6 +307,63 @@ impl<R: ReadSeek> FstReader<R> {
         self.hierarchy.as_ref()
     }
 
+    /// Cross-checks the loaded geometry against the hierarchy and header: the geometry must hold
+    /// the header's `max_handle` entries and cover every handle a variable declares, and each
+    /// variable's declared length must match its geometry entry (fixed-width signals by bit count,
+    /// reals by byte size or unset, variable-length signals unset). Succeeds trivially when either
+    /// block has not been loaded.
+    pub fn validate_metadata(&self) -> Result<()> {
+        let (Some(geom), Some(hier)) = (&self.geometry, &self.hierarchy) else {
+            return Ok(());
+        };
+        if geom.entries.len() as u64 != self.header.max_handle {
+            return Err(Error::invalid(format!(
+                "geometry holds {} entries but the header declares {} handles",
+                geom.entries.len(),
+                self.header.max_handle
+            )));
+        }
+        let max_handle = hier
+            .variables
+            .iter()
+            .map(|var| var.handle)
+            .max()
+            .unwrap_or(0);
+        if max_handle as usize > geom.entries.len() {
+            return Err(Error::invalid(format!(
+                "geometry holds {} entries but the hierarchy declares handles up to {max_handle}",
+                geom.entries.len()
+            )));
+        }
+        for var in &hier.variables {
+            let entry = geom.entry(var.handle).ok_or_else(|| {
+                Error::invalid(format!(
+                    "variable '{}' has invalid handle {}",
+                    var.name, var.handle
+                ))
+            })?;
+            let consistent = match entry {
+                GeomEntry::Fixed(width) => var.length == Some(*width),
+                GeomEntry::Real => matches!(var.length, None | Some(8)),
+                GeomEntry::ShortReal => matches!(var.length, None | Some(4)),
+                GeomEntry::Variable => var.length.is_none(),
+            };
+            if !consistent {
+                return Err(Error::invalid(format!(
+                    "variable '{}' (handle {}) declares length {:?} but its geometry is {entry:?}",
+                    var.name, var.handle, var.length
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` when the file was wrapped in an `FST_BL_ZWRAPPER` envelope and is being read
+    /// from its inflated contents.
+    pub fn is_zlib_wrapped(&self) -> bool {
+        self.backend.is_inflated()
+    }
+
     /// Returns a mutable reference to the underlying reader backend.
     pub fn raw_reader(&mut self) -> &mut ReaderBackend<R> {
         &mut self.backend
@@ -133,76 +374,519 @@ impl<R: ReadSeek> FstReader<R> {
         self.backend.into_inner()
     }
 
-    /// Placeholder for future block iteration support.
+    /// Walks every remaining block from the current stream position without decoding value
+    /// changes. Geometry, blackout, and hierarchy blocks encountered on the way are loaded, so this
+    /// can be used to validate that a file is fully parseable.
     pub fn skip_remaining(&mut self) -> Result<()> {
-        Err(Error::unsupported(
-            "block iteration not yet implemented in fst-format crate",
-        ))
+        self.current_vc_block = None;
+        while let Some(block_type) = self.read_block_tag()? {
+            match block_type {
+                BlockType::VcData | BlockType::VcDataDynAlias | BlockType::VcDataDynAlias2 => {
+                    let reader = self.backend.get_mut();
+                    let section_length = read_u64_be(reader)?;
+                    let payload_len = payload_length(section_length)?;
+                    skip_bytes(reader, payload_len)?;
+                }
+                other => self.consume_metadata_block(other, false)?,
+            }
+        }
+        Ok(())
     }
 
-    /// Returns metadata for the next value-change block, advancing the stream.
+    /// Returns metadata for the next value-change block, advancing the stream. Metadata blocks
+    /// met on the way are loaded, including any that trail the last value-change block, so once
+    /// this returns `None` a hierarchy or geometry written after the value changes is available.
     pub fn next_vc_block(&mut self) -> Result<Option<VcBlockMeta>> {
-        loop {
-            let reader = self.backend.get_mut();
-            let mut tag = [0u8; 1];
-            match reader.read_exact(&mut tag) {
-                Ok(()) => {}
-                Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
-                Err(err) => return Err(err.into()),
+        while let Some(block_type) = self.read_block_tag()? {
+            match block_type {
+                BlockType::VcData | BlockType::VcDataDynAlias | BlockType::VcDataDynAlias2 => {
+                    let body_start = self.backend.get_mut().stream_position()?;
+                    return match self.read_vc_block_body(block_type) {
+                        Ok(block) => Ok(Some(block)),
+                        Err(_)
+                            if self.options.tolerate_truncation
+                                && self.section_exceeds_stream(body_start)? =>
+                        {
+                            self.backend.get_mut().seek(SeekFrom::End(0))?;
+                            Ok(None)
+                        }
+                        Err(err) => Err(err),
+                    };
+                }
+                other => self.consume_metadata_block(other, false)?,
             }
+        }
+        Ok(None)
+    }
 
-            let block_type = BlockType::try_from(tag[0])
-                .map_err(|_| Error::invalid(format!("unknown block type {:02x}", tag[0])))?;
+    /// Decodes only the time table of the next value-change block and advances past the block.
+    /// The frame, chain index, and chains are skipped, which makes this considerably cheaper than
+    /// [`Self::next_vc_block`] when only the timestamps are needed.
+    pub fn next_time_table(&mut self) -> Result<Option<TimeTable>> {
+        while let Some(block_type) = self.read_block_tag()? {
+            match block_type {
+                BlockType::VcData | BlockType::VcDataDynAlias | BlockType::VcDataDynAlias2 => {
+                    let reader = self.backend.get_mut();
+                    let section_length = read_u64_be(reader)?;
+                    let section_start = reader.stream_position()?;
+                    let payload_len = payload_length(section_length)?;
+                    return parse_time_table(reader, section_start, payload_len).map(Some);
+                }
+                other => self.consume_metadata_block(other, false)?,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Scans every value-change block in the file and records where it lives and which time range
+    /// it covers. Only the block prefix up to `vc_max_handle` is read; chains, index tables, and
+    /// time tables are skipped. Metadata blocks met during the scan are loaded as usual, and the
+    /// stream position is restored afterwards.
+    ///
+    /// Offsets refer to the block tag and can be passed to [`Self::read_block_at`]. For files
+    /// wrapped in a z-wrapper envelope they are offsets into the inflated stream.
+    ///
+    /// The index is built on first use and cached, so later calls do not rescan the file.
+    pub fn build_block_index(&mut self) -> Result<Vec<VcBlockIndexEntry>> {
+        Ok(self.cached_block_index()?.to_vec())
+    }
 
+    /// Returns the cached block index, scanning the file for it on first use.
+    fn cached_block_index(&mut self) -> Result<Arc<[VcBlockIndexEntry]>> {
+        if let Some(index) = &self.block_index {
+            return Ok(Arc::clone(index));
+        }
+        let index: Arc<[VcBlockIndexEntry]> = self.scan_block_index()?.into();
+        self.block_index = Some(Arc::clone(&index));
+        Ok(index)
+    }
+
+    fn scan_block_index(&mut self) -> Result<Vec<VcBlockIndexEntry>> {
+        let resume = self.backend.get_mut().stream_position()?;
+        self.backend
+            .get_mut()
+            .seek(SeekFrom::Start(self.data_start))?;
+        let mut entries = Vec::new();
+        loop {
+            let offset = self.backend.get_mut().stream_position()?;
+            let Some(block_type) = self.read_block_tag()? else {
+                break;
+            };
             match block_type {
                 BlockType::VcData | BlockType::VcDataDynAlias | BlockType::VcDataDynAlias2 => {
+                    let reader = self.backend.get_mut();
                     let section_length = read_u64_be(reader)?;
                     let section_start = reader.stream_position()?;
                     let payload_len = payload_length(section_length)?;
-                    let meta = parse_vc_block(reader, block_type, section_start, payload_len)?;
+                    let begin_time = read_u64_be(reader)?;
+                    let end_time = read_u64_be(reader)?;
+                    skip_bytes(reader, 8)?; // required_memory
+                    read_varint_from_reader(reader)?; // frame_uncompressed_len
+                    let (frame_compressed_len, _) = read_varint_from_reader(reader)?;
+                    read_varint_from_reader(reader)?; // frame_max_handle
+                    skip_bytes(reader, frame_compressed_len)?;
+                    let (vc_max_handle, _) = read_varint_from_reader(reader)?;
                     let block_end = section_start.checked_add(payload_len).ok_or_else(|| {
                         Error::invalid("value-change payload exceeds file bounds")
                     })?;
                     reader.seek(SeekFrom::Start(block_end))?;
-                    return Ok(Some(meta));
+                    entries.push(VcBlockIndexEntry {
+                        offset,
+                        block_type,
+                        begin_time,
+                        end_time,
+                        vc_max_handle,
+                    });
                 }
-                BlockType::Geometry => {
-                    let section_length = read_u64_be(reader)?;
-                    let payload_len = payload_length(section_length)?;
-                    if self.options.eager_geometry || self.geometry.is_none() {
-                        let geom = Self::read_geometry_block(reader, section_length)?;
-                        self.geometry = Some(geom);
-                    } else {
-                        skip_bytes(reader, payload_len)?;
-                    }
+                other => self.consume_metadata_block(other, false)?,
+            }
+        }
+        self.backend.get_mut().seek(SeekFrom::Start(resume))?;
+        Ok(entries)
+    }
+
+    /// Returns the handles that never change in any value-change block, i.e. whose value is only
+    /// ever given by the frames. Only each block's chain index is read. Handles the index marks as
+    /// aliases are left out; they follow their canonical handle, which is reported on its own.
+    /// The stream position is restored afterwards.
+    pub fn constant_signals(&mut self) -> Result<Vec<u32>> {
+        let saved = self.backend.get_mut().stream_position()?;
+        let index = self.build_block_index()?;
+        let handle_count = self
+            .geometry
+            .as_ref()
+            .ok_or_else(|| Error::invalid("geometry metadata is required to list signals"))?
+            .entries
+            .len();
+        let mut changed = vec![false; handle_count];
+        let mut aliased = vec![false; handle_count];
+        for entry in &index {
+            let reader = self.backend.get_mut();
+            reader.seek(SeekFrom::Start(entry.offset + 1))?;
+            let section_length = read_u64_be(reader)?;
+            let section_start = reader.stream_position()?;
+            let payload_len = payload_length(section_length)?;
+            let entries =
+                read_chain_index_entries(reader, entry.block_type, section_start, payload_len)?;
+            for (idx, chain) in entries.iter().enumerate().take(handle_count) {
+                match chain {
+                    ChainIndexEntry::Data { .. } => changed[idx] = true,
+                    ChainIndexEntry::Alias { .. } => aliased[idx] = true,
+                    ChainIndexEntry::Empty => {}
                 }
-                BlockType::Blackout => {
-                    let section_length = read_u64_be(reader)?;
-                    let payload_len = payload_length(section_length)?;
-                    let payload_len_usize = usize::try_from(payload_len).map_err(|_| {
-                        Error::invalid("blackout payload exceeds addressable memory")
-                    })?;
-                    let mut buf = vec![0u8; payload_len_usize];
-                    reader.read_exact(&mut buf)?;
-                    self.blackout = Some(BlackoutBlock::decode(&buf)?);
+            }
+        }
+        self.backend.get_mut().seek(SeekFrom::Start(saved))?;
+        Ok((1..=handle_count as u32)
+            .filter(|handle| {
+                let idx = *handle as usize - 1;
+                !changed[idx] && !aliased[idx]
+            })
+            .collect())
+    }
+
+    /// Decodes every value-change block in the file, decompressing the frame, each chain, and
+    /// the time table, and checks that each decodes to the length its block declares. Failing
+    /// blocks are collected into the report instead of aborting the scan.
+    ///
+    /// Errors locating the blocks themselves are returned directly. Any in-progress
+    /// [`Self::next_value_changes`] iteration is reset; the stream position is restored.
+    pub fn verify(&mut self) -> Result<VerifyReport> {
+        self.verify_with(false)
+    }
+
+    /// Like [`Self::verify`], but when `stop_on_failure` is set the scan ends at the first
+    /// failing block.
+    pub fn verify_with(&mut self, stop_on_failure: bool) -> Result<VerifyReport> {
+        let resume = self.backend.get_mut().stream_position()?;
+        let entries = self.build_block_index()?;
+        let mut report = VerifyReport::default();
+        for entry in entries {
+            report.blocks_checked += 1;
+            if let Err(err) = self.read_block_at(entry.offset) {
+                report.failures.push(VerifyFailure {
+                    offset: entry.offset,
+                    message: err.to_string(),
+                });
+                if stop_on_failure {
+                    break;
                 }
-                BlockType::Hierarchy | BlockType::HierarchyLz4 | BlockType::HierarchyLz4Duo => {
-                    let hier = Self::read_hierarchy_block(reader, block_type)?;
-                    self.hierarchy = Some(hier);
+            }
+        }
+        self.backend.get_mut().seek(SeekFrom::Start(resume))?;
+        Ok(report)
+    }
+
+    /// Parses the value-change block whose tag starts at `offset` (as reported by
+    /// [`Self::build_block_index`]). The stream is left positioned after the block.
+    pub fn read_block_at(&mut self, offset: u64) -> Result<VcBlockMeta> {
+        self.current_vc_block = None;
+        self.backend.get_mut().seek(SeekFrom::Start(offset))?;
+        match self.read_block_tag()? {
+            Some(
+                block_type @ (BlockType::VcData
+                | BlockType::VcDataDynAlias
+                | BlockType::VcDataDynAlias2),
+            ) => self.read_vc_block_body(block_type),
+            Some(other) => Err(Error::invalid(format!(
+                "block at offset {offset} is {other:?}, not a value-change block"
+            ))),
+            None => Err(Error::invalid(format!(
+                "offset {offset} lies beyond the last block"
+            ))),
+        }
+    }
+
+    /// Positions the reader on the first value-change block covering `time` and returns its
+    /// metadata. `time` uses the same scale as [`ValueChange::timestamp`], i.e. the header's
+    /// `time_zero` is added to the block bounds before comparing unless
+    /// [`ReaderOptions::apply_time_zero`] is disabled.
+    ///
+    /// The scan always restarts from the first block after the preamble, so seeking backwards is
+    /// supported. Blocks ending before `time` are skipped using their section length without
+    /// decoding. When `time` falls before the first block or inside a gap between blocks, the next
+    /// block is returned; when it lies beyond every block, `None` is returned. On success the stream
+    /// is left at the start of the returned block so a following [`Self::next_value_changes`]
+    /// decodes it.
+    pub fn seek_to_time(&mut self, time: u64) -> Result<Option<VcBlockMeta>> {
+        self.current_vc_block = None;
+        let Some(position) = self.locate_vc_block(time)? else {
+            return Ok(None);
+        };
+        self.backend.get_mut().seek(SeekFrom::Start(position))?;
+        let meta = self.next_vc_block()?;
+        self.backend.get_mut().seek(SeekFrom::Start(position))?;
+        Ok(meta)
+    }
+
+    /// Returns the value `handle` holds at absolute `time`: its last change at or before `time`,
+    /// looked up in the blocks starting at or before `time`, latest first. Only the chain of
+    /// `handle` (after resolving aliases) is decompressed in each block read. A block without
+    /// changes for the handle supplies it from its frame when the frame covers it; otherwise the
+    /// search continues with the earlier block. Returns `None` when the file has no value-change
+    /// blocks or `time` precedes the handle's first recorded value. The block index is cached
+    /// after the first query and the stream position is restored afterwards.
+    pub fn value_at(&mut self, handle: u32, time: u64) -> Result<Option<SignalValue<'static>>> {
+        if handle == 0 {
+            return Err(Error::invalid("handle 0 is not a valid signal handle"));
+        }
+        let saved = self.backend.get_mut().stream_position()?;
+        let result = self.value_at_inner(handle, time);
+        self.backend.get_mut().seek(SeekFrom::Start(saved))?;
+        result
+    }
+
+    fn value_at_inner(&mut self, handle: u32, time: u64) -> Result<Option<SignalValue<'static>>> {
+        let index = self.cached_block_index()?;
+        let time_zero = self.time_zero();
+        let started =
+            index.partition_point(|entry| entry.begin_time.saturating_add(time_zero) <= time);
+
+        for entry in index[..started].iter().rev() {
+            let block = self.read_block_filtered(entry.offset, Some(handle))?;
+            let geom = self.geometry.as_ref().ok_or_else(|| {
+                Error::invalid("geometry metadata is required before querying values")
+            })?;
+            let canonical = block.index.resolve_alias(handle);
+            match value_at_in_block(&block, geom, canonical, time, time_zero)? {
+                BlockValue::Changed(value) => return Ok(Some(value)),
+                BlockValue::Unchanged => {
+                    // Frames hold the end-of-block state, which equals the state throughout a
+                    // block that never changes the handle.
+                    if let Some(value) = block_frame_value(&block, geom, canonical)? {
+                        return Ok(Some(value));
+                    }
                 }
-                BlockType::Skip => {
+                BlockValue::ChangesLater => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses the value-change block at `offset`, decompressing only the chain `only_handle` reads
+    /// when it is given. Unlike [`Self::read_block_at`], the current block is left untouched.
+    fn read_block_filtered(
+        &mut self,
+        offset: u64,
+        only_handle: Option<u32>,
+    ) -> Result<VcBlockMeta> {
+        self.backend.get_mut().seek(SeekFrom::Start(offset))?;
+        let block_type = self
+            .read_block_tag()?
+            .ok_or_else(|| Error::decode("value-change block vanished while seeking"))?;
+        let reader = self.backend.get_mut();
+        let section_length = read_u64_be(reader)?;
+        let section_start = reader.stream_position()?;
+        let payload_len = payload_length(section_length)?;
+        parse_vc_block_filtered(
+            reader,
+            block_type,
+            section_start,
+            payload_len,
+            &self.options,
+            self.header.endianness,
+            only_handle,
+        )
+    }
+
+    /// Decodes every value-change block of the file into owned changes, parsing the blocks on the
+    /// rayon thread pool. Block payloads are read serially first; the result is identical to
+    /// collecting [`Self::all_value_changes`] from the start of the data section. The stream
+    /// position is restored afterwards.
+    #[cfg(feature = "parallel")]
+    pub fn decode_all_parallel(&mut self) -> Result<Vec<ValueChange<'static>>> {
+        let saved = self.backend.get_mut().stream_position()?;
+        let index = self.build_block_index()?;
+        let mut payloads = Vec::with_capacity(index.len());
+        for entry in &index {
+            let reader = self.backend.get_mut();
+            reader.seek(SeekFrom::Start(entry.offset + 1))?;
+            let payload_len = payload_length(read_u64_be(reader)?)?;
+            let payload_len_usize = usize::try_from(payload_len)
+                .map_err(|_| Error::invalid("value-change payload exceeds addressable memory"))?;
+            let mut payload = vec![0u8; payload_len_usize];
+            reader.read_exact(&mut payload)?;
+            payloads.push((entry.block_type, payload));
+        }
+        self.backend.get_mut().seek(SeekFrom::Start(saved))?;
+
+        let geom = self.geometry.as_ref().ok_or_else(|| {
+            Error::invalid("geometry metadata is required before iterating value changes")
+        })?;
+        let options = &self.options;
+        let endianness = self.header.endianness;
+        let time_zero = self.time_zero();
+        let decoded = payloads
+            .into_par_iter()
+            .map(
+                |(block_type, payload)| -> Result<Vec<ValueChange<'static>>> {
+                    let payload_len = payload.len() as u64;
+                    let block = parse_vc_block(
+                        &mut Cursor::new(payload),
+                        block_type,
+                        0,
+                        payload_len,
+                        options,
+                        endianness,
+                    )?;
+                    block
+                        .changes(geom, time_zero)?
+                        .expand_packed_bits(options.expand_packed_bits)
+                        .owned()
+                        .collect()
+                },
+            )
+            .collect::<Result<Vec<_>>>()?;
+
+        let changes: Vec<ValueChange<'static>> = decoded.into_iter().flatten().collect();
+        if let Some(pair) = changes
+            .windows(2)
+            .find(|pair| pair[1].timestamp < pair[0].timestamp)
+        {
+            return Err(Error::decode(format!(
+                "value-change blocks are out of order: timestamp {} follows {}",
+                pair[1].timestamp, pair[0].timestamp
+            )));
+        }
+        Ok(changes)
+    }
+
+    /// Offset added to block-relative timestamps, or zero when
+    /// [`ReaderOptions::apply_time_zero`] is disabled.
+    fn time_zero(&self) -> u64 {
+        if self.options.apply_time_zero {
+            self.header.time_zero
+        } else {
+            0
+        }
+    }
+
+    /// Returns the value every handle covered by the first value-change block's frame holds at the
+    /// block's begin time, decoded against the geometry. A change at the begin time gives the
+    /// value; a handle the block never changes takes it from the frame. Handles whose first change
+    /// comes later in the block are omitted, like [`Self::value_at`] before a first change, because
+    /// the frame holds the end-of-block state. The block's chains are decoded, but only changes at
+    /// the begin time are walked. Metadata stored after the value-change blocks is loaded first.
+    /// `Variable` handles have no frame value and are omitted; a trace without value-change blocks
+    /// yields an empty list. The stream position is restored afterwards.
+    pub fn initial_values(&mut self) -> Result<Vec<(u32, SignalValue<'static>)>> {
+        let saved = self.backend.get_mut().stream_position()?;
+        let result = self.initial_values_inner();
+        self.backend.get_mut().seek(SeekFrom::Start(saved))?;
+        result
+    }
+
+    fn initial_values_inner(&mut self) -> Result<Vec<(u32, SignalValue<'static>)>> {
+        let index = self.cached_block_index()?;
+        let Some(first) = index.first() else {
+            return Ok(Vec::new());
+        };
+        let block = self.read_block_filtered(first.offset, None)?;
+        let geom = self.geometry.as_ref().ok_or_else(|| {
+            Error::invalid("geometry metadata is required before decoding initial values")
+        })?;
+        let time_zero = self.time_zero();
+        let begin_time = block.header.begin_time.saturating_add(time_zero);
+        let mut values = block.frame.decode_values(geom, self.header.endianness)?;
+
+        let mut at_begin: HashMap<u32, SignalValue<'static>> = HashMap::new();
+        for change in block
+            .changes(geom, time_zero)?
+            .expand_packed_bits(self.options.expand_packed_bits)
+        {
+            let change = change?;
+            if change.timestamp > begin_time {
+                break;
+            }
+            at_begin
+                .entry(change.handle)
+                .or_insert_with(|| change.value.into_owned());
+        }
+        values.retain_mut(|(handle, value)| {
+            if let Some(changed) = at_begin.remove(handle) {
+                *value = changed;
+                return true;
+            }
+            let canonical = block.index.resolve_alias(*handle);
+            block
+                .chains
+                .get((canonical - 1) as usize)
+                .is_none_or(|chain| chain.is_none())
+        });
+        Ok(values)
+    }
+
+    /// Returns the value every handle covered by the last value-change block's frame holds at the
+    /// end of the trace: its last change in that block, falling back to the frame. This is the
+    /// state to seed [`FstWriter::append`](crate::writer::FstWriter::append) with. Metadata stored
+    /// after the value-change blocks is loaded first. `Variable` handles are omitted; a trace
+    /// without value-change blocks yields an empty list. The stream position is restored
+    /// afterwards.
+    pub fn final_values(&mut self) -> Result<Vec<(u32, SignalValue<'static>)>> {
+        let saved = self.backend.get_mut().stream_position()?;
+        let result = self.final_values_inner();
+        self.backend.get_mut().seek(SeekFrom::Start(saved))?;
+        result
+    }
+
+    fn final_values_inner(&mut self) -> Result<Vec<(u32, SignalValue<'static>)>> {
+        let index = self.cached_block_index()?;
+        let Some(last) = index.last() else {
+            return Ok(Vec::new());
+        };
+        let block = self.read_block_filtered(last.offset, None)?;
+        let geom = self.geometry.as_ref().ok_or_else(|| {
+            Error::invalid("geometry metadata is required before decoding final values")
+        })?;
+        let mut values = block.frame.decode_values(geom, self.header.endianness)?;
+
+        let mut latest: HashMap<u32, SignalValue<'static>> = HashMap::new();
+        for change in block
+            .changes(geom, self.time_zero())?
+            .expand_packed_bits(self.options.expand_packed_bits)
+        {
+            let change = change?;
+            latest.insert(change.handle, change.value.into_owned());
+        }
+        for (handle, value) in &mut values {
+            if let Some(changed) = latest.remove(handle) {
+                *value = changed;
+            }
+        }
+        Ok(values)
+    }
+
+    /// Scans value-change blocks from the start of the data section, skipping their payloads,
+    /// until one ends at or after `time`, and returns the offset of its tag.
+    fn locate_vc_block(&mut self, time: u64) -> Result<Option<u64>> {
+        let time_zero = self.time_zero();
+        self.backend
+            .get_mut()
+            .seek(SeekFrom::Start(self.data_start))?;
+        loop {
+            let position = self.backend.get_mut().stream_position()?;
+            let Some(block_type) = self.read_block_tag()? else {
+                return Ok(None);
+            };
+            match block_type {
+                BlockType::VcData | BlockType::VcDataDynAlias | BlockType::VcDataDynAlias2 => {
+                    let reader = self.backend.get_mut();
                     let section_length = read_u64_be(reader)?;
+                    let section_start = reader.stream_position()?;
                     let payload_len = payload_length(section_length)?;
-                    skip_bytes(reader, payload_len)?;
-                }
-                BlockType::ZWrapper => {
-                    return Err(Error::unsupported(
-                        "zlib wrapper blocks are not yet supported",
-                    ));
-                }
-                BlockType::Header => {
-                    return Err(Error::invalid("duplicate header block encountered"));
+                    skip_bytes(reader, 8)?; // begin_time
+                    let end_time = read_u64_be(reader)?
+                        .checked_add(time_zero)
+                        .ok_or_else(|| Error::decode("block end time overflow"))?;
+                    if end_time >= time {
+                        return Ok(Some(position));
+                    }
+                    let block_end = section_start.checked_add(payload_len).ok_or_else(|| {
+                        Error::invalid("value-change payload exceeds file bounds")
+                    })?;
+                    reader.seek(SeekFrom::Start(block_end))?;
                 }
+                other => self.consume_metadata_block(other, false)?,
             }
         }
     }
@@ -219,133 +903,155 @@ impl<R: ReadSeek> FstReader<R> {
         let geom = self.geometry.as_ref().ok_or_else(|| {
             Error::invalid("geometry metadata is required before iterating value changes")
         })?;
-        let time_zero = self.header.time_zero;
+        let time_zero = self.time_zero();
         let block_ref = self.current_vc_block.as_ref().expect("block just stored");
-        block_ref.changes(geom, time_zero).map(Some)
+        let expand = self.options.expand_packed_bits;
+        block_ref
+            .changes(geom, time_zero)
+            .map(|changes| Some(changes.expand_packed_bits(expand)))
     }
 
-    fn parse_preamble(&mut self) -> Result<()> {
-        let reader = self.backend.get_mut();
-        loop {
-            let mut tag = [0u8; 1];
-            match reader.read_exact(&mut tag) {
-                Ok(()) => {}
-                Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
-                Err(err) => return Err(err.into()),
-            }
-            let block_type = match BlockType::try_from(tag[0]) {
-                Ok(bt) => bt,
-                Err(_) => return Err(Error::invalid(format!("unknown block type {:02x}", tag[0]))),
-            };
+    /// Returns a single iterator over the value changes of every remaining block, in file order.
+    /// Changes are yielded as owned values so the iterator can advance across block boundaries.
+    pub fn all_value_changes(&mut self) -> Result<AllChanges<'_, R>> {
+        Ok(AllChanges::new(self))
+    }
 
+    fn parse_preamble(&mut self) -> Result<()> {
+        while let Some(block_type) = self.read_block_tag()? {
             match block_type {
-                BlockType::Geometry => {
-                    let section_length = read_u64_be(reader)?;
-                    let payload_len = payload_length(section_length)?;
-                    if self.options.eager_geometry || self.geometry.is_none() {
-                        let geom = Self::read_geometry_block(reader, section_length)?;
-                        self.geometry = Some(geom);
-                    } else {
-                        skip_bytes(reader, payload_len)?;
-                    }
-                }
-                BlockType::Blackout => {
-                    let section_length = read_u64_be(reader)?;
-                    let payload_len = payload_length(section_length)?;
-                    let payload_len_usize = usize::try_from(payload_len).map_err(|_| {
-                        Error::invalid("blackout payload exceeds addressable memory")
-                    })?;
-                    let mut buf = vec![0u8; payload_len_usize];
-                    reader.read_exact(&mut buf)?;
-                    self.blackout = Some(BlackoutBlock::decode(&buf)?);
-                }
-                BlockType::Hierarchy | BlockType::HierarchyLz4 | BlockType::HierarchyLz4Duo => {
-                    let hier = Self::read_hierarchy_block(reader, block_type)?;
-                    self.hierarchy = Some(hier);
-                }
-                BlockType::Skip => {
-                    let section_length = read_u64_be(reader)?;
-                    let payload_len = payload_length(section_length)?;
-                    skip_bytes(reader, payload_len)?;
-                }
                 BlockType::VcData | BlockType::VcDataDynAlias | BlockType::VcDataDynAlias2 => {
-                    reader.seek(SeekFrom::Current(-1))?;
+                    self.backend.get_mut().seek(SeekFrom::Current(-1))?;
                     break;
                 }
-                BlockType::ZWrapper => {
-                    return Err(Error::unsupported(
-                        "zlib wrapper blocks are not yet supported",
-                    ));
-                }
-                BlockType::Header => {
-                    return Err(Error::invalid("duplicate header block encountered"));
-                }
+                other => self.consume_metadata_block(other, false)?,
             }
         }
-
         Ok(())
     }
 
     fn load_metadata_until_next_vc(&mut self) -> Result<()> {
         loop {
-            let reader = self.backend.get_mut();
-            let position = reader.stream_position()?;
-            let mut tag = [0u8; 1];
-            match reader.read_exact(&mut tag) {
-                Ok(()) => {}
-                Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(()),
-                Err(err) => return Err(err.into()),
-            }
-
-            let block_type = BlockType::try_from(tag[0])
-                .map_err(|_| Error::invalid(format!("unknown block type {:02x}", tag[0])))?;
-
+            let position = self.backend.get_mut().stream_position()?;
+            let Some(block_type) = self.read_block_tag()? else {
+                return Ok(());
+            };
             match block_type {
                 BlockType::VcData | BlockType::VcDataDynAlias | BlockType::VcDataDynAlias2 => {
-                    reader.seek(SeekFrom::Start(position))?;
+                    self.backend.get_mut().seek(SeekFrom::Start(position))?;
                     return Ok(());
                 }
-                BlockType::Geometry => {
-                    let section_length = read_u64_be(reader)?;
+                other => self.consume_metadata_block(other, true)?,
+            }
+        }
+    }
+
+    /// Returns `true` when the section whose length word starts at `body_start` is cut short by
+    /// the end of the stream.
+    fn section_exceeds_stream(&mut self, body_start: u64) -> Result<bool> {
+        let reader = self.backend.get_mut();
+        let stream_end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(body_start))?;
+        let section_length = match read_u64_be(reader) {
+            Ok(length) => length,
+            Err(Error::Io(err)) if err.kind() == ErrorKind::UnexpectedEof => return Ok(true),
+            Err(err) => return Err(err),
+        };
+        Ok(body_start.saturating_add(section_length) > stream_end)
+    }
+
+    /// Parses a value-change block whose tag has just been read, leaving the stream after it.
+    fn read_vc_block_body(&mut self, block_type: BlockType) -> Result<VcBlockMeta> {
+        let reader = self.backend.get_mut();
+        let section_length = read_u64_be(reader)?;
+        let section_start = reader.stream_position()?;
+        let payload_len = payload_length(section_length)?;
+        parse_vc_block(
+            reader,
+            block_type,
+            section_start,
+            payload_len,
+            &self.options,
+            self.header.endianness,
+        )
+    }
+
+    /// Reads the next block tag, returning `None` once the end of the stream is reached.
+    fn read_block_tag(&mut self) -> Result<Option<BlockType>> {
+        let reader = self.backend.get_mut();
+        let mut tag = [0u8; 1];
+        match reader.read_exact(&mut tag) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        BlockType::try_from(tag[0])
+            .map(Some)
+            .map_err(|_| Error::invalid(format!("unknown block type {:02x}", tag[0])))
+    }
+
+    /// Consumes a non value-change block whose tag has just been read, updating the cached
+    /// metadata. Geometry blocks are only decoded when `force_geometry` is set, eager geometry is
+    /// enabled, or no geometry has been loaded yet.
+    fn consume_metadata_block(
+        &mut self,
+        block_type: BlockType,
+        force_geometry: bool,
+    ) -> Result<()> {
+        let load_geometry =
+            force_geometry || self.options.eager_geometry || self.geometry.is_none();
+        let reader = self.backend.get_mut();
+        match block_type {
+            BlockType::Geometry => {
+                let section_length = read_u64_be(reader)?;
+                let payload_len = payload_length(section_length)?;
+                if load_geometry {
                     let geom = Self::read_geometry_block(reader, section_length)?;
                     self.geometry = Some(geom);
-                }
-                BlockType::Blackout => {
-                    let section_length = read_u64_be(reader)?;
-                    let payload_len = payload_length(section_length)?;
-                    let payload_len_usize = usize::try_from(payload_len).map_err(|_| {
-                        Error::invalid("blackout payload exceeds addressable memory")
-                    })?;
-                    let mut buf = vec![0u8; payload_len_usize];
-                    reader.read_exact(&mut buf)?;
-                    self.blackout = Some(BlackoutBlock::decode(&buf)?);
-                }
-                BlockType::Hierarchy | BlockType::HierarchyLz4 | BlockType::HierarchyLz4Duo => {
-                    let hier = Self::read_hierarchy_block(reader, block_type)?;
-                    self.hierarchy = Some(hier);
-                }
-                BlockType::Skip => {
-                    let section_length = read_u64_be(reader)?;
-                    let payload_len = payload_length(section_length)?;
+                } else {
                     skip_bytes(reader, payload_len)?;
                 }
-                BlockType::ZWrapper => {
-                    return Err(Error::unsupported(
-                        "zlib wrapper blocks are not yet supported",
-                    ));
-                }
-                BlockType::Header => {
-                    return Err(Error::invalid("duplicate header block encountered"));
-                }
+            }
+            BlockType::Blackout => {
+                let section_length = read_u64_be(reader)?;
+                let payload_len = payload_length(section_length)?;
+                let payload_len_usize = usize::try_from(payload_len)
+                    .map_err(|_| Error::invalid("blackout payload exceeds addressable memory"))?;
+                let mut buf = vec![0u8; payload_len_usize];
+                reader.read_exact(&mut buf)?;
+                self.blackout = Some(BlackoutBlock::decode(&buf)?);
+            }
+            BlockType::Hierarchy | BlockType::HierarchyLz4 | BlockType::HierarchyLz4Duo => {
+                let hier = Self::read_hierarchy_block(reader, block_type)?;
+                self.hierarchy = Some(hier);
+            }
+            BlockType::Skip => {
+                let section_length = read_u64_be(reader)?;
+                let payload_len = payload_length(section_length)?;
+                skip_bytes(reader, payload_len)?;
+            }
+            BlockType::ZWrapper => {
+                return Err(Error::invalid(
+                    "zlib wrapper block may only appear at the start of the file",
+                ));
+            }
+            BlockType::Header => {
+                return Err(Error::invalid("duplicate header block encountered"));
+            }
+            BlockType::VcData | BlockType::VcDataDynAlias | BlockType::VcDataDynAlias2 => {
+                return Err(Error::invalid(
+                    "value-change block passed to metadata dispatch",
+                ));
             }
         }
+        Ok(())
     }
 
     fn read_geometry_block<Rd: Read + Seek>(
         reader: &mut Rd,
         section_length: u64,
     ) -> Result<GeomInfo> {
-        GeomInfo::decode_block(reader, section_length)
+        GeomInfo::decode_block_streaming(reader, section_length)
     }
 
     fn read_hierarchy_block<Rd: Read + Seek>(
@@ -356,6 +1062,61 @@ impl<R: ReadSeek> FstReader<R> {
         HierarchyBlock::decode_block(reader, block_type, section_length)
     }
 }
+
+/// Buffers the source and, when it starts with an `FST_BL_ZWRAPPER` block, inflates the wrapped
+/// file so parsing can continue from the decompressed stream.
+fn open_backend<R: ReadSeek>(source: R) -> Result<ReaderBackend<R>> {
+    let mut buffered = BufReader::new(source);
+    let mut tag = [0u8; 1];
+    buffered.read_exact(&mut tag)?;
+    if tag[0] != BlockType::ZWrapper as u8 {
+        buffered.seek_relative(-1)?;
+        return Ok(ReaderBackend::from_buffered(buffered));
+    }
+    let data = inflate_zwrapper(&mut buffered)?;
+    Ok(ReaderBackend::inflated(buffered.into_inner(), data))
+}
+
+/// Reads the z-wrapper payload that follows the block tag: section length, uncompressed length,
+/// compressed length, and the gzip stream itself.
+fn inflate_zwrapper<Rd: Read>(reader: &mut Rd) -> Result<Vec<u8>> {
+    let section_length = read_u64_be(reader)?;
+    let uncompressed_len = read_u64_be(reader)?;
+    let compressed_len = read_u64_be(reader)?;
+    let expected_section = compressed_len
+        .checked_add(24)
+        .ok_or_else(|| Error::invalid("z-wrapper section length overflow"))?;
+    if section_length != expected_section {
+        return Err(Error::invalid(format!(
+            "z-wrapper section length {section_length} does not match payload length {compressed_len}"
+        )));
+    }
+    let compressed_len = usize::try_from(compressed_len)
+        .map_err(|_| Error::invalid("z-wrapper payload exceeds addressable memory"))?;
+    let expected = usize::try_from(uncompressed_len)
+        .map_err(|_| Error::invalid("z-wrapper uncompressed payload exceeds addressable memory"))?;
+    let mut compressed = vec![0u8; compressed_len];
+    reader.read_exact(&mut compressed)?;
+
+    #[cfg(feature = "gzip")]
+    {
+        let mut decoder = GzDecoder::new(compressed.as_slice());
+        let mut inflated = Vec::with_capacity(expected);
+        decoder.read_to_end(&mut inflated)?;
+        if inflated.len() != expected {
+            return Err(Error::decode("z-wrapper decompression length mismatch"));
+        }
+        Ok(inflated)
+    }
+    #[cfg(not(feature = "gzip"))]
+    {
+        let _ = (compressed, expected);
+        Err(Error::unsupported(
+            "z-wrapper decompression requires the `gzip` feature",
+        ))
+    }
+}
+
 fn payload_length(section_length: u64) -> Result<u64> {
     section_length
         .checked_sub(8)
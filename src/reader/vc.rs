@@ -1,21 +1,19 @@
 use std::borrow::Cow;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom};
 use std::ops::Range;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-#[cfg(feature = "fastlz")]
-use fastlz_sys::fastlz_decompress;
-#[cfg(feature = "gzip")]
-use flate2::read::ZlibDecoder;
-#[cfg(feature = "lz4")]
-use lz4_flex::block::decompress as lz4_decompress;
-
-use crate::block::{FrameSection, PackMarker, TimeSection, TimeTable, VcBlock};
-use crate::encoding::{decode_svarint, decode_varint_with_len};
+use super::ReaderOptions;
+use crate::block::{
+    ChainIndexEntry, FrameSection, PackMarker, TimeSection, TimeTable, VcBlock,
+    decode_chain_index_entries_at,
+};
+use crate::compression::{CustomDecompressor, Decompressor, decompressor_for};
+use crate::encoding::decode_varint_with_len;
 use crate::error::{Error, Result};
-use crate::types::{BlockType, PackType};
+use crate::types::{BlockType, Endianness, PackType};
 use crate::util::{read_u64_be, read_varint_from_reader};
 
 /// Fully decoded metadata and payload slices extracted from a value-change block.
@@ -28,6 +26,76 @@ pub struct VcBlockMeta {
     pub time_section: TimeSection,
     pub time_table: TimeTable,
     pub index: ChainIndex,
+    /// Byte order of real values in the frame and chains, taken from the file header.
+    pub endianness: Endianness,
+}
+
+impl VcBlockMeta {
+    /// Returns the uncompressed chain of `handle`, following aliases to the chain that owns the
+    /// data. Returns `None` when the handle has no chain in this block or was not decoded.
+    pub fn chain_bytes(&self, handle: u32) -> Option<Cow<'_, [u8]>> {
+        let canonical = self.index.resolve_alias(handle);
+        let chain = self
+            .chains
+            .get((canonical as usize).checked_sub(1)?)?
+            .as_ref()?;
+        Some(chain.payload.as_slice(&self.chain_buffer))
+    }
+
+    /// Returns `(handle, stored_len)` for every decoded non-alias chain in this block, largest
+    /// first. Handles of equal size are ordered ascending.
+    pub fn handle_sizes(&self) -> Vec<(u32, u32)> {
+        let mut sizes: Vec<(u32, u32)> = self
+            .chains
+            .iter()
+            .flatten()
+            .filter(|chain| chain.alias_of.is_none())
+            .map(|chain| (chain.handle + 1, chain.stored_len))
+            .collect();
+        sizes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        sizes
+    }
+}
+
+/// Location and time range of a value-change block, as recorded by
+/// [`FstReader::build_block_index`](crate::reader::FstReader::build_block_index).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VcBlockIndexEntry {
+    /// Stream offset of the block tag.
+    pub offset: u64,
+    /// Value-change block flavour found at `offset`.
+    pub block_type: BlockType,
+    /// First timestamp covered by the block (before `time_zero` is applied).
+    pub begin_time: u64,
+    /// Last timestamp covered by the block (before `time_zero` is applied).
+    pub end_time: u64,
+    /// Highest handle referenced by the block's chains.
+    pub vc_max_handle: u64,
+}
+
+/// Outcome of [`FstReader::verify`](crate::reader::FstReader::verify).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Number of value-change blocks that were fully decoded.
+    pub blocks_checked: usize,
+    /// Blocks whose frame, chains, or time table failed to decode to their declared lengths.
+    pub failures: Vec<VerifyFailure>,
+}
+
+impl VerifyReport {
+    /// Returns `true` when every checked block decoded cleanly.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// A value-change block that failed verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyFailure {
+    /// Stream offset of the block tag, as in [`VcBlockIndexEntry::offset`].
+    pub offset: u64,
+    /// Rendered decode error.
+    pub message: String,
 }
 
 /// Resolved per-handle chain metadata extracted from the block index.
@@ -36,6 +104,26 @@ pub struct ChainIndex {
     pub slots: Vec<Option<ChainSlot>>,
 }
 
+impl ChainIndex {
+    /// Follows alias slots from `handle` to the handle that owns the chain data. Handles outside
+    /// the index are returned unchanged.
+    pub fn resolve_alias(&self, handle: u32) -> u32 {
+        let mut current = handle;
+        for _ in 0..=self.slots.len() {
+            let alias_of = current
+                .checked_sub(1)
+                .and_then(|idx| self.slots.get(idx as usize))
+                .and_then(|slot| slot.as_ref())
+                .and_then(|slot| slot.alias_of);
+            match alias_of {
+                Some(target) if target != current => current = target,
+                _ => break,
+            }
+        }
+        current
+    }
+}
+
 /// Offset/length pair describing where compressed chain data resides for a handle.
 #[derive(Debug, Clone, Copy)]
 pub struct ChainSlot {
@@ -82,10 +170,124 @@ pub fn parse_vc_block<R: Read + Seek>(
     block_type: BlockType,
     section_start: u64,
     payload_len: u64,
+    options: &ReaderOptions,
+    endianness: Endianness,
+) -> Result<VcBlockMeta> {
+    parse_vc_block_filtered(
+        reader,
+        block_type,
+        section_start,
+        payload_len,
+        options,
+        endianness,
+        None,
+    )
+}
+
+/// Rejects blocks whose declared decode memory exceeds [`ReaderOptions::max_block_memory`].
+fn check_block_memory(options: &ReaderOptions, required_memory: u64) -> Result<()> {
+    if let Some(limit) = options.max_block_memory
+        && required_memory > limit
+    {
+        return Err(Error::invalid(format!(
+            "value-change block requires {required_memory} bytes, above the {limit}-byte limit"
+        )));
+    }
+    Ok(())
+}
+
+/// An in-memory copy of a block payload that reports and accepts the stream offsets of the
+/// source it was read from, so parsing (and error offsets) behave as if reading the source.
+struct PrefetchedBlock {
+    data: Cursor<Vec<u8>>,
+    base: u64,
+}
+
+impl Read for PrefetchedBlock {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+impl Seek for PrefetchedBlock {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => {
+                SeekFrom::Start(offset.checked_sub(self.base).ok_or_else(|| {
+                    std::io::Error::new(ErrorKind::InvalidInput, "seek before prefetched block")
+                })?)
+            }
+            relative => relative,
+        };
+        Ok(self.data.seek(pos)? + self.base)
+    }
+}
+
+/// Like [`parse_vc_block`], but when `only_handle` is set only that handle's chain (after
+/// following aliases in the block index) is decompressed; every other chain is left as `None`.
+pub fn parse_vc_block_filtered<R: Read + Seek>(
+    reader: &mut R,
+    block_type: BlockType,
+    section_start: u64,
+    payload_len: u64,
+    options: &ReaderOptions,
+    endianness: Endianness,
+    only_handle: Option<u32>,
+) -> Result<VcBlockMeta> {
+    let prefetch = options
+        .prefetch_limit
+        .is_some_and(|limit| payload_len <= limit);
+    if !prefetch {
+        return parse_vc_block_body(
+            reader,
+            block_type,
+            section_start,
+            payload_len,
+            options,
+            endianness,
+            only_handle,
+        );
+    }
+
+    let len = usize::try_from(payload_len)
+        .map_err(|_| Error::invalid("value-change payload exceeds addressable memory"))?;
+    let mut payload = vec![0u8; len];
+    let prefix = len.min(24);
+    reader.read_exact(&mut payload[..prefix])?;
+    if prefix == 24 {
+        let required_memory = u64::from_be_bytes(payload[16..24].try_into().expect("8 bytes"));
+        check_block_memory(options, required_memory)?;
+    }
+    reader.read_exact(&mut payload[prefix..])?;
+    let mut window = PrefetchedBlock {
+        data: Cursor::new(payload),
+        base: section_start,
+    };
+    parse_vc_block_body(
+        &mut window,
+        block_type,
+        section_start,
+        payload_len,
+        options,
+        endianness,
+        only_handle,
+    )
+}
+
+/// Parses a value-change block directly from `reader`, seeking between its sections.
+fn parse_vc_block_body<R: Read + Seek>(
+    reader: &mut R,
+    block_type: BlockType,
+    section_start: u64,
+    payload_len: u64,
+    options: &ReaderOptions,
+    endianness: Endianness,
+    only_handle: Option<u32>,
 ) -> Result<VcBlockMeta> {
     let begin_time = read_u64_be(reader)?;
     let end_time = read_u64_be(reader)?;
     let required_memory = read_u64_be(reader)?;
+    check_block_memory(options, required_memory)?;
     let (frame_uncompressed_len, _) = read_varint_from_reader(reader)?;
     let (frame_compressed_len, _) = read_varint_from_reader(reader)?;
     let (frame_max_handle, _) = read_varint_from_reader(reader)?;
@@ -103,51 +305,39 @@ pub fn parse_vc_block<R: Read + Seek>(
 
     let (vc_max_handle, _) = read_varint_from_reader(reader)?;
 
+    let pack_offset = reader.stream_position()?;
     let mut pack = [0u8; 1];
     reader.read_exact(&mut pack)?;
-    let pack_marker = PackMarker::new(pack[0])
-        .ok_or_else(|| Error::decode(format!("unknown pack marker {:02x}", pack[0])))?;
+    let custom = options
+        .custom_chain_decompressors
+        .iter()
+        .find(|codec| codec.marker() == pack[0]);
+    let pack_marker = match (PackMarker::new(pack[0]), custom) {
+        (Some(marker), _) => marker,
+        (None, Some(_)) => PackMarker {
+            pack_type: PackType::Custom(pack[0]),
+            marker: pack[0],
+        },
+        (None, None) => {
+            return Err(Error::decode_at(
+                format!("unknown pack marker {:02x}", pack[0]),
+                pack_offset,
+            ));
+        }
+    };
 
     let chain_start = reader.stream_position()?;
-    let block_end = section_start
-        .checked_add(payload_len)
-        .ok_or_else(|| Error::invalid("value-change block exceeds file bounds"))?;
-
-    if payload_len < 32 {
-        return Err(Error::invalid(
-            "value-change payload shorter than required trailer",
+    let (time_section, time_data_start) = read_time_trailer(reader, section_start, payload_len)?;
+    let block_end = section_start + payload_len;
+
+    let (index_start, index_length) = locate_chain_index(reader, time_data_start)?;
+    if options.strict && index_start < chain_start {
+        return Err(Error::decode_at(
+            "chain index overlaps the block header",
+            index_start,
         ));
     }
 
-    let time_trailer_start = block_end
-        .checked_sub(24)
-        .ok_or_else(|| Error::invalid("value-change trailer underflow"))?;
-    reader.seek(SeekFrom::Start(time_trailer_start))?;
-    let time_uncompressed_len = u64::from_be_bytes(crate::util::read_array::<8, _>(reader)?);
-    let time_compressed_len = u64::from_be_bytes(crate::util::read_array::<8, _>(reader)?);
-    let time_item_count = u64::from_be_bytes(crate::util::read_array::<8, _>(reader)?);
-
-    let time_section = TimeSection {
-        uncompressed_len: time_uncompressed_len,
-        compressed_len: time_compressed_len,
-        item_count: time_item_count,
-    };
-
-    let time_data_len = time_section.compressed_len;
-    let time_data_start = time_trailer_start
-        .checked_sub(time_data_len)
-        .ok_or_else(|| Error::invalid("invalid time section lengths"))?;
-
-    let index_length_pos = time_data_start
-        .checked_sub(8)
-        .ok_or_else(|| Error::invalid("missing index length trailer"))?;
-    reader.seek(SeekFrom::Start(index_length_pos))?;
-    let index_length = u64::from_be_bytes(crate::util::read_array::<8, _>(reader)?);
-
-    let index_start = index_length_pos
-        .checked_sub(index_length)
-        .ok_or_else(|| Error::invalid("index length exceeds block bounds"))?;
-
     let chain_end = index_start;
 
     let header = VcBlock {
@@ -168,18 +358,11 @@ pub fn parse_vc_block<R: Read + Seek>(
         index_start,
         index_length,
         vc_max_handle as usize,
-        chain_start,
-        chain_end,
+        chain_start..chain_end,
+        options.strict,
     )?;
 
-    reader.seek(SeekFrom::Start(time_data_start))?;
-    let time_data_len_usize = usize::try_from(time_data_len)
-        .map_err(|_| Error::invalid("time section exceeds addressable memory"))?;
-    let mut time_bytes = vec![0u8; time_data_len_usize];
-    if time_data_len_usize > 0 {
-        reader.read_exact(&mut time_bytes)?;
-    }
-    let time_table = TimeTable::decode(&time_section, time_bytes)?;
+    let time_table = read_time_table(reader, &time_section, time_data_start)?;
 
     let chain_span = chain_end
         .checked_sub(chain_start)
@@ -194,11 +377,15 @@ pub fn parse_vc_block<R: Read + Seek>(
 
     reader.seek(SeekFrom::Start(block_end))?;
 
+    let wanted =
+        only_handle.and_then(|handle| (index.resolve_alias(handle) as usize).checked_sub(1));
     let chains = build_chains(
         &chain_buffer,
         chain_start,
         &index,
         header.pack_marker.pack_type,
+        custom,
+        wanted,
     )?;
 
     Ok(VcBlockMeta {
@@ -209,14 +396,118 @@ pub fn parse_vc_block<R: Read + Seek>(
         time_section,
         time_table,
         index,
+        endianness,
     })
 }
 
+/// Decodes only the time table of a value-change block whose payload spans
+/// `section_start..section_start + payload_len`. The frame, index, and chains are never read; the
+/// stream is left at the end of the block.
+pub fn parse_time_table<R: Read + Seek>(
+    reader: &mut R,
+    section_start: u64,
+    payload_len: u64,
+) -> Result<TimeTable> {
+    let (time_section, time_data_start) = read_time_trailer(reader, section_start, payload_len)?;
+    let time_table = read_time_table(reader, &time_section, time_data_start)?;
+    reader.seek(SeekFrom::Start(section_start + payload_len))?;
+    Ok(time_table)
+}
+
+/// Reads the raw chain index entries of a value-change block, skipping its frame, chains, and
+/// time table. The stream is left at the end of the block.
+pub(crate) fn read_chain_index_entries<R: Read + Seek>(
+    reader: &mut R,
+    block_type: BlockType,
+    section_start: u64,
+    payload_len: u64,
+) -> Result<Vec<ChainIndexEntry>> {
+    let (_, time_data_start) = read_time_trailer(reader, section_start, payload_len)?;
+    let (index_start, index_length) = locate_chain_index(reader, time_data_start)?;
+    reader.seek(SeekFrom::Start(index_start))?;
+    let index_len_usize = usize::try_from(index_length)
+        .map_err(|_| Error::invalid("index length exceeds addressable memory"))?;
+    let mut bytes = vec![0u8; index_len_usize];
+    reader.read_exact(&mut bytes)?;
+    let entries = decode_chain_index_entries_at(block_type, &bytes, index_start)?;
+    reader.seek(SeekFrom::Start(section_start + payload_len))?;
+    Ok(entries)
+}
+
+/// Reads the index length word stored just before the time data and returns the start offset and
+/// length of the chain index.
+fn locate_chain_index<R: Read + Seek>(reader: &mut R, time_data_start: u64) -> Result<(u64, u64)> {
+    let index_length_pos = time_data_start
+        .checked_sub(8)
+        .ok_or_else(|| Error::invalid("missing index length trailer"))?;
+    reader.seek(SeekFrom::Start(index_length_pos))?;
+    let index_length = u64::from_be_bytes(crate::util::read_array::<8, _>(reader)?);
+
+    let index_start = index_length_pos
+        .checked_sub(index_length)
+        .ok_or_else(|| Error::invalid("index length exceeds block bounds"))?;
+    Ok((index_start, index_length))
+}
+
+/// Reads the fixed 24-byte time trailer at the end of the block and returns the time section
+/// together with the offset of its (possibly compressed) payload.
+fn read_time_trailer<R: Read + Seek>(
+    reader: &mut R,
+    section_start: u64,
+    payload_len: u64,
+) -> Result<(TimeSection, u64)> {
+    let block_end = section_start
+        .checked_add(payload_len)
+        .ok_or_else(|| Error::invalid("value-change block exceeds file bounds"))?;
+
+    if payload_len < 32 {
+        return Err(Error::invalid(
+            "value-change payload shorter than required trailer",
+        ));
+    }
+
+    let time_trailer_start = block_end
+        .checked_sub(24)
+        .ok_or_else(|| Error::invalid("value-change trailer underflow"))?;
+    reader.seek(SeekFrom::Start(time_trailer_start))?;
+    let time_uncompressed_len = u64::from_be_bytes(crate::util::read_array::<8, _>(reader)?);
+    let time_compressed_len = u64::from_be_bytes(crate::util::read_array::<8, _>(reader)?);
+    let time_item_count = u64::from_be_bytes(crate::util::read_array::<8, _>(reader)?);
+
+    let time_section = TimeSection {
+        uncompressed_len: time_uncompressed_len,
+        compressed_len: time_compressed_len,
+        item_count: time_item_count,
+    };
+
+    let time_data_start = time_trailer_start
+        .checked_sub(time_section.compressed_len)
+        .ok_or_else(|| Error::invalid("invalid time section lengths"))?;
+    Ok((time_section, time_data_start))
+}
+
+fn read_time_table<R: Read + Seek>(
+    reader: &mut R,
+    time_section: &TimeSection,
+    time_data_start: u64,
+) -> Result<TimeTable> {
+    reader.seek(SeekFrom::Start(time_data_start))?;
+    let time_data_len_usize = usize::try_from(time_section.compressed_len)
+        .map_err(|_| Error::invalid("time section exceeds addressable memory"))?;
+    let mut time_bytes = vec![0u8; time_data_len_usize];
+    if time_data_len_usize > 0 {
+        reader.read_exact(&mut time_bytes)?;
+    }
+    TimeTable::decode(time_section, time_bytes)
+}
+
 fn build_chains(
     buffer: &[u8],
     chain_start: u64,
     index: &ChainIndex,
     pack_type: PackType,
+    custom: Option<&CustomDecompressor>,
+    wanted: Option<usize>,
 ) -> Result<Vec<Option<ChainData>>> {
     struct ChainJob<'a> {
         handle_index: usize,
@@ -239,6 +530,9 @@ fn build_chains(
         let Some(slot) = slot_opt else {
             continue;
         };
+        if wanted.is_some_and(|wanted| wanted != handle_index) {
+            continue;
+        }
 
         let rel_offset = (slot.offset - chain_start) as usize;
         let length = slot.length as usize;
@@ -279,7 +573,11 @@ fn build_chains(
     let decompress = |job: ChainJob<'_>| -> Result<ChainJobResult> {
         let expected = usize::try_from(job.stored_len)
             .map_err(|_| Error::decode("chain stored length exceeds addressable memory"))?;
-        let data = decompress_chain_payload(pack_type, job.compressed, expected)?;
+        let mut decompressor = match (pack_type, custom) {
+            (PackType::Custom(_), Some(codec)) => codec.create(),
+            _ => decompressor_for(pack_type)?,
+        };
+        let data = decompress_chain_payload(decompressor.as_mut(), job.compressed, expected)?;
         let stored_len = u32::try_from(job.stored_len)
             .map_err(|_| Error::decode("chain stored length exceeds u32 range"))?;
         Ok(ChainJobResult {
@@ -317,100 +615,56 @@ fn build_chains(
 }
 
 fn decompress_chain_payload(
-    pack_type: PackType,
+    decompressor: &mut dyn Decompressor,
     input: &[u8],
     expected_len: usize,
 ) -> Result<Vec<u8>> {
-    match pack_type {
-        PackType::None => {
-            if input.len() != expected_len {
-                return Err(Error::decode("chain length mismatch"));
-            }
-            Ok(input.to_vec())
-        }
-        PackType::Zlib => {
-            #[cfg(feature = "gzip")]
-            {
-                let mut decoder = ZlibDecoder::new(input);
-                let mut out = Vec::with_capacity(expected_len);
-                decoder.read_to_end(&mut out)?;
-                if out.len() != expected_len {
-                    return Err(Error::decode("chain zlib length mismatch"));
-                }
-                Ok(out)
-            }
-            #[cfg(not(feature = "gzip"))]
-            {
-                let _ = (input, expected_len);
-                Err(Error::unsupported(
-                    "chain zlib decompression requires the `gzip` feature",
-                ))
-            }
-        }
-        PackType::Lz4 => {
-            #[cfg(feature = "lz4")]
-            {
-                let out = lz4_decompress(input, expected_len)
-                    .map_err(|e| Error::decode(e.to_string()))?;
-                if out.len() != expected_len {
-                    return Err(Error::decode("chain lz4 length mismatch"));
-                }
-                Ok(out)
-            }
-            #[cfg(not(feature = "lz4"))]
-            {
-                let _ = (input, expected_len);
-                Err(Error::unsupported(
-                    "chain lz4 decompression requires the `lz4` feature",
-                ))
-            }
-        }
-        PackType::FastLz => {
-            #[cfg(feature = "fastlz")]
-            {
-                let input_len = i32::try_from(input.len())
-                    .map_err(|_| Error::decode("fastlz chain length exceeds i32 range"))?;
-                let expected_len_i32 = i32::try_from(expected_len)
-                    .map_err(|_| Error::decode("fastlz output length exceeds i32 range"))?;
-                let mut out = vec![0u8; expected_len];
-                let written = unsafe {
-                    fastlz_decompress(
-                        input.as_ptr() as *const _,
-                        input_len,
-                        out.as_mut_ptr() as *mut _,
-                        expected_len_i32,
-                    )
-                };
-                if written <= 0 {
-                    return Err(Error::decode("fastlz decompression failed"));
-                }
-                let written_usize = usize::try_from(written).map_err(|_| {
-                    Error::decode("fastlz decoded length exceeds addressable memory")
-                })?;
-                if written_usize != expected_len {
-                    return Err(Error::decode("chain fastlz length mismatch"));
-                }
-                Ok(out)
-            }
-            #[cfg(not(feature = "fastlz"))]
-            {
-                let _ = (input, expected_len);
-                Err(Error::unsupported(
-                    "chain fastlz decompression requires the `fastlz` feature",
-                ))
-            }
+    let mut out = Vec::with_capacity(expected_len);
+    decompressor.decompress_exact(input, expected_len, &mut out)?;
+    if out.len() != expected_len {
+        return Err(Error::decode("chain length mismatch after decompression"));
+    }
+    Ok(out)
+}
+
+/// Checks that the chains named by `offsets` (relative to the chain section) tile all
+/// `total_chain_len` bytes: the first starts at offset 0, each is non-empty, and the last ends
+/// at the chain index.
+fn check_chain_partition(offsets: &[Option<u64>], total_chain_len: u64) -> Result<()> {
+    let mut previous: Option<u64> = None;
+    for (idx, offset) in offsets.iter().enumerate() {
+        let Some(offset) = *offset else {
+            continue;
+        };
+        let follows = match previous {
+            None => offset == 0,
+            Some(previous) => offset > previous,
+        };
+        if !follows || offset >= total_chain_len {
+            return Err(Error::decode(format!(
+                "chain for handle {} at offset {offset} does not follow the previous chain",
+                idx + 1
+            )));
         }
+        previous = Some(offset);
+    }
+    if previous.is_none() && total_chain_len != 0 {
+        return Err(Error::decode(format!(
+            "{total_chain_len} chain bytes are not referenced by the index"
+        )));
     }
+    Ok(())
 }
 
+/// Decodes the per-handle chain index and resolves each slot to its chain range.
 fn decode_chain_index<R: Read + Seek>(
     reader: &mut R,
     block_type: BlockType,
     index_start: u64,
     index_length: u64,
     max_handle_hint: usize,
-    chain_start: u64,
-    chain_end: u64,
+    chains: Range<u64>,
+    strict: bool,
 ) -> Result<ChainIndex> {
     reader.seek(SeekFrom::Start(index_start))?;
     let index_len_usize = usize::try_from(index_length)
@@ -418,97 +672,15 @@ fn decode_chain_index<R: Read + Seek>(
     let mut bytes = vec![0u8; index_len_usize];
     reader.read_exact(&mut bytes)?;
 
-    #[derive(Debug, Clone)]
-    enum EntryTmp {
-        Empty,
-        Data { offset: u64 },
-        Alias { target: usize },
-    }
-
-    let mut entries: Vec<EntryTmp> = Vec::with_capacity(max_handle_hint + 1);
-    let mut has_payload: Vec<bool> = Vec::with_capacity(max_handle_hint + 1);
-    let mut slice = bytes.as_slice();
-    let mut last_offset = 0u64;
-    let mut last_alias_target: Option<usize> = None;
-
-    while !slice.is_empty() {
-        if block_type == BlockType::VcDataDynAlias2 && (slice[0] & 0x01) != 0 {
-            let mut tmp = slice;
-            let raw = decode_svarint(&mut tmp)?;
-            let shval = raw >> 1;
-            slice = tmp;
-
-            if shval > 0 {
-                last_offset = last_offset
-                    .checked_add(shval as u64)
-                    .ok_or_else(|| Error::decode("chain index overflow"))?;
-                entries.push(EntryTmp::Data {
-                    offset: last_offset,
-                });
-                has_payload.push(true);
-                last_alias_target = None;
-            } else if shval < 0 {
-                let target = ((-shval) as u64)
-                    .checked_sub(1)
-                    .ok_or_else(|| Error::decode("invalid alias target"))?
-                    as usize;
-                entries.push(EntryTmp::Alias { target });
-                has_payload.push(false);
-                last_alias_target = Some(target);
-            } else if let Some(target) = last_alias_target {
-                entries.push(EntryTmp::Alias { target });
-                has_payload.push(false);
-            } else {
-                entries.push(EntryTmp::Empty);
-                has_payload.push(false);
-            }
-            continue;
-        }
+    let entries = decode_chain_index_entries_at(block_type, &bytes, index_start)?;
+    let has_payload: Vec<bool> = entries
+        .iter()
+        .map(|entry| matches!(entry, ChainIndexEntry::Data { .. }))
+        .collect();
 
-        let (value, consumed) = decode_varint_with_len(slice)?;
-        slice = &slice[consumed..];
-
-        if value == 0 {
-            let (alias, alias_consumed) = decode_varint_with_len(slice)?;
-            slice = &slice[alias_consumed..];
-            if alias == 0 {
-                entries.push(EntryTmp::Empty);
-                has_payload.push(false);
-                last_alias_target = None;
-            } else {
-                let target = alias
-                    .checked_sub(1)
-                    .ok_or_else(|| Error::decode("invalid alias handle"))?
-                    as usize;
-                entries.push(EntryTmp::Alias { target });
-                has_payload.push(false);
-                last_alias_target = Some(target);
-            }
-            continue;
-        }
-
-        if (value & 1) == 0 {
-            let repeat = (value >> 1) as usize;
-            for _ in 0..repeat {
-                entries.push(EntryTmp::Empty);
-                has_payload.push(false);
-            }
-            continue;
-        }
-
-        let delta = value >> 1;
-        last_offset = last_offset
-            .checked_add(delta)
-            .ok_or_else(|| Error::decode("chain index overflow"))?;
-        entries.push(EntryTmp::Data {
-            offset: last_offset,
-        });
-        has_payload.push(true);
-        last_alias_target = None;
-    }
-
-    let total_chain_len = chain_end
-        .checked_sub(chain_start)
+    let total_chain_len = chains
+        .end
+        .checked_sub(chains.start)
         .ok_or_else(|| Error::invalid("negative chain range"))?;
 
     let mut offsets = Vec::<Option<u64>>::with_capacity(entries.len());
@@ -517,30 +689,36 @@ fn decode_chain_index<R: Read + Seek>(
 
     for entry in &entries {
         match entry {
-            EntryTmp::Empty => {
+            ChainIndexEntry::Empty => {
                 offsets.push(None);
                 lengths.push(None);
                 alias_targets.push(None);
             }
-            EntryTmp::Data { offset } => {
+            ChainIndexEntry::Data { offset } => {
                 offsets.push(Some(*offset));
                 lengths.push(None);
                 alias_targets.push(None);
             }
-            EntryTmp::Alias { target } => {
+            ChainIndexEntry::Alias { target } => {
                 offsets.push(None);
                 lengths.push(None);
-                alias_targets.push(Some(*target));
+                alias_targets.push(Some(*target as usize - 1));
             }
         }
     }
 
-    const PACK_MARKER_PREFIX: u64 = 1;
-    for off in offsets.iter_mut().flatten() {
-        if *off < PACK_MARKER_PREFIX {
-            return Err(Error::decode("chain offset precedes pack marker"));
+    if strict {
+        if entries.len() != max_handle_hint {
+            return Err(Error::decode_at(
+                format!(
+                    "chain index holds {} entries but the block declares {max_handle_hint} handles",
+                    entries.len()
+                ),
+                index_start,
+            ));
         }
-        *off -= PACK_MARKER_PREFIX;
+        check_chain_partition(&offsets, total_chain_len)
+            .map_err(|err| err.at_offset(index_start))?;
     }
 
     let mut prev_data_idx: Option<usize> = None;
@@ -600,26 +778,31 @@ fn decode_chain_index<R: Read + Seek>(
         }
     }
 
+    /// Follows alias links from `idx` to the slot holding the payload. Aliases of empty slots
+    /// legitimately resolve to `None` (the canonical signal did not change in this block), but a
+    /// chain of aliases that loops back on itself is corrupt and reported as an error.
     fn resolve_canonical(
         idx: usize,
         alias_targets: &[Option<usize>],
         has_payload: &[bool],
         memo: &mut [Option<Option<usize>>],
         visiting: &mut [bool],
-    ) -> Option<usize> {
+    ) -> Result<Option<usize>> {
         if let Some(cached) = memo[idx] {
-            return cached;
+            return Ok(cached);
         }
         if visiting[idx] {
-            memo[idx] = Some(None);
-            return None;
+            return Err(Error::decode(format!(
+                "alias cycle through handle {}",
+                idx + 1
+            )));
         }
         visiting[idx] = true;
         let result = if has_payload[idx] {
             Some(idx)
         } else if let Some(target) = alias_targets[idx] {
             if target < alias_targets.len() {
-                resolve_canonical(target, alias_targets, has_payload, memo, visiting)
+                resolve_canonical(target, alias_targets, has_payload, memo, visiting)?
             } else {
                 None
             }
@@ -628,7 +811,7 @@ fn decode_chain_index<R: Read + Seek>(
         };
         visiting[idx] = false;
         memo[idx] = Some(result);
-        result
+        Ok(result)
     }
 
     let mut canonical_memo = vec![None; alias_targets.len()];
@@ -641,7 +824,7 @@ fn decode_chain_index<R: Read + Seek>(
             &has_payload,
             &mut canonical_memo,
             &mut canonical_visiting,
-        );
+        )?;
         canonical.push(resolved);
     }
 
@@ -649,7 +832,8 @@ fn decode_chain_index<R: Read + Seek>(
     for idx in 0..offsets.len() {
         match (offsets[idx], lengths[idx]) {
             (Some(off), Some(len)) => {
-                let absolute = chain_start
+                let absolute = chains
+                    .start
                     .checked_add(off)
                     .ok_or_else(|| Error::invalid("chain offset overflow"))?;
                 let alias_handle = if has_payload[idx] {
@@ -1,15 +1,15 @@
 //! Helper utilities for exporting FST metadata and value changes through `serde`.
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::block::{AttributeEntry, HierarchyBlock, ScopeEntry, VarEntry};
+use crate::block::{AttributeEntry, HierarchyBlock, HierarchyItem, ScopeEntry, VarEntry};
 use crate::error::Result;
 use crate::reader::{ValueChange, VcBlockChanges};
 use crate::types::SignalValue;
 use crate::types::{ScopeType, VarDir, VarType};
 
 /// Hierarchy snapshot containing a tree of scopes and any variables/attributes attached to the root.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HierarchySnapshot {
     /// Roots of the scope tree reconstructed from the hierarchy payload.
     pub scopes: Vec<ScopeNode>,
@@ -22,7 +22,7 @@ pub struct HierarchySnapshot {
 }
 
 /// Recursive scope representation matching the structure described in `doc/wavefst.md Section 6`.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScopeNode {
     /// Scope classification (module, task, package, etc.).
     pub scope_type: ScopeType,
@@ -43,7 +43,7 @@ pub struct ScopeNode {
 }
 
 /// Serializable view of a hierarchy attribute.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttributeNode {
     /// Attribute type tag (matches the wire-format enumeration).
     pub attr_type: u8,
@@ -56,7 +56,7 @@ pub struct AttributeNode {
 }
 
 /// Serializable representation of a variable entry.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VariableNode {
     /// Variable kind (wire, reg, real, string, etc.).
     pub var_type: VarType,
@@ -77,7 +77,7 @@ pub struct VariableNode {
 }
 
 /// Fully owned representation of a value change event suitable for JSON export.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OwnedValueChange {
     /// Absolute simulation timestamp (already adjusted for `time_zero`).
     pub timestamp: u64,
@@ -91,7 +91,7 @@ pub struct OwnedValueChange {
 }
 
 /// Fully owned variant of [`SignalValue`] tailored for serialization.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "kind", content = "data")]
 pub enum OwnedSignalValue {
     /// Single-bit value (`'0'`, `'1'`, `'x'`, `'z'`, etc.).
@@ -142,7 +142,7 @@ impl OwnedValueChange {
 pub fn collect_value_changes<'a>(
     changes: &mut VcBlockChanges<'a>,
 ) -> Result<Vec<OwnedValueChange>> {
-    let mut out = Vec::new();
+    let mut out = Vec::with_capacity(changes.size_hint().0);
     for event in changes.by_ref() {
         let event = event?;
         out.push(OwnedValueChange::from_change(&event));
@@ -199,6 +199,78 @@ pub fn snapshot_hierarchy(hierarchy: &HierarchyBlock) -> HierarchySnapshot {
     }
 }
 
+/// Rebuilds a [`HierarchyBlock`] from a snapshot, restoring `parent`/`scope` links and keeping
+/// the handles recorded in the snapshot.
+///
+/// The snapshot does not keep the original token interleaving, so items are emitted in a
+/// canonical order: root attributes and variables first, then each scope as its attributes,
+/// variables, and child scopes.
+pub fn rebuild_hierarchy(snapshot: &HierarchySnapshot) -> HierarchyBlock {
+    let mut block = HierarchyBlock::default();
+    push_contents(
+        &mut block,
+        None,
+        &snapshot.root_attributes,
+        &snapshot.root_variables,
+    );
+    for scope in &snapshot.scopes {
+        push_scope(&mut block, None, scope);
+    }
+    block
+}
+
+fn push_scope(block: &mut HierarchyBlock, parent: Option<usize>, node: &ScopeNode) {
+    let scope_index = block.scopes.len();
+    block.scopes.push(ScopeEntry {
+        scope_type: node.scope_type,
+        name: node.name.clone(),
+        component: node.component.clone(),
+        parent,
+    });
+    block.items.push(HierarchyItem::ScopeBegin { scope_index });
+    push_contents(block, Some(scope_index), &node.attributes, &node.variables);
+    for child in &node.children {
+        push_scope(block, Some(scope_index), child);
+    }
+    block.items.push(HierarchyItem::ScopeEnd);
+}
+
+fn push_contents(
+    block: &mut HierarchyBlock,
+    scope: Option<usize>,
+    attributes: &[AttributeNode],
+    variables: &[VariableNode],
+) {
+    for attr in attributes {
+        let attribute_index = block.attributes.len();
+        block.attributes.push(AttributeEntry {
+            attr_type: attr.attr_type,
+            subtype: attr.subtype,
+            name: attr.name.clone(),
+            argument: attr.argument,
+            scope,
+        });
+        block
+            .items
+            .push(HierarchyItem::AttributeBegin { attribute_index });
+        block.items.push(HierarchyItem::AttributeEnd);
+    }
+    for var in variables {
+        let var_index = block.variables.len();
+        block.variables.push(VarEntry {
+            var_type: var.var_type,
+            direction: var.direction,
+            name: var.name.clone(),
+            length: var.length,
+            handle: var.handle,
+            alias_of: var.alias_of,
+            scope,
+            is_alias: var.is_alias,
+        });
+        block.items.push(HierarchyItem::Var { var_index });
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ScopeBuilder {
     scope_type: ScopeType,
@@ -14,6 +14,14 @@ pub(crate) fn pack_ascii_bits(data: &[u8], width: u32, packed_len: usize) -> Opt
             }
         }
     }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            unsafe {
+                return pack_ascii_bits_aarch64_neon(data, packed_len);
+            }
+        }
+    }
     let _ = packed_len;
     None
 }
@@ -56,24 +64,62 @@ unsafe fn pack_ascii_bits_x86_sse2(data: &[u8], packed_len: usize) -> Option<Vec
         idx += 16;
     }
 
-    while idx + 8 <= data.len() {
-        let mut byte = 0u8;
-        for &c in &data[idx..idx + 8] {
-            if c != b'0' && c != b'1' {
-                return None;
-            }
-            byte = (byte << 1) | ((c == b'1') as u8);
+    pack_ascii_remainder(&data[idx..], &mut out[out_index..])?;
+    Some(out)
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn pack_ascii_bits_aarch64_neon(data: &[u8], packed_len: usize) -> Option<Vec<u8>> {
+    use std::arch::aarch64::*;
+
+    const WEIGHTS: [u8; 16] = [
+        0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01, 0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02,
+        0x01,
+    ];
+
+    let mut out = vec![0u8; packed_len];
+    let mut idx = 0usize;
+    let mut out_index = 0usize;
+
+    if data.is_empty() {
+        return Some(out);
+    }
+
+    let zero = vdupq_n_u8(b'0');
+    let one = vdupq_n_u8(b'1');
+    let weights = unsafe { vld1q_u8(WEIGHTS.as_ptr()) };
+
+    while idx + 16 <= data.len() {
+        let bytes = unsafe { vld1q_u8(data.as_ptr().add(idx)) };
+        let is_zero = vceqq_u8(bytes, zero);
+        let is_one = vceqq_u8(bytes, one);
+        if vminvq_u8(vorrq_u8(is_zero, is_one)) != 0xFF {
+            return None;
         }
-        if out_index < packed_len {
-            out[out_index] = byte;
-            out_index += 1;
+
+        // Each lane keeps its MSB-first weight when set; summing a half yields one packed byte.
+        let bits = vandq_u8(is_one, weights);
+        for byte in [vaddv_u8(vget_low_u8(bits)), vaddv_u8(vget_high_u8(bits))] {
+            if out_index < packed_len {
+                out[out_index] = byte;
+                out_index += 1;
+            }
         }
-        idx += 8;
+        idx += 16;
     }
 
-    if idx < data.len() {
+    pack_ascii_remainder(&data[idx..], &mut out[out_index..])?;
+    Some(out)
+}
+
+/// Packs the bytes left after the vector loop eight at a time, returning `None` on a character
+/// other than `'0'`/`'1'`. Bits beyond `out` are dropped, as in the vector loops.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn pack_ascii_remainder(data: &[u8], out: &mut [u8]) -> Option<()> {
+    for (chunk_index, chunk) in data.chunks(8).enumerate() {
         let mut byte = 0u8;
-        for (offset, &c) in data[idx..].iter().enumerate() {
+        for (offset, &c) in chunk.iter().enumerate() {
             if c != b'0' && c != b'1' {
                 return None;
             }
@@ -81,10 +127,80 @@ unsafe fn pack_ascii_bits_x86_sse2(data: &[u8], packed_len: usize) -> Option<Vec
                 byte |= 1 << (7 - offset);
             }
         }
-        if out_index < packed_len {
-            out[out_index] = byte;
+        if let Some(slot) = out.get_mut(chunk_index) {
+            *slot = byte;
         }
     }
+    Some(())
+}
 
-    Some(out)
+/// Attempts to expand the first `width` bits of `bits` (MSB-first within each byte) into ASCII
+/// `'0'`/`'1'` using architecture specific SIMD instructions. Returns `None` if the architecture is
+/// unsupported, the required hardware feature is unavailable at runtime, or `bits` is too short.
+pub(crate) fn unpack_packed_bits(bits: &[u8], width: u32) -> Option<Vec<u8>> {
+    let width = usize::try_from(width).ok()?;
+    if bits.len() < width.div_ceil(8) {
+        return None;
+    }
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::arch::is_x86_feature_detected!("sse2") {
+            unsafe {
+                return Some(unpack_packed_bits_x86_sse2(bits, width));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn unpack_packed_bits_x86_sse2(bits: &[u8], width: usize) -> Vec<u8> {
+    use std::arch::x86_64::*;
+
+    let byte_count = width.div_ceil(8);
+    let mut out = vec![0u8; byte_count * 8];
+    let masks = _mm_setr_epi8(
+        0x80u8 as i8,
+        0x40,
+        0x20,
+        0x10,
+        0x08,
+        0x04,
+        0x02,
+        0x01,
+        0x80u8 as i8,
+        0x40,
+        0x20,
+        0x10,
+        0x08,
+        0x04,
+        0x02,
+        0x01,
+    );
+    let ones = _mm_set1_epi8(1);
+    let zero_char = _mm_set1_epi8(b'0' as i8);
+
+    let mut idx = 0usize;
+    while idx + 2 <= byte_count {
+        // Broadcast each source byte across one half of the register, then test one bit per lane.
+        let lo = i64::from_ne_bytes([bits[idx]; 8]);
+        let hi = i64::from_ne_bytes([bits[idx + 1]; 8]);
+        let spread = _mm_set_epi64x(hi, lo);
+        let set = _mm_cmpeq_epi8(_mm_and_si128(spread, masks), masks);
+        let ascii = _mm_add_epi8(zero_char, _mm_and_si128(set, ones));
+        let ptr = unsafe { out.as_mut_ptr().add(idx * 8) as *mut __m128i };
+        unsafe { _mm_storeu_si128(ptr, ascii) };
+        idx += 2;
+    }
+
+    if idx < byte_count {
+        let byte = bits[idx];
+        for bit in 0..8 {
+            out[idx * 8 + bit] = b'0' + ((byte >> (7 - bit)) & 1);
+        }
+    }
+
+    out.truncate(width);
+    out
 }
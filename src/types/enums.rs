@@ -93,6 +93,16 @@ pub enum VarType {
     SvShortReal = 29,
 }
 
+/// Source language recorded in the header's file-type byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum FileType {
+    Verilog = 0,
+    Vhdl = 1,
+    VerilogVhdl = 2,
+}
+
 /// Signal direction (input/output) metadata.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -114,6 +124,12 @@ pub enum PackType {
     Zlib,
     FastLz,
     Lz4,
+    /// Zstandard chains. libfst defines no marker for zstd, so `wavefst` uses `'S'`; other
+    /// readers will reject such files as an unknown pack type.
+    Zstd,
+    /// User-registered codec identified by its marker byte. Never produced by
+    /// [`PackType::from_marker`]; readers must register a matching decompressor.
+    Custom(u8),
 }
 
 impl PackType {
@@ -123,6 +139,7 @@ impl PackType {
             b'Z' | b'!' | b'^' => Some(Self::Zlib),
             b'F' => Some(Self::FastLz),
             b'4' => Some(Self::Lz4),
+            b'S' => Some(Self::Zstd),
             0 => Some(Self::None),
             _ => None,
         }
@@ -135,6 +152,66 @@ impl PackType {
             Self::Zlib => b'Z',
             Self::FastLz => b'F',
             Self::Lz4 => b'4',
+            Self::Zstd => b'S',
+            Self::Custom(marker) => marker,
+        }
+    }
+}
+
+/// Byte order of real values stored in frames and chains, as announced by the header's
+/// `endian_test` marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Returns the byte order of the host, which the writer uses for reals.
+    pub const fn native() -> Self {
+        if cfg!(target_endian = "little") {
+            Self::Little
+        } else {
+            Self::Big
+        }
+    }
+
+    /// Decodes an `f64` stored in this byte order.
+    pub fn f64_from_bytes(self, bytes: [u8; 8]) -> f64 {
+        match self {
+            Self::Little => f64::from_le_bytes(bytes),
+            Self::Big => f64::from_be_bytes(bytes),
         }
     }
+
+    /// Encodes an `f64` in this byte order.
+    pub fn f64_to_bytes(self, value: f64) -> [u8; 8] {
+        match self {
+            Self::Little => value.to_le_bytes(),
+            Self::Big => value.to_be_bytes(),
+        }
+    }
+
+    /// Decodes an `f32` stored in this byte order.
+    pub fn f32_from_bytes(self, bytes: [u8; 4]) -> f32 {
+        match self {
+            Self::Little => f32::from_le_bytes(bytes),
+            Self::Big => f32::from_be_bytes(bytes),
+        }
+    }
+
+    /// Encodes an `f32` in this byte order.
+    pub fn f32_to_bytes(self, value: f32) -> [u8; 4] {
+        match self {
+            Self::Little => value.to_le_bytes(),
+            Self::Big => value.to_be_bytes(),
+        }
+    }
+}
+
+impl Default for Endianness {
+    fn default() -> Self {
+        Self::native()
+    }
 }
@@ -1,4 +1,8 @@
 use std::borrow::Cow;
+use std::fmt;
+
+use crate::encoding::bit_position;
+use crate::util::{pack_ascii_bits, unpack_to_ascii};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -24,12 +28,166 @@ pub enum SignalValue<'a> {
     Bytes(Cow<'a, [u8]>),
 }
 
+/// Summary of the logic levels present in a [`SignalValue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LogicState {
+    /// Every bit is `0` or `1` (also reported for real values).
+    TwoState,
+    /// At least one bit is unknown (`x`/`X`).
+    HasX,
+    /// At least one bit is high-impedance (`z`/`Z`) and none is unknown.
+    HasZ,
+    /// Some bit uses another nine-valued state (`h`, `u`, `w`, `l`, `-`, ...) and none is `x`/`z`.
+    HasOther,
+}
+
+/// Renders bits as their character, vectors and packed bits as `b<bits>` (missing packed bits
+/// show as `x`), reals with their shortest round-trip form, and bytes as text when they are valid
+/// UTF-8 or as `0x`-prefixed hex otherwise.
+impl fmt::Display for SignalValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignalValue::Bit(ch) => write!(f, "{ch}"),
+            SignalValue::Vector(text) => write!(f, "b{text}"),
+            SignalValue::PackedBits { width, bits } => {
+                f.write_str("b")?;
+                for idx in 0..*width as usize {
+                    let bit = match bits.get(idx / 8) {
+                        Some(byte) if byte & (0x80 >> (idx % 8)) != 0 => '1',
+                        Some(_) => '0',
+                        None => 'x',
+                    };
+                    write!(f, "{bit}")?;
+                }
+                Ok(())
+            }
+            SignalValue::Real(value) => write!(f, "{value}"),
+            SignalValue::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => f.write_str(text),
+                Err(_) => {
+                    f.write_str("0x")?;
+                    bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+                }
+            },
+        }
+    }
+}
+
+impl SignalValue<'static> {
+    /// Builds a two-state vector of `width` bits from the low bits of `value`.
+    ///
+    /// Bits above bit 63 are zero-filled; bits of `value` beyond `width` are dropped.
+    pub fn from_u64(value: u64, width: u32) -> SignalValue<'static> {
+        let text = (0..width)
+            .rev()
+            .map(|bit| {
+                if bit < 64 && (value >> bit) & 1 == 1 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect::<String>();
+        SignalValue::Vector(Cow::Owned(text))
+    }
+
+    /// Wraps a double-precision value for real-typed variables.
+    pub fn from_f64(value: f64) -> SignalValue<'static> {
+        SignalValue::Real(value)
+    }
+}
+
 impl<'a> SignalValue<'a> {
     /// Returns `true` if the value denotes an unknown (`x`) state.
     pub fn is_unknown(&self) -> bool {
         matches!(self, SignalValue::Bit(ch) if *ch == 'x' || *ch == 'X')
     }
 
+    /// Classifies the logic levels of the value. `Bit`, `Vector`, and `Bytes` payloads are scanned
+    /// character by character; `PackedBits` and `Real` are always [`LogicState::TwoState`].
+    pub fn logic_state(&self) -> LogicState {
+        match self {
+            SignalValue::Bit(ch) => classify_bits([*ch as u8].into_iter()),
+            SignalValue::Vector(text) => classify_bits(text.bytes()),
+            SignalValue::Bytes(bytes) => classify_bits(bytes.iter().copied()),
+            SignalValue::PackedBits { .. } | SignalValue::Real(_) => LogicState::TwoState,
+        }
+    }
+
+    /// Interprets a two-state bit, vector, or packed value as an unsigned integer.
+    ///
+    /// Returns `None` when any bit is not `'0'`/`'1'`, when a set bit lies above
+    /// bit 63, or for real and byte payloads.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            SignalValue::Bit(ch) => bits_to_u64([*ch as u8].into_iter()),
+            SignalValue::Vector(text) => bits_to_u64(text.bytes()),
+            SignalValue::PackedBits { width, bits } => {
+                let width = *width as usize;
+                if bits.len() * 8 < width {
+                    return None;
+                }
+                bits_to_u64((0..width).map(|idx| {
+                    let (index, shift) = bit_position(idx, true);
+                    if bits[index] & (1 << shift) != 0 {
+                        b'1'
+                    } else {
+                        b'0'
+                    }
+                }))
+            }
+            SignalValue::Real(_) | SignalValue::Bytes(_) => None,
+        }
+    }
+
+    /// Interprets a two-state value of `width` bits as an integer, sign-extending from bit
+    /// `width - 1` when `signed` is set.
+    ///
+    /// Returns `None` under the same conditions as [`SignalValue::as_u64`], when `width` is
+    /// zero or above 64, or when an unsigned value does not fit in an `i64`.
+    pub fn as_i64(&self, signed: bool, width: u32) -> Option<i64> {
+        if width == 0 || width > 64 {
+            return None;
+        }
+        let raw = self.as_u64()?;
+        if !signed {
+            return i64::try_from(raw).ok();
+        }
+        let shift = 64 - width;
+        Some(((raw << shift) as i64) >> shift)
+    }
+
+    /// Returns the value as `(width, bytes)` packed MSB-first, the layout of
+    /// [`SignalValue::PackedBits`]. Two-state bits and vectors are packed, packed values are
+    /// copied through, and anything holding other states (or a real or byte payload) yields
+    /// `None`.
+    pub fn to_packed(&self) -> Option<(u32, Vec<u8>)> {
+        match self {
+            SignalValue::Bit(ch) => Some((1, pack_ascii_bits(&[*ch as u8], 1)?)),
+            SignalValue::Vector(text) => {
+                let width = u32::try_from(text.len()).ok()?;
+                Some((width, pack_ascii_bits(text.as_bytes(), width)?))
+            }
+            SignalValue::PackedBits { width, bits } => Some((*width, bits.to_vec())),
+            SignalValue::Real(_) | SignalValue::Bytes(_) => None,
+        }
+    }
+
+    /// Returns the value as an ASCII bit string: packed values are expanded to `'0'`/`'1'`, while
+    /// bits and vectors are returned as written. Yields `None` for real and byte payloads, or
+    /// when a packed payload is shorter than its width.
+    pub fn to_ascii_vector(&self) -> Option<String> {
+        match self {
+            SignalValue::Bit(ch) => Some(ch.to_string()),
+            SignalValue::Vector(text) => Some(text.to_string()),
+            SignalValue::PackedBits { width, bits } => {
+                String::from_utf8(unpack_to_ascii(bits, *width)?).ok()
+            }
+            SignalValue::Real(_) | SignalValue::Bytes(_) => None,
+        }
+    }
+
     /// Converts the value into an owned representation.
     pub fn into_owned(self) -> SignalValue<'static> {
         match self {
@@ -44,3 +202,32 @@ impl<'a> SignalValue<'a> {
         }
     }
 }
+
+fn classify_bits(bits: impl Iterator<Item = u8>) -> LogicState {
+    let mut state = LogicState::TwoState;
+    for bit in bits {
+        match bit {
+            b'0' | b'1' => {}
+            b'x' | b'X' => return LogicState::HasX,
+            b'z' | b'Z' => state = LogicState::HasZ,
+            _ if state == LogicState::TwoState => state = LogicState::HasOther,
+            _ => {}
+        }
+    }
+    state
+}
+
+fn bits_to_u64(bits: impl Iterator<Item = u8>) -> Option<u64> {
+    let mut value = 0u64;
+    for bit in bits {
+        if value >> 63 != 0 {
+            return None;
+        }
+        value = match bit {
+            b'0' => value << 1,
+            b'1' => (value << 1) | 1,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
@@ -1,7 +1,9 @@
 use std::borrow::Cow;
 use std::io::{Read, Seek, SeekFrom};
 
+use crate::encoding::bit_position;
 use crate::error::{Error, Result};
+use crate::types::Endianness;
 
 /// Reads an exact number of bytes into a fixed-size array.
 #[inline]
@@ -68,15 +70,87 @@ pub fn read_varint_from_reader<R: Read>(reader: &mut R) -> Result<(u64, usize)>
     Ok((value, consumed))
 }
 
-/// Ensures the `endian_test` field matches the expected constant.
-pub fn validate_endian(endian_test: f64) -> Result<()> {
+/// Ensures the `endian_test` field (read big-endian) matches the expected constant and returns
+/// the byte order the producer stored it in.
+pub fn validate_endian(endian_test: f64) -> Result<Endianness> {
     const EXPECTED: f64 = std::f64::consts::E;
     let bits = endian_test.to_bits();
     let expected_bits = EXPECTED.to_bits();
-    if bits != expected_bits && bits != expected_bits.swap_bytes() {
-        return Err(Error::invalid(format!(
+    if bits == expected_bits {
+        Ok(Endianness::Big)
+    } else if bits == expected_bits.swap_bytes() {
+        Ok(Endianness::Little)
+    } else {
+        Err(Error::invalid(format!(
             "unexpected endian test marker: {endian_test:?}"
-        )));
+        )))
     }
-    Ok(())
+}
+
+/// Packs an ASCII `'0'`/`'1'` string into `width.div_ceil(8)` bytes (at least one), MSB-first
+/// within each byte, using SIMD instructions when the `simd` feature and the host allow it.
+/// Returns `None` if `data` contains any other character. To pack `bool` slices in either bit
+/// order, use [`crate::encoding::pack_bits`].
+pub fn pack_ascii_bits(data: &[u8], width: u32) -> Option<Vec<u8>> {
+    #[cfg(feature = "simd")]
+    {
+        let len = packed_len(width)?;
+        if let Some(result) = crate::simd::pack_ascii_bits(data, width, len) {
+            return Some(result);
+        }
+    }
+
+    pack_ascii_bits_scalar(data, width)
+}
+
+/// Portable implementation of [`pack_ascii_bits`].
+pub fn pack_ascii_bits_scalar(data: &[u8], width: u32) -> Option<Vec<u8>> {
+    if data.iter().any(|b| *b != b'0' && *b != b'1') {
+        return None;
+    }
+    let mut out = vec![0u8; packed_len(width)?];
+    for (idx, byte) in data.iter().enumerate() {
+        let (index, shift) = bit_position(idx, true);
+        if *byte == b'1'
+            && let Some(slot) = out.get_mut(index)
+        {
+            *slot |= 1 << shift;
+        }
+    }
+    Some(out)
+}
+
+fn packed_len(width: u32) -> Option<usize> {
+    Some(usize::try_from(width).ok()?.div_ceil(8).max(1))
+}
+
+/// Expands the first `width` bits of `bits` (MSB-first within each byte) into ASCII `'0'`/`'1'`,
+/// using SIMD instructions when the `simd` feature and the host allow it. Returns `None` when
+/// `bits` holds fewer than `width` bits. [`crate::encoding::unpack_bits`] expands to `bool`s
+/// instead.
+pub fn unpack_to_ascii(bits: &[u8], width: u32) -> Option<Vec<u8>> {
+    #[cfg(feature = "simd")]
+    {
+        if let Some(result) = crate::simd::unpack_packed_bits(bits, width) {
+            return Some(result);
+        }
+    }
+
+    unpack_to_ascii_scalar(bits, width)
+}
+
+/// Portable implementation of [`unpack_to_ascii`].
+pub fn unpack_to_ascii_scalar(bits: &[u8], width: u32) -> Option<Vec<u8>> {
+    let width = usize::try_from(width).ok()?;
+    if bits.len() < width.div_ceil(8) {
+        return None;
+    }
+    Some(
+        (0..width)
+            .map(|idx| {
+                let (index, shift) = bit_position(idx, true);
+                b'0' + ((bits[index] >> shift) & 1)
+            })
+            .collect(),
+    )
 }
@@ -1,27 +1,45 @@
 //! Incremental writer producing FST output streams.
 
 use crate::block::{
-    ChainIndexEntry, GeomEntry, GeomInfo, Header, HierarchyBlock, HierarchyCompression,
-    HierarchyItem, ScopeEntry, VarEntry, encode_chain_index, encode_chain_payload,
-    encode_frame_section, encode_time_section,
+    ATTR_TYPE_MISC, AttributeEntry, BlackoutBlock, BlackoutEvent, ChainIndexEntry, GeomEntry,
+    GeomInfo, Header, HierarchyBlock, HierarchyCompression, HierarchyItem, MISC_TYPE_ENUM_TABLE,
+    ScopeEntry, VarEntry, encode_chain_index, encode_chain_payload, encode_chain_payload_with,
+    encode_enum_table, encode_frame_section, encode_time_section,
 };
-use crate::encoding::encode_varint;
+use crate::compression::CustomCompressor;
+use crate::encoding::{encode_varint, varint_len};
 use crate::error::{Error, Result};
 use crate::io::{WriteSeek, WriterBackend};
-use crate::types::{BlockType, PackType, ScopeType, SignalValue, VarDir, VarType};
+use crate::types::{
+    BlockType, Endianness, FileType, PackType, ScopeType, SignalValue, VarDir, VarType,
+};
+use crate::util::{pack_ascii_bits, unpack_to_ascii};
 use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::io::{Cursor, Write};
+use std::fs::File;
+use std::io::{Cursor, ErrorKind, Seek, SeekFrom, Write};
+use std::path::Path;
 
 #[cfg(feature = "gzip")]
 use flate2::{Compression, write::GzEncoder};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// Options controlling [`FstWriter`] behaviour.
 #[derive(Debug, Clone)]
 pub struct WriterOptions {
     /// Base-10 exponent describing the timescale to encode inside the header.
     pub timescale_exponent: i8,
-    /// Optional compression quality hint (algorithm specific).
+    /// File type to record in the header, overriding the value passed to
+    /// [`FstWriter::write_header`] when set.
+    pub file_type: Option<FileType>,
+    /// Version string to record in the header, overriding the value passed to
+    /// [`FstWriter::write_header`] when set. Truncated to fit the 128-byte field.
+    pub version: Option<String>,
+    /// Date string to record in the header, overriding the value passed to
+    /// [`FstWriter::write_header`] when set. Truncated to fit the 119-byte field.
+    pub date: Option<String>,
+    /// Compression level: 0-9 for zlib (checked when a zlib path is selected), up to 22 for Zstd.
     pub compression_level: Option<u32>,
     /// Compression applied to chain payloads inside value-change blocks.
     pub chain_compression: ChainCompression,
@@ -29,6 +47,42 @@ pub struct WriterOptions {
     pub time_compression: TimeCompression,
     /// Wrap the entire file in an outer `FST_BL_ZWRAPPER` gzip envelope.
     pub wrap_zlib: bool,
+    /// Codec used when `chain_compression` is [`ChainCompression::Custom`].
+    pub custom_chain_compressor: Option<CustomCompressor>,
+    /// Attempt zlib compression of the geometry block (kept raw when it does not shrink).
+    pub compress_geometry: bool,
+    /// Store runs of identical geometry entries as `(count, entry)` pairs when that is shorter.
+    /// This is a `wavefst` extension (see [`crate::block::GEOMETRY_RLE_FLAG`]) that libfst-based
+    /// tools cannot read, so it is off by default.
+    pub geometry_rle: bool,
+    /// Compression applied to the hierarchy block written alongside the header.
+    pub hierarchy_compression: HierarchyCompression,
+    /// Policy applied when a handle receives more than one change at the same timestamp.
+    pub duplicate_changes: DuplicateChangePolicy,
+    /// Flush a value-change block automatically once this many changes are pending. The block is
+    /// only cut when a later timestamp arrives, so one timestamp never spans two blocks.
+    pub max_pending_changes: Option<usize>,
+    /// Flush a value-change block automatically once the estimated `required_memory` of the
+    /// pending block (frame plus uncompressed chains) reaches this many bytes. Like
+    /// `max_pending_changes`, the block is only cut at a timestamp boundary.
+    pub max_block_memory: Option<u64>,
+    /// Reject real-valued changes that are NaN or infinite instead of storing them. Untouched
+    /// reals in the frame are still initialised to NaN.
+    pub reject_nonfinite_reals: bool,
+    /// Accept value changes before [`FstWriter::write_header`], so scopes and variables can be
+    /// declared while changes are already being emitted. Changes are held in memory until the
+    /// header is written; [`FstWriter::finish`] writes a default header if none was written.
+    pub deferred_header: bool,
+    /// Reject variables and aliases whose name is already used by another variable in the same
+    /// scope, which would make path lookups ambiguous.
+    pub forbid_duplicate_names: bool,
+    /// Write each value-change block's initial frame. When disabled, blocks carry an empty frame
+    /// and readers only learn a signal's value from its first change in the block, which suits
+    /// producers that always start from a known reset state.
+    pub emit_frame: bool,
+    /// Bit state written to the initial frame for `Fixed` signals that have not received a
+    /// value change yet. Must be one of the FST states `01xzhuwl-?`.
+    pub undriven_fill: char,
 }
 
 /// Compression choice for the per-handle value-change payloads.
@@ -42,6 +96,14 @@ pub enum ChainCompression {
     Lz4,
     /// Compress using FastLZ (`pack marker` = `'F'`).
     FastLz,
+    /// Compress using Zstandard (`pack marker` = `'S'`, a `wavefst` extension).
+    Zstd,
+    /// Compress with [`WriterOptions::custom_chain_compressor`] under its own marker byte.
+    Custom,
+    /// Per block, compress the chains with every libfst-readable codec compiled into the crate
+    /// (raw, zlib, LZ4, FastLZ) and keep whichever chain buffer is smallest. Zstd is never tried,
+    /// so enabling the `zstd` feature does not change the format `Auto` writes.
+    Auto,
 }
 
 /// Compression choice for the block-level time delta section.
@@ -51,6 +113,39 @@ pub enum TimeCompression {
     Raw,
     /// Compress the time table using zlib.
     Zlib,
+    /// Compress the time table using LZ4 (tagged with
+    /// [`TIME_SECTION_LZ4_TAG`](crate::block::TIME_SECTION_LZ4_TAG)).
+    Lz4,
+}
+
+/// Resolution applied to repeated changes of one handle at a single timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateChangePolicy {
+    /// Keep only the most recently emitted value for the `(timestamp, handle)` pair.
+    #[default]
+    LastWins,
+    /// Fail the block flush with an error describing the duplicated pair.
+    Reject,
+}
+
+/// Size breakdown of a flushed value-change block, as reported by
+/// [`FstWriter::last_block_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BlockStats {
+    /// Total length of the encoded chains before compression.
+    pub chain_bytes_raw: u64,
+    /// Bytes occupied by the chain area in the block, including per-chain length prefixes.
+    pub chain_bytes_stored: u64,
+    /// Length of the initial frame before compression.
+    pub frame_uncompressed_len: u64,
+    /// Length of the initial frame as written.
+    pub frame_compressed_len: u64,
+    /// Length of the time table before compression.
+    pub time_uncompressed_len: u64,
+    /// Length of the time table as written.
+    pub time_compressed_len: u64,
+    /// Number of canonical handles that carry a chain in the block.
+    pub handle_count: u64,
 }
 
 impl Default for WriterOptions {
@@ -65,12 +160,32 @@ impl Default for WriterOptions {
         } else {
             TimeCompression::Raw
         };
+        let hierarchy_compression = if cfg!(feature = "gzip") {
+            HierarchyCompression::Zlib { level: 4 }
+        } else {
+            HierarchyCompression::Raw
+        };
         Self {
             timescale_exponent: -9,
+            file_type: None,
+            version: None,
+            date: None,
             compression_level: None,
             chain_compression,
             time_compression,
             wrap_zlib: false,
+            custom_chain_compressor: None,
+            compress_geometry: cfg!(feature = "gzip"),
+            geometry_rle: false,
+            hierarchy_compression,
+            duplicate_changes: DuplicateChangePolicy::default(),
+            max_pending_changes: None,
+            max_block_memory: None,
+            reject_nonfinite_reals: false,
+            deferred_header: false,
+            forbid_duplicate_names: false,
+            emit_frame: true,
+            undriven_fill: 'x',
         }
     }
 }
@@ -120,15 +235,136 @@ impl<W: WriteSeek> WriterBuilder<W> {
         self
     }
 
+    /// Sets the file type that will be recorded in the header.
+    pub fn file_type(mut self, file_type: FileType) -> Self {
+        self.options.file_type = Some(file_type);
+        self
+    }
+
+    /// Sets the version string that will be recorded in the header.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.options.version = Some(version.into());
+        self
+    }
+
+    /// Sets the date string that will be recorded in the header.
+    pub fn date(mut self, date: impl Into<String>) -> Self {
+        self.options.date = Some(date.into());
+        self
+    }
+
+    /// Compresses chains with a user-supplied codec, storing its marker byte in each block.
+    pub fn custom_chain_compression(mut self, compressor: CustomCompressor) -> Self {
+        self.options.chain_compression = ChainCompression::Custom;
+        self.options.custom_chain_compressor = Some(compressor);
+        self
+    }
+
+    /// Enables or disables zlib compression of the geometry block.
+    pub fn compress_geometry(mut self, enabled: bool) -> Self {
+        self.options.compress_geometry = enabled;
+        self
+    }
+
+    /// Enables the run-length geometry encoding (a `wavefst` extension unreadable by libfst).
+    pub fn geometry_rle(mut self, enabled: bool) -> Self {
+        self.options.geometry_rle = enabled;
+        self
+    }
+
+    /// Allows value changes to be emitted before the header is written.
+    pub fn deferred_header(mut self, enabled: bool) -> Self {
+        self.options.deferred_header = enabled;
+        self
+    }
+
+    /// Rejects duplicate variable names within a scope.
+    pub fn forbid_duplicate_names(mut self, enabled: bool) -> Self {
+        self.options.forbid_duplicate_names = enabled;
+        self
+    }
+
+    /// Enables or disables the initial frame written with each value-change block.
+    pub fn emit_frame(mut self, enabled: bool) -> Self {
+        self.options.emit_frame = enabled;
+        self
+    }
+
+    /// Sets the bit state used for undriven signals in the initial frame.
+    pub fn undriven_fill(mut self, fill: char) -> Self {
+        self.options.undriven_fill = fill;
+        self
+    }
+
+    /// Selects the compression used for the hierarchy block.
+    pub fn hierarchy_compression(mut self, compression: HierarchyCompression) -> Self {
+        self.options.hierarchy_compression = compression;
+        self
+    }
+
+    /// Chooses how repeated changes for the same handle and timestamp are resolved.
+    pub fn duplicate_changes(mut self, policy: DuplicateChangePolicy) -> Self {
+        self.options.duplicate_changes = policy;
+        self
+    }
+
+    /// Flushes a value-change block automatically once `limit` changes are pending.
+    pub fn max_pending_changes(mut self, limit: Option<usize>) -> Self {
+        self.options.max_pending_changes = limit;
+        self
+    }
+
+    /// Flushes a value-change block automatically once its estimated memory reaches `limit` bytes.
+    pub fn max_block_memory(mut self, limit: Option<u64>) -> Self {
+        self.options.max_block_memory = limit;
+        self
+    }
+
+    /// Rejects NaN and infinite values emitted for real-valued handles.
+    pub fn reject_nonfinite_reals(mut self, value: bool) -> Self {
+        self.options.reject_nonfinite_reals = value;
+        self
+    }
+
+    /// Sets the compression level. zlib accepts 0-9, which [`Self::build`] enforces when zlib
+    /// chains, time tables, geometry compression, or the wrapper are selected; Zstd chains accept up
+    /// to 22 and clamp higher values.
+    pub fn compression_level(mut self, level: Option<u32>) -> Self {
+        self.options.compression_level = level;
+        self
+    }
+
     /// Builds the writer, validating options before returning the instance.
     pub fn build(self) -> Result<FstWriter<W>> {
         FstWriter::with_backend(self.sink, self.options)
     }
 }
 
+/// Rejects geometry that contradicts the storage implied by `var_type`.
+fn check_var_geometry(var_type: VarType, geometry: &GeomEntry) -> Result<()> {
+    let consistent = match var_type {
+        VarType::SvByte => *geometry == GeomEntry::Fixed(8),
+        VarType::SvShortInt => *geometry == GeomEntry::Fixed(16),
+        VarType::SvInt => *geometry == GeomEntry::Fixed(32),
+        VarType::SvLongInt => *geometry == GeomEntry::Fixed(64),
+        VarType::VcdReal | VarType::VcdRealParameter | VarType::VcdRealtime => {
+            *geometry == GeomEntry::Real
+        }
+        VarType::SvShortReal => matches!(geometry, GeomEntry::Real | GeomEntry::ShortReal),
+        _ => true,
+    };
+    if consistent {
+        Ok(())
+    } else {
+        Err(Error::invalid(format!(
+            "{var_type:?} variables cannot use geometry {geometry:?}"
+        )))
+    }
+}
+
 fn validate_options(options: &WriterOptions) -> Result<()> {
     match options.chain_compression {
-        ChainCompression::Raw => {}
+        ChainCompression::Raw | ChainCompression::Auto => {}
         ChainCompression::Zlib => {
             #[cfg(not(feature = "gzip"))]
             {
@@ -153,6 +389,25 @@ fn validate_options(options: &WriterOptions) -> Result<()> {
                 ));
             }
         }
+        ChainCompression::Zstd => {
+            #[cfg(not(feature = "zstd"))]
+            {
+                return Err(Error::unsupported(
+                    "zstd chain compression requires the `zstd` feature",
+                ));
+            }
+        }
+        ChainCompression::Custom => {
+            let compressor = options.custom_chain_compressor.as_ref().ok_or_else(|| {
+                Error::invalid("custom chain compression requires a registered compressor")
+            })?;
+            let marker = compressor.marker();
+            if PackType::from_marker(marker).is_some() {
+                return Err(Error::invalid(format!(
+                    "custom pack marker {marker:02x} collides with a built-in marker"
+                )));
+            }
+        }
     }
 
     match options.time_compression {
@@ -165,6 +420,60 @@ fn validate_options(options: &WriterOptions) -> Result<()> {
                 ));
             }
         }
+        TimeCompression::Lz4 => {
+            #[cfg(not(feature = "lz4"))]
+            {
+                return Err(Error::unsupported(
+                    "LZ4 time compression requires the `lz4` feature",
+                ));
+            }
+        }
+    }
+
+    if options.compress_geometry {
+        #[cfg(not(feature = "gzip"))]
+        {
+            return Err(Error::unsupported(
+                "geometry compression requires the `gzip` feature",
+            ));
+        }
+    }
+
+    match options.hierarchy_compression {
+        HierarchyCompression::Raw => {}
+        HierarchyCompression::Zlib { .. } => {
+            #[cfg(not(feature = "gzip"))]
+            {
+                return Err(Error::unsupported(
+                    "zlib hierarchy compression requires the `gzip` feature",
+                ));
+            }
+        }
+        HierarchyCompression::Lz4 | HierarchyCompression::Lz4Duo => {
+            #[cfg(not(feature = "lz4"))]
+            {
+                return Err(Error::unsupported(
+                    "lz4 hierarchy compression requires the `lz4` feature",
+                ));
+            }
+        }
+    }
+
+    BitValue::from_char(options.undriven_fill).map_err(|_| {
+        Error::invalid(format!(
+            "undriven fill '{}' is not an FST bit state",
+            options.undriven_fill
+        ))
+    })?;
+
+    #[cfg(feature = "gzip")]
+    if let Some(level) = options.compression_level
+        && level > 9
+        && uses_zlib_level(options)
+    {
+        return Err(Error::invalid(format!(
+            "compression level {level} is outside the zlib range 0-9"
+        )));
     }
 
     if options.wrap_zlib {
@@ -176,9 +485,31 @@ fn validate_options(options: &WriterOptions) -> Result<()> {
         }
     }
 
+    if options.max_pending_changes == Some(0) {
+        return Err(Error::invalid(
+            "max_pending_changes must be greater than zero",
+        ));
+    }
+
+    if options.max_block_memory == Some(0) {
+        return Err(Error::invalid("max_block_memory must be greater than zero"));
+    }
+
     Ok(())
 }
 
+/// Whether `compression_level` reaches a zlib encoder: zlib or `Auto` chains, a zlib time table,
+/// geometry compression, or the file-level wrapper.
+#[cfg(feature = "gzip")]
+fn uses_zlib_level(options: &WriterOptions) -> bool {
+    matches!(
+        options.chain_compression,
+        ChainCompression::Zlib | ChainCompression::Auto
+    ) || options.time_compression == TimeCompression::Zlib
+        || options.compress_geometry
+        || options.wrap_zlib
+}
+
 /// Streaming writer for FST files.
 enum OutputBackend<W: WriteSeek> {
     Direct(WriterBackend<W>),
@@ -221,6 +552,41 @@ impl<W: WriteSeek> OutputBackend<W> {
         Ok(())
     }
 
+    fn position(&mut self) -> Result<u64> {
+        let position = match self {
+            OutputBackend::Direct(backend) => backend.get_mut().stream_position()?,
+            OutputBackend::Wrapped { buffer, .. } => buffer.get_mut().stream_position()?,
+        };
+        Ok(position)
+    }
+
+    /// Overwrites bytes at `offset` and returns to the end of the stream. Returns `false` without
+    /// writing when the sink cannot reposition (e.g. [`crate::io::SequentialWriter`]).
+    fn patch(&mut self, offset: u64, bytes: &[u8]) -> Result<bool> {
+        fn patch_seekable<S: Write + Seek>(
+            sink: &mut S,
+            offset: u64,
+            bytes: &[u8],
+        ) -> Result<bool> {
+            let end = sink.stream_position()?;
+            match sink.seek(SeekFrom::Start(offset)) {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::Unsupported => return Ok(false),
+                Err(err) => return Err(err.into()),
+            }
+            sink.write_all(bytes)?;
+            sink.seek(SeekFrom::Start(end))?;
+            Ok(true)
+        }
+
+        match self {
+            OutputBackend::Direct(backend) => patch_seekable(backend.get_mut(), offset, bytes),
+            OutputBackend::Wrapped { buffer, .. } => {
+                patch_seekable(buffer.get_mut(), offset, bytes)
+            }
+        }
+    }
+
     fn with_writer<F>(&mut self, f: F) -> Result<()>
     where
         F: FnOnce(&mut dyn Write) -> Result<()>,
@@ -292,8 +658,14 @@ pub struct FstWriter<W: WriteSeek> {
     metadata_written: bool,
     frame_state: FrameState,
     scopes: Vec<ScopeEntry>,
+    /// Handles declared under each variable name, per scope. Used to reject duplicates when
+    /// `forbid_duplicate_names` is set and to resolve alias targets by name.
+    scope_names: Vec<HashMap<String, Vec<u32>>>,
     variables: Vec<VarEntry>,
     hierarchy_items: Vec<HierarchyItem>,
+    attributes: Vec<AttributeEntry>,
+    open_attributes: usize,
+    enum_tables: u64,
     scope_stack: Vec<usize>,
     geometry: Vec<GeomEntry>,
     alias_of: Vec<Option<u32>>,
@@ -301,7 +673,25 @@ pub struct FstWriter<W: WriteSeek> {
     next_handle: u32,
     header: Option<Header>,
     pending_changes: Vec<PendingChange>,
+    pending_max_time: u64,
+    pending_memory: u64,
+    last_block_stats: Option<BlockStats>,
     vc_blocks_written: u64,
+    blackout_events: Vec<BlackoutEvent>,
+    trailing_blackout: bool,
+    header_offset: u64,
+    observed_times: Option<(u64, u64)>,
+    /// Latest timestamp of the value-change blocks already written. Changes within the pending
+    /// block may arrive in any order, but never before this time.
+    flushed_end_time: Option<u64>,
+}
+
+impl FstWriter<File> {
+    /// Creates (or truncates) the file at `path` and returns a writer with default options.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::create(path)?;
+        FstWriter::builder(file).build()
+    }
 }
 
 impl<W: WriteSeek> FstWriter<W> {
@@ -319,8 +709,12 @@ impl<W: WriteSeek> FstWriter<W> {
             metadata_written: false,
             frame_state: FrameState::default(),
             scopes: Vec::new(),
+            scope_names: Vec::new(),
             variables: Vec::new(),
             hierarchy_items: Vec::new(),
+            attributes: Vec::new(),
+            open_attributes: 0,
+            enum_tables: 0,
             scope_stack: Vec::new(),
             geometry: Vec::new(),
             alias_of: Vec::new(),
@@ -328,7 +722,15 @@ impl<W: WriteSeek> FstWriter<W> {
             next_handle: 1,
             header: None,
             pending_changes: Vec::new(),
+            pending_max_time: 0,
+            pending_memory: 0,
+            last_block_stats: None,
             vc_blocks_written: 0,
+            blackout_events: Vec::new(),
+            trailing_blackout: false,
+            header_offset: 0,
+            observed_times: None,
+            flushed_end_time: None,
         })
     }
 
@@ -337,6 +739,83 @@ impl<W: WriteSeek> FstWriter<W> {
         WriterBuilder::new(sink)
     }
 
+    /// Reopens an existing trace so further value-change blocks can be appended to it. `header`
+    /// and `geometry` must describe the file as written, e.g. as returned by
+    /// [`FstReader::header`](crate::reader::FstReader::header) and
+    /// [`FstReader::geometry`](crate::reader::FstReader::geometry). The sink is positioned at its
+    /// end, after which [`Self::emit_change`] and [`Self::flush`] behave as usual; scopes and
+    /// variables can no longer be declared. [`Self::finish`] widens the header's time range and
+    /// block count to cover the appended blocks.
+    ///
+    /// `final_values` seeds the frame state with the value each handle holds at the end of the
+    /// existing trace, as returned by
+    /// [`FstReader::final_values`](crate::reader::FstReader::final_values); handles it omits start
+    /// from the undriven fill. Changes earlier than the header's end time are rejected once the
+    /// trace holds a value-change block.
+    ///
+    /// The geometry does not record aliases, so every handle is written as an independent signal;
+    /// changes should be emitted on the handles that carried data in the original file. The trace
+    /// must not be wrapped in a zlib envelope.
+    pub fn append(
+        mut sink: W,
+        header: Header,
+        geometry: GeomInfo,
+        final_values: &[(u32, SignalValue<'_>)],
+    ) -> Result<FstWriter<W>> {
+        if geometry.entries.len() as u64 != header.max_handle {
+            return Err(Error::invalid(format!(
+                "geometry has {} entries but the header declares {} handles",
+                geometry.entries.len(),
+                header.max_handle
+            )));
+        }
+        if header.endianness != Endianness::native() {
+            return Err(Error::unsupported(
+                "appending to a trace with non-native real byte order",
+            ));
+        }
+        let next_handle = u32::try_from(header.max_handle)
+            .ok()
+            .and_then(|max| max.checked_add(1))
+            .ok_or_else(|| Error::invalid("handle counter overflow"))?;
+        sink.seek(SeekFrom::End(0))?;
+
+        let options = WriterOptions {
+            timescale_exponent: header.timescale_exponent,
+            ..WriterOptions::default()
+        };
+        let mut writer = Self::with_backend(sink, options)?;
+        let fill = writer.undriven_fill_byte();
+        for (index, entry) in geometry.entries.iter().enumerate() {
+            writer
+                .frame_state
+                .register_handle(index as u32 + 1, entry, fill);
+        }
+        for (handle, value) in final_values {
+            let entry = handle
+                .checked_sub(1)
+                .and_then(|index| geometry.entries.get(index as usize))
+                .ok_or_else(|| {
+                    Error::invalid(format!("final value for unknown handle {handle}"))
+                })?;
+            let owned = Self::convert_value(value.clone(), entry)?;
+            writer.frame_state.update(*handle, &owned)?;
+        }
+        let handle_count = geometry.entries.len();
+        writer.geometry = geometry.entries;
+        writer.alias_of = vec![None; handle_count];
+        writer.alias_children = vec![Vec::new(); handle_count];
+        writer.next_handle = next_handle;
+        writer.header_written = true;
+        writer.metadata_written = true;
+        writer.vc_blocks_written = header.vc_section_count;
+        writer.observed_times =
+            (header.vc_section_count > 0).then_some((header.start_time, header.end_time));
+        writer.flushed_end_time = (header.vc_section_count > 0).then_some(header.end_time);
+        writer.header = Some(header);
+        Ok(writer)
+    }
+
     /// Writes the FST header block. This implementation currently emits a minimal header and is
     /// intended as a starting point for further development.
     pub fn write_header(&mut self, mut header: Header) -> Result<()> {
@@ -353,11 +832,24 @@ impl<W: WriteSeek> FstWriter<W> {
         header.var_count = self.variables.len() as u64;
         header.max_handle = self.next_handle.saturating_sub(1) as u64;
         header.timescale_exponent = self.options.timescale_exponent;
+        if let Some(file_type) = self.options.file_type {
+            header.file_type = file_type.into();
+        }
+        if let Some(version) = &self.options.version {
+            header.version.clone_from(version);
+        }
+        if let Some(date) = &self.options.date {
+            header.date.clone_from(date);
+        }
         header.section_length = 329;
+        // Reals are stored in host order; the header's marker must announce that order.
+        header.endianness = Endianness::native();
 
+        self.header_offset = self.output.position()?;
         self.write_header_block(&header)?;
-        self.write_geometry_block(false)?;
+        self.write_geometry_block(self.options.compress_geometry)?;
         self.write_hierarchy_block()?;
+        self.write_blackout_block()?;
 
         self.header_written = true;
         self.metadata_written = true;
@@ -365,6 +857,30 @@ impl<W: WriteSeek> FstWriter<W> {
         Ok(())
     }
 
+    /// Records a dump on/off event. Events must be added in chronological order. Events recorded
+    /// after the header are written as a trailing blackout block when the writer finishes.
+    pub fn add_blackout(&mut self, is_on: bool, time: u64) -> Result<()> {
+        if let Some(last) = self.blackout_events.last()
+            && time < last.time
+        {
+            return Err(Error::invalid(format!(
+                "blackout event at time {time} precedes previous event at time {}",
+                last.time
+            )));
+        }
+        self.blackout_events.push(BlackoutEvent { is_on, time });
+        self.trailing_blackout |= self.header_written;
+        Ok(())
+    }
+
+    /// Overrides the timescale exponent configured on the builder. Must be called before the
+    /// header is written.
+    pub fn set_timescale_exponent(&mut self, exponent: i8) -> Result<()> {
+        self.ensure_metadata_mutable()?;
+        self.options.timescale_exponent = exponent;
+        Ok(())
+    }
+
     /// Starts a new scope and pushes it onto the hierarchy stack.
     pub fn begin_scope(
         &mut self,
@@ -381,6 +897,7 @@ impl<W: WriteSeek> FstWriter<W> {
             parent,
         };
         self.scopes.push(scope);
+        self.scope_names.push(HashMap::new());
         let index = self.scopes.len() - 1;
         self.hierarchy_items
             .push(HierarchyItem::ScopeBegin { scope_index: index });
@@ -398,7 +915,63 @@ impl<W: WriteSeek> FstWriter<W> {
         Ok(())
     }
 
+    /// Opens an attribute (`FST_ST_GEN_ATTRBEGIN`) within the currently active scope.
+    pub fn begin_attribute(
+        &mut self,
+        attr_type: u8,
+        subtype: u8,
+        name: impl Into<String>,
+        argument: u64,
+    ) -> Result<()> {
+        self.ensure_metadata_mutable()?;
+        self.attributes.push(AttributeEntry {
+            attr_type,
+            subtype,
+            name: name.into(),
+            argument,
+            scope: self.scope_stack.last().copied(),
+        });
+        let attribute_index = self.attributes.len() - 1;
+        self.hierarchy_items
+            .push(HierarchyItem::AttributeBegin { attribute_index });
+        self.open_attributes += 1;
+        Ok(())
+    }
+
+    /// Closes the most recently opened attribute.
+    pub fn end_attribute(&mut self) -> Result<()> {
+        self.ensure_metadata_mutable()?;
+        if self.open_attributes == 0 {
+            return Err(Error::invalid("attribute stack underflow"));
+        }
+        self.open_attributes -= 1;
+        self.hierarchy_items.push(HierarchyItem::AttributeEnd);
+        Ok(())
+    }
+
+    /// Declares an enum value table as an `FST_MT_ENUMTABLE` attribute in the current scope and
+    /// returns its table handle. `members` are `(literal, value)` pairs, e.g. `("IDLE", "00")`.
+    pub fn add_enum_definition(&mut self, name: &str, members: &[(String, String)]) -> Result<u64> {
+        if name.is_empty() {
+            return Err(Error::invalid("enum definitions require a name"));
+        }
+        let handle = self.enum_tables + 1;
+        self.begin_attribute(
+            ATTR_TYPE_MISC,
+            MISC_TYPE_ENUM_TABLE,
+            encode_enum_table(name, members),
+            handle,
+        )?;
+        self.end_attribute()?;
+        self.enum_tables = handle;
+        Ok(handle)
+    }
+
     /// Declares a variable within the currently active scope. Returns the newly allocated handle.
+    ///
+    /// Types with an implied storage are checked against `geometry`: `SvByte`, `SvShortInt`,
+    /// `SvInt`, and `SvLongInt` require `Fixed` widths of 8, 16, 32, and 64 bits, the VCD real
+    /// types require [`GeomEntry::Real`], and `SvShortReal` accepts `Real` or `ShortReal`.
     pub fn add_variable(
         &mut self,
         var_type: VarType,
@@ -407,22 +980,22 @@ impl<W: WriteSeek> FstWriter<W> {
         geometry: GeomEntry,
     ) -> Result<u32> {
         self.ensure_metadata_mutable()?;
+        check_var_geometry(var_type, &geometry)?;
         let scope = self
             .scope_stack
             .last()
             .copied()
             .ok_or_else(|| Error::invalid("variables require an active scope"))?;
-
+        let name = name.into();
         let handle = self.next_handle;
+        self.claim_name(scope, &name, handle)?;
+
         self.next_handle = self
             .next_handle
             .checked_add(1)
             .ok_or_else(|| Error::invalid("handle counter overflow"))?;
 
-        let length = match geometry {
-            GeomEntry::Fixed(bytes) => Some(bytes),
-            GeomEntry::Real | GeomEntry::Variable => None,
-        };
+        let length = geometry.bit_width();
 
         self.geometry.push(geometry);
         self.alias_of.push(None);
@@ -430,7 +1003,7 @@ impl<W: WriteSeek> FstWriter<W> {
         self.variables.push(VarEntry {
             var_type,
             direction,
-            name: name.into(),
+            name,
             length,
             handle,
             alias_of: None,
@@ -440,13 +1013,15 @@ impl<W: WriteSeek> FstWriter<W> {
         let var_index = self.variables.len() - 1;
         self.hierarchy_items.push(HierarchyItem::Var { var_index });
         if let Some(entry) = self.geometry.last() {
-            self.frame_state.register_handle(handle, entry);
+            self.frame_state
+                .register_handle(handle, entry, self.undriven_fill_byte());
         }
 
         Ok(handle)
     }
 
-    /// Declares an alias that reuses the value stream of an existing handle.
+    /// Declares an alias that reuses the value stream of an existing handle. The alias is
+    /// allocated its own handle, whose geometry entry repeats the target's.
     pub fn add_alias(
         &mut self,
         var_type: VarType,
@@ -467,6 +1042,7 @@ impl<W: WriteSeek> FstWriter<W> {
             .last()
             .copied()
             .ok_or_else(|| Error::invalid("aliases require an active scope"))?;
+        let name = name.into();
 
         let canonical = self.resolve_canonical_handle(target_handle)?;
         let target_index = (canonical - 1) as usize;
@@ -477,6 +1053,8 @@ impl<W: WriteSeek> FstWriter<W> {
         })?;
 
         let handle = self.next_handle;
+        self.claim_name(scope, &name, handle)?;
+
         self.next_handle = self
             .next_handle
             .checked_add(1)
@@ -494,11 +1072,8 @@ impl<W: WriteSeek> FstWriter<W> {
         self.variables.push(VarEntry {
             var_type,
             direction,
-            name: name.into(),
-            length: match geometry {
-                GeomEntry::Fixed(bytes) => Some(bytes),
-                GeomEntry::Real | GeomEntry::Variable => None,
-            },
+            name,
+            length: geometry.bit_width(),
             handle,
             alias_of: Some(canonical),
             scope: Some(scope),
@@ -507,20 +1082,56 @@ impl<W: WriteSeek> FstWriter<W> {
         let var_index = self.variables.len() - 1;
         self.hierarchy_items.push(HierarchyItem::Var { var_index });
 
-        self.frame_state.register_handle(handle, &geometry);
+        self.frame_state
+            .register_handle(handle, &geometry, self.undriven_fill_byte());
         self.frame_state.clone_from(canonical, handle)?;
 
         Ok(handle)
     }
 
-    /// Records a value change that will be emitted in the next value-change block.
+    /// Declares an alias of the variable called `target_name` in the currently active scope. Fails
+    /// when no variable of that name exists there, or when several do.
+    pub fn add_alias_by_name(
+        &mut self,
+        var_type: VarType,
+        direction: VarDir,
+        name: impl Into<String>,
+        target_name: &str,
+    ) -> Result<u32> {
+        self.ensure_metadata_mutable()?;
+        let scope = self
+            .scope_stack
+            .last()
+            .copied()
+            .ok_or_else(|| Error::invalid("aliases require an active scope"))?;
+        let target_handle = match self.scope_names[scope].get(target_name).map(Vec::as_slice) {
+            Some([handle]) => *handle,
+            Some(_) => {
+                return Err(Error::invalid(format!(
+                    "alias target '{target_name}' is ambiguous in scope '{}'",
+                    self.scopes[scope].name
+                )));
+            }
+            None => {
+                return Err(Error::invalid(format!(
+                    "alias target '{target_name}' is not declared in scope '{}'",
+                    self.scopes[scope].name
+                )));
+            }
+        };
+        self.add_alias(var_type, direction, name, target_handle)
+    }
+
+    /// Records a value change that will be emitted in the next value-change block. Timestamps
+    /// may arrive out of order within a block, but not before the end of an already written
+    /// block.
     pub fn emit_change(
         &mut self,
         timestamp: u64,
         handle: u32,
         value: SignalValue<'_>,
     ) -> Result<()> {
-        if !self.header_written {
+        if !self.header_written && !self.options.deferred_header {
             return Err(Error::invalid(
                 "value changes cannot be emitted before the header is written",
             ));
@@ -531,6 +1142,13 @@ impl<W: WriteSeek> FstWriter<W> {
                 self.next_handle - 1
             )));
         }
+        if let Some(end) = self.flushed_end_time
+            && timestamp < end
+        {
+            return Err(Error::invalid(format!(
+                "timestamp {timestamp} precedes time {end} of an already written value-change block"
+            )));
+        }
 
         let canonical = self.resolve_canonical_handle(handle)?;
         let geom_index = (canonical - 1) as usize;
@@ -540,22 +1158,95 @@ impl<W: WriteSeek> FstWriter<W> {
             ))
         })?;
         let owned_value = Self::convert_value(value, geom_entry)?;
+        if self.options.reject_nonfinite_reals
+            && let Some(real) = owned_value.as_real()
+            && !real.is_finite()
+        {
+            return Err(Error::invalid(format!(
+                "non-finite real value {real} for handle {handle}"
+            )));
+        }
+
+        let limit_reached = self
+            .options
+            .max_pending_changes
+            .is_some_and(|limit| self.pending_changes.len() >= limit);
+        let memory_exceeded = self
+            .options
+            .max_block_memory
+            .is_some_and(|limit| !self.pending_changes.is_empty() && self.pending_memory >= limit);
+        if (limit_reached || memory_exceeded) && timestamp > self.pending_max_time {
+            self.flush_value_changes()?;
+        }
+
+        if self.options.max_block_memory.is_some() {
+            if self.pending_changes.is_empty() {
+                self.pending_memory = self.geometry.iter().map(frame_memory_estimate).sum();
+            }
+            self.pending_memory = self
+                .pending_memory
+                .saturating_add(owned_value.chain_memory_estimate());
+        }
 
+        if self.pending_changes.is_empty() || timestamp > self.pending_max_time {
+            self.pending_max_time = timestamp;
+        }
+        self.observed_times = Some(match self.observed_times {
+            Some((start, end)) => (start.min(timestamp), end.max(timestamp)),
+            None => (timestamp, timestamp),
+        });
         self.pending_changes.push(PendingChange {
             timestamp,
             handle: canonical,
-            value: owned_value.clone(),
+            value: owned_value,
         });
-        self.frame_state.update(canonical, &owned_value)?;
-        if let Some(children) = self.alias_children.get((canonical - 1) as usize) {
-            for &alias in children {
-                self.frame_state.update(alias, &owned_value)?;
-            }
+
+        Ok(())
+    }
+
+    /// Emits `cycles` periods of a clock on the single-bit `handle`: each period starting at
+    /// `start + k * period` drives `1`, then `0` after `half_duty` time units. The result is
+    /// identical to calling [`Self::emit_change`] for every edge.
+    pub fn emit_clock(
+        &mut self,
+        handle: u32,
+        start: u64,
+        period: u64,
+        half_duty: u64,
+        cycles: u64,
+    ) -> Result<()> {
+        let canonical = self.resolve_canonical_handle(handle)?;
+        if self.geometry.get((canonical - 1) as usize) != Some(&GeomEntry::Fixed(1)) {
+            return Err(Error::invalid(format!(
+                "clock handle {handle} must have Fixed(1) geometry"
+            )));
+        }
+        if half_duty == 0 || half_duty >= period {
+            return Err(Error::invalid(format!(
+                "clock high time {half_duty} must lie strictly between 0 and the period {period}"
+            )));
+        }
+        if let Some(last) = cycles.checked_sub(1) {
+            last.checked_mul(period)
+                .and_then(|offset| offset.checked_add(start))
+                .and_then(|rise| rise.checked_add(half_duty))
+                .ok_or_else(|| Error::invalid("clock timestamps overflow u64"))?;
         }
 
+        for cycle in 0..cycles {
+            let rise = start + cycle * period;
+            self.emit_change(rise, handle, SignalValue::Bit('1'))?;
+            self.emit_change(rise + half_duty, handle, SignalValue::Bit('0'))?;
+        }
         Ok(())
     }
 
+    /// Returns the size breakdown of the most recently flushed value-change block, or `None` if no
+    /// block has been written yet. Call [`Self::flush`] first to include the pending changes.
+    pub fn last_block_stats(&self) -> Option<BlockStats> {
+        self.last_block_stats
+    }
+
     /// Flushes any buffered data to the sink.
     pub fn flush(&mut self) -> Result<()> {
         self.flush_value_changes()?;
@@ -563,12 +1254,120 @@ impl<W: WriteSeek> FstWriter<W> {
         Ok(())
     }
 
+    /// Flushes encoded bytes to the sink and returns it, or `None` while the output is held for
+    /// the z-wrapper envelope.
+    #[cfg(feature = "async")]
+    pub(crate) fn flushed_sink_mut(&mut self) -> Result<Option<&mut W>> {
+        self.output.flush()?;
+        Ok(match &mut self.output {
+            OutputBackend::Direct(backend) => Some(backend.get_mut().get_mut()),
+            OutputBackend::Wrapped { .. } => None,
+        })
+    }
+
+    /// Flushes pending changes, then writes a `Skip` block holding `len` zero bytes. Readers
+    /// step over it, so it can pad the file to an alignment boundary or reserve space to patch
+    /// later.
+    pub fn write_skip(&mut self, len: u64) -> Result<()> {
+        if !self.header_written {
+            return Err(Error::invalid(
+                "skip blocks cannot be written before the header",
+            ));
+        }
+        let section_length = len
+            .checked_add(8)
+            .ok_or_else(|| Error::invalid("skip block length overflow"))?;
+        self.flush_value_changes()?;
+        self.output.write_all(&[BlockType::Skip as u8])?;
+        self.output.write_all(&section_length.to_be_bytes())?;
+        let zeros = [0u8; 4096];
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = remaining.min(zeros.len() as u64) as usize;
+            self.output.write_all(&zeros[..chunk])?;
+            remaining -= chunk as u64;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of value-change blocks written so far.
+    pub fn vc_section_count(&self) -> u64 {
+        self.vc_blocks_written
+    }
+
     /// Consumes the writer, returning the underlying sink once buffered data has been flushed.
+    /// The header's `start_time`/`end_time` are rewritten with the earliest and latest emitted
+    /// timestamps, and `vc_section_count` with [`Self::vc_section_count`], when the sink supports
+    /// seeking.
     pub fn finish(mut self) -> Result<W> {
+        self.write_deferred_header()?;
         self.flush()?;
+        if self.trailing_blackout {
+            // Readers keep the last blackout block they see, so re-emit the full event list.
+            self.write_blackout_block()?;
+        }
+        self.patch_header_times()?;
+        self.patch_vc_section_count()?;
         self.output.into_inner(&self.options)
     }
 
+    /// Like [`Self::finish`], but also returns the handles (aliases included) that never received
+    /// a value change, so callers can assert full coverage. Undriven handles keep the
+    /// [`WriterOptions::undriven_fill`] state (or NaN) in the frame.
+    pub fn finish_checked(mut self) -> Result<(W, Vec<u32>)> {
+        self.write_deferred_header()?;
+        self.flush()?;
+        let undriven = self.frame_state.undriven_handles(self.next_handle - 1);
+        Ok((self.finish()?, undriven))
+    }
+
+    /// Writes a default header when [`WriterOptions::deferred_header`] is set and the caller never
+    /// wrote one, so the buffered changes can be flushed.
+    fn write_deferred_header(&mut self) -> Result<()> {
+        if self.options.deferred_header && !self.header_written {
+            self.write_header(Header::default())?;
+        }
+        Ok(())
+    }
+
+    fn patch_header_times(&mut self) -> Result<()> {
+        let Some((start_time, end_time)) = self.observed_times else {
+            return Ok(());
+        };
+        let mut fields = [0u8; 16];
+        fields[..8].copy_from_slice(&start_time.to_be_bytes());
+        fields[8..].copy_from_slice(&end_time.to_be_bytes());
+        // The times follow the block tag and the 8-byte section length.
+        self.output.patch(self.header_offset + 9, &fields)?;
+        Ok(())
+    }
+
+    fn patch_vc_section_count(&mut self) -> Result<()> {
+        if !self.metadata_written {
+            return Ok(());
+        }
+        // Tag, section length, start/end time, endian marker, memory used, scope/var counts, and
+        // max handle precede the count.
+        let offset = self.header_offset + 1 + 8 * 8;
+        self.output
+            .patch(offset, &self.vc_blocks_written.to_be_bytes())?;
+        Ok(())
+    }
+
+    /// Records `name` as declared in `scope` with `handle`, failing on a repeat when
+    /// [`WriterOptions::forbid_duplicate_names`] is set.
+    fn claim_name(&mut self, scope: usize, name: &str, handle: u32) -> Result<()> {
+        let names = &mut self.scope_names[scope];
+        if self.options.forbid_duplicate_names && names.contains_key(name) {
+            return Err(Error::invalid(format!(
+                "variable name '{name}' is already declared in scope '{}'",
+                self.scopes[scope].name
+            )));
+        }
+        names.entry(name.to_owned()).or_default().push(handle);
+        Ok(())
+    }
+
     fn ensure_metadata_mutable(&self) -> Result<()> {
         if self.metadata_written {
             Err(Error::unsupported(
@@ -649,7 +1448,7 @@ impl<W: WriteSeek> FstWriter<W> {
                                 width_usize
                             )));
                         }
-                        let data = bytes.to_vec();
+                        let data = normalize_bit_states(bytes)?;
                         let packed = pack_ascii_bits(&data, *width);
                         Ok(OwnedValue::Vector {
                             width: *width,
@@ -666,10 +1465,11 @@ impl<W: WriteSeek> FstWriter<W> {
                                 width_usize
                             )));
                         }
-                        let packed = pack_ascii_bits(&owned, *width);
+                        let data = normalize_bit_states(&owned)?;
+                        let packed = pack_ascii_bits(&data, *width);
                         Ok(OwnedValue::Vector {
                             width: *width,
-                            data: owned,
+                            data,
                             packed,
                         })
                     }
@@ -705,12 +1505,24 @@ impl<W: WriteSeek> FstWriter<W> {
                     }
                     let mut raw = [0u8; 8];
                     raw.copy_from_slice(&owned);
-                    let value = if cfg!(target_endian = "little") {
-                        f64::from_le_bytes(raw)
-                    } else {
-                        f64::from_be_bytes(raw)
-                    };
-                    Ok(OwnedValue::Real(value))
+                    Ok(OwnedValue::Real(Endianness::native().f64_from_bytes(raw)))
+                }
+                _ => Err(Error::unsupported(
+                    "value type is not compatible with real-valued geometry",
+                )),
+            },
+            GeomEntry::ShortReal => match value {
+                SignalValue::Real(real) => Ok(OwnedValue::ShortReal(real as f32)),
+                SignalValue::Bytes(bytes) => {
+                    let raw: [u8; 4] = bytes.as_ref().try_into().map_err(|_| {
+                        Error::invalid(format!(
+                            "shortreal signal expects 4 bytes, received {}",
+                            bytes.len()
+                        ))
+                    })?;
+                    Ok(OwnedValue::ShortReal(
+                        Endianness::native().f32_from_bytes(raw),
+                    ))
                 }
                 _ => Err(Error::unsupported(
                     "value type is not compatible with real-valued geometry",
@@ -719,19 +1531,25 @@ impl<W: WriteSeek> FstWriter<W> {
             GeomEntry::Variable => match value {
                 SignalValue::Bytes(bytes) => Ok(OwnedValue::VarLen(bytes.into_owned())),
                 SignalValue::Vector(text) => Ok(OwnedValue::VarLen(text.into_owned().into_bytes())),
-                SignalValue::Bit(bit) => Ok(OwnedValue::VarLen(vec![bit as u8])),
                 _ => Err(Error::unsupported(
-                    "value type is not compatible with variable-length geometry",
+                    "variable-length geometry only accepts `Bytes` or `Vector` payloads",
                 )),
             },
         }
     }
 
+    /// Frame byte for undriven `Fixed` signals; `validate_options` has already checked the state.
+    fn undriven_fill_byte(&self) -> u8 {
+        self.options.undriven_fill.to_ascii_lowercase() as u8
+    }
+
     fn flush_value_changes(&mut self) -> Result<()> {
-        if self.pending_changes.is_empty() {
+        // With a deferred header, changes stay buffered until the metadata has been written.
+        if self.pending_changes.is_empty() || !self.header_written {
             return Ok(());
         }
         let changes = std::mem::take(&mut self.pending_changes);
+        self.flushed_end_time = Some(self.pending_max_time);
         let payload = self.build_vc_block(changes)?;
         let section_length = (payload.len() as u64)
             .checked_add(8)
@@ -754,6 +1572,8 @@ impl<W: WriteSeek> FstWriter<W> {
         }
 
         changes.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.handle.cmp(&b.handle)));
+        let changes = self.resolve_duplicates(changes)?;
+        self.apply_frame_updates(&changes)?;
 
         let max_handle = self.next_handle.saturating_sub(1);
         if max_handle == 0 {
@@ -779,9 +1599,15 @@ impl<W: WriteSeek> FstWriter<W> {
             time_index.insert(*ts, idx);
         }
 
-        let frame_bytes = self
-            .frame_state
-            .build_frame_bytes(&self.geometry, max_handle)?;
+        let frame_bytes = if self.options.emit_frame {
+            self.frame_state.build_frame_bytes(
+                &self.geometry,
+                max_handle,
+                self.undriven_fill_byte(),
+            )?
+        } else {
+            Vec::new()
+        };
         let frame_encoding = encode_frame_section(frame_bytes, self.options.compression_level)?;
         let frame_max_handle = if frame_encoding.uncompressed_len > 0 {
             max_handle as u64
@@ -790,6 +1616,7 @@ impl<W: WriteSeek> FstWriter<W> {
         };
 
         let mut required_memory = frame_encoding.uncompressed_len;
+        let mut chain_bytes_raw = 0u64;
 
         let mut per_handle: Vec<Vec<(usize, OwnedValue)>> = vec![Vec::new(); max_handle as usize];
         for change in &changes {
@@ -799,10 +1626,7 @@ impl<W: WriteSeek> FstWriter<W> {
             per_handle[(change.handle - 1) as usize].push((idx, change.value.clone()));
         }
 
-        let pack_type = self.chain_pack_type();
-
-        let mut chain_buffer = Vec::new();
-        let mut chain_offsets: Vec<Option<u64>> = vec![None; max_handle as usize];
+        let mut jobs: Vec<(usize, Vec<u8>)> = Vec::new();
 
         for (handle_idx, events) in per_handle.iter().enumerate() {
             if events.is_empty() {
@@ -859,12 +1683,14 @@ impl<W: WriteSeek> FstWriter<W> {
                             .map_err(|_| Error::invalid("time delta exceeds u64 range"))?;
                         let marker = (delta_u64 << 1) | 1;
                         encode_varint(marker, &mut chain_bytes);
-                        let bytes = if cfg!(target_endian = "little") {
-                            value.to_le_bytes()
-                        } else {
-                            value.to_be_bytes()
-                        };
-                        chain_bytes.extend_from_slice(&bytes);
+                        chain_bytes.extend_from_slice(&Endianness::native().f64_to_bytes(*value));
+                    }
+                    OwnedValue::ShortReal(value) => {
+                        let delta_u64 = u64::try_from(delta)
+                            .map_err(|_| Error::invalid("time delta exceeds u64 range"))?;
+                        let marker = (delta_u64 << 1) | 1;
+                        encode_varint(marker, &mut chain_bytes);
+                        chain_bytes.extend_from_slice(&Endianness::native().f32_to_bytes(*value));
                     }
                     OwnedValue::VarLen(bytes) => {
                         let delta_u64 = u64::try_from(delta)
@@ -885,15 +1711,31 @@ impl<W: WriteSeek> FstWriter<W> {
             required_memory = required_memory
                 .checked_add(raw_len)
                 .ok_or_else(|| Error::invalid("chain memory requirement overflow"))?;
+            chain_bytes_raw += raw_len;
 
-            let (stored_len, payload_bytes) =
-                encode_chain_payload(pack_type, chain_bytes, self.options.compression_level)?;
+            jobs.push((handle_idx, chain_bytes));
+        }
 
-            let offset = chain_buffer.len() as u64;
-            encode_varint(stored_len, &mut chain_buffer);
-            chain_buffer.extend_from_slice(&payload_bytes);
-            chain_offsets[handle_idx] = Some(offset);
+        let handle_count = jobs.len() as u64;
+        let candidates = self.chain_pack_candidates();
+        let mut best: Option<(PackType, Vec<u8>, Vec<Option<u64>>)> = None;
+        for (position, candidate) in candidates.iter().enumerate() {
+            let candidate_jobs = if position + 1 == candidates.len() {
+                std::mem::take(&mut jobs)
+            } else {
+                jobs.clone()
+            };
+            let (buffer, offsets) =
+                self.assemble_chain_buffer(*candidate, candidate_jobs, max_handle as usize)?;
+            if best
+                .as_ref()
+                .is_none_or(|(_, smallest, _)| buffer.len() < smallest.len())
+            {
+                best = Some((*candidate, buffer, offsets));
+            }
         }
+        let (pack_type, chain_buffer, chain_offsets) =
+            best.ok_or_else(|| Error::invalid("no chain compression candidates available"))?;
 
         let mut index_entries = Vec::with_capacity(chain_offsets.len());
         for (handle_idx, offset) in chain_offsets.iter().enumerate() {
@@ -914,6 +1756,7 @@ impl<W: WriteSeek> FstWriter<W> {
         let mut time_data = Vec::with_capacity(time_points.len() * 2);
         let mut prev_time = 0u64;
         for (idx, ts) in time_points.iter().enumerate() {
+            // The first entry is stored relative to zero, so a change at t=0 encodes a zero delta.
             let delta = if idx == 0 {
                 *ts
             } else {
@@ -930,14 +1773,30 @@ impl<W: WriteSeek> FstWriter<W> {
         let time_encoding = encode_time_section(
             time_data,
             time_item_count,
-            matches!(self.options.time_compression, TimeCompression::Zlib),
+            match self.options.time_compression {
+                TimeCompression::Raw => PackType::None,
+                TimeCompression::Zlib => PackType::Zlib,
+                TimeCompression::Lz4 => PackType::Lz4,
+            },
             self.options.compression_level,
         )?;
 
         let begin_time = *time_points.first().unwrap();
         let end_time = *time_points.last().unwrap();
 
-        let mut payload = Vec::new();
+        let payload_len = 24
+            + varint_len(frame_encoding.uncompressed_len)
+            + varint_len(frame_encoding.compressed_len)
+            + varint_len(frame_max_handle)
+            + frame_encoding.payload.len()
+            + varint_len(max_handle as u64)
+            + 1
+            + chain_buffer.len()
+            + index_bytes.len()
+            + 8
+            + time_encoding.payload.len()
+            + 24;
+        let mut payload = Vec::with_capacity(payload_len);
         payload.extend_from_slice(&begin_time.to_be_bytes());
         payload.extend_from_slice(&end_time.to_be_bytes());
         payload.extend_from_slice(&required_memory.to_be_bytes());
@@ -956,44 +1815,76 @@ impl<W: WriteSeek> FstWriter<W> {
         payload.extend_from_slice(&time_encoding.compressed_len.to_be_bytes());
         payload.extend_from_slice(&time_encoding.item_count.to_be_bytes());
 
+        self.last_block_stats = Some(BlockStats {
+            chain_bytes_raw,
+            chain_bytes_stored: chain_buffer.len() as u64,
+            frame_uncompressed_len: frame_encoding.uncompressed_len,
+            frame_compressed_len: frame_encoding.compressed_len,
+            time_uncompressed_len: time_encoding.uncompressed_len,
+            time_compressed_len: time_encoding.compressed_len,
+            handle_count,
+        });
+
         Ok(payload)
     }
 
+    /// Collapses repeated `(timestamp, handle)` pairs according to the configured policy. The
+    /// input must already be sorted with a stable sort so emission order is preserved per pair.
+    fn resolve_duplicates(&self, changes: Vec<PendingChange>) -> Result<Vec<PendingChange>> {
+        let mut resolved: Vec<PendingChange> = Vec::with_capacity(changes.len());
+        for change in changes {
+            match resolved.last_mut() {
+                Some(prev)
+                    if prev.timestamp == change.timestamp && prev.handle == change.handle =>
+                {
+                    match self.options.duplicate_changes {
+                        DuplicateChangePolicy::LastWins => *prev = change,
+                        DuplicateChangePolicy::Reject => {
+                            return Err(Error::invalid(format!(
+                                "handle {} received multiple changes at timestamp {}",
+                                change.handle, change.timestamp
+                            )));
+                        }
+                    }
+                }
+                _ => resolved.push(change),
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Folds time-ordered changes into the frame state so it reflects each handle's value at the
+    /// end of the block, regardless of the order in which changes were emitted.
+    fn apply_frame_updates(&mut self, changes: &[PendingChange]) -> Result<()> {
+        for change in changes {
+            self.frame_state.update(change.handle, &change.value)?;
+            if let Some(children) = self.alias_children.get((change.handle - 1) as usize) {
+                for &alias in children {
+                    self.frame_state.update(alias, &change.value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn write_header_block(&mut self, header: &Header) -> Result<()> {
-        self.output.write_all(&[BlockType::Header as u8])?;
-        self.output
-            .write_all(&header.section_length.to_be_bytes())?;
-        self.output.write_all(&header.start_time.to_be_bytes())?;
-        self.output.write_all(&header.end_time.to_be_bytes())?;
-        self.output
-            .write_all(&std::f64::consts::E.to_bits().to_be_bytes())?;
-        self.output.write_all(&header.memory_used.to_be_bytes())?;
-        self.output.write_all(&header.scope_count.to_be_bytes())?;
-        self.output.write_all(&header.var_count.to_be_bytes())?;
-        self.output.write_all(&header.max_handle.to_be_bytes())?;
-        self.output
-            .write_all(&header.vc_section_count.to_be_bytes())?;
-        self.output.write_all(&[header.timescale_exponent as u8])?;
-
-        let mut version = [0u8; crate::block::VERSION_FIELD_LEN];
-        let mut date = [0u8; crate::block::DATE_FIELD_LEN];
-        write_cstring(&mut version, &header.version);
-        write_cstring(&mut date, &header.date);
-        self.output.write_all(&version)?;
-        self.output.write_all(&date)?;
-
-        self.output.write_all(&[header.file_type])?;
-        self.output.write_all(&header.time_zero.to_be_bytes())?;
+        self.output.with_writer(|writer| header.write_to(writer))?;
         self.output.flush()?;
         Ok(())
     }
 
     fn write_geometry_block(&mut self, compress: bool) -> Result<()> {
+        // Aliases own a handle, so they also get an entry (a copy of their target's). The frame
+        // and chain index are sized by `max_handle`, and readers index all three by handle.
         let geom = GeomInfo {
             max_handle: self.geometry.len() as u64,
             entries: self.geometry.clone(),
         };
-        let encoded = geom.encode_block(compress)?;
+        let encoded = if self.options.geometry_rle {
+            geom.encode_block_rle(compress, self.options.compression_level)?
+        } else {
+            geom.encode_block(compress, self.options.compression_level)?
+        };
         self.output.write_all(&[BlockType::Geometry as u8])?;
         self.output.with_writer(|writer| encoded.write_to(writer))?;
         Ok(())
@@ -1004,29 +1895,106 @@ impl<W: WriteSeek> FstWriter<W> {
             items: self.hierarchy_items.clone(),
             scopes: self.scopes.clone(),
             variables: self.variables.clone(),
-            attributes: Vec::new(),
+            attributes: self.attributes.clone(),
         };
-        let encoded = block.encode_block(HierarchyCompression::Raw)?;
+        let encoded = block.encode_block(self.options.hierarchy_compression)?;
         self.output.write_all(&[encoded.block_type as u8])?;
         self.output.with_writer(|writer| encoded.write_to(writer))?;
         Ok(())
     }
 
-    fn chain_pack_type(&self) -> PackType {
-        match self.options.chain_compression {
+    fn write_blackout_block(&mut self) -> Result<()> {
+        if self.blackout_events.is_empty() {
+            return Ok(());
+        }
+        let block = BlackoutBlock {
+            events: self.blackout_events.clone(),
+        };
+        let mut payload = Vec::new();
+        block.encode(&mut payload);
+        let section_length = (payload.len() as u64)
+            .checked_add(8)
+            .ok_or_else(|| Error::invalid("blackout block length overflow"))?;
+        self.output.write_all(&[BlockType::Blackout as u8])?;
+        self.output.write_all(&section_length.to_be_bytes())?;
+        self.output.write_all(&payload)?;
+        Ok(())
+    }
+
+    /// Returns the pack types to try for a block's chains; the smallest result is kept.
+    fn chain_pack_candidates(&self) -> Vec<PackType> {
+        let single = match self.options.chain_compression {
             ChainCompression::Raw => PackType::None,
             ChainCompression::Zlib => PackType::Zlib,
             ChainCompression::Lz4 => PackType::Lz4,
             ChainCompression::FastLz => PackType::FastLz,
-        }
+            ChainCompression::Zstd => PackType::Zstd,
+            ChainCompression::Custom => PackType::Custom(
+                self.options
+                    .custom_chain_compressor
+                    .as_ref()
+                    .map(CustomCompressor::marker)
+                    .unwrap_or_default(),
+            ),
+            ChainCompression::Auto => {
+                return [
+                    (true, PackType::None),
+                    (cfg!(feature = "gzip"), PackType::Zlib),
+                    (cfg!(feature = "lz4"), PackType::Lz4),
+                    (cfg!(feature = "fastlz"), PackType::FastLz),
+                ]
+                .into_iter()
+                .filter_map(|(available, pack_type)| available.then_some(pack_type))
+                .collect();
+            }
+        };
+        vec![single]
     }
-}
 
-fn write_cstring(buf: &mut [u8], value: &str) {
-    let bytes = value.as_bytes();
-    let len = bytes.len().min(buf.len().saturating_sub(1));
-    buf[..len].copy_from_slice(&bytes[..len]);
-    buf[len] = 0;
+    /// Compresses every `(handle index, chain)` job with `pack_type` and lays the results out as
+    /// a chain buffer, returning it with each handle's offset into it.
+    fn assemble_chain_buffer(
+        &self,
+        pack_type: PackType,
+        jobs: Vec<(usize, Vec<u8>)>,
+        max_handle: usize,
+    ) -> Result<(Vec<u8>, Vec<Option<u64>>)> {
+        let options = &self.options;
+        let compress =
+            |(handle_idx, chain_bytes): (usize, Vec<u8>)| -> Result<(usize, u64, Vec<u8>)> {
+                let (stored_len, payload_bytes) = match &options.custom_chain_compressor {
+                    Some(custom) if matches!(pack_type, PackType::Custom(_)) => {
+                        encode_chain_payload_with(custom.create().as_mut(), chain_bytes)?
+                    }
+                    _ => encode_chain_payload(pack_type, chain_bytes, options.compression_level)?,
+                };
+                Ok((handle_idx, stored_len, payload_bytes))
+            };
+
+        #[cfg(feature = "parallel")]
+        let encoded: Vec<(usize, u64, Vec<u8>)> = if jobs.len() > 1 {
+            jobs.into_par_iter()
+                .map(compress)
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            jobs.into_iter().map(compress).collect::<Result<_>>()?
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let encoded: Vec<(usize, u64, Vec<u8>)> =
+            jobs.into_iter().map(compress).collect::<Result<_>>()?;
+
+        let mut chain_buffer = Vec::new();
+        let mut chain_offsets: Vec<Option<u64>> = vec![None; max_handle];
+        for (handle_idx, stored_len, payload_bytes) in encoded {
+            let offset = chain_buffer.len() as u64;
+            chain_buffer.reserve(varint_len(stored_len) + payload_bytes.len());
+            encode_varint(stored_len, &mut chain_buffer);
+            chain_buffer.extend_from_slice(&payload_bytes);
+            chain_offsets[handle_idx] = Some(offset);
+        }
+        Ok((chain_buffer, chain_offsets))
+    }
 }
 
 /// Identifier returned when opening a scope.
@@ -1035,6 +2003,25 @@ pub struct ScopeId(pub usize);
 
 const SPECIAL_BIT_CHARS: [u8; 8] = *b"xzhuwl-?";
 
+/// Lowercases a fixed-width vector and checks every character is an FST bit state
+/// (`0 1 x z h u w l - ?`), so nine-value `std_logic` buses round-trip unchanged.
+fn normalize_bit_states(bytes: &[u8]) -> Result<Vec<u8>> {
+    bytes
+        .iter()
+        .map(|byte| {
+            let lower = byte.to_ascii_lowercase();
+            if lower == b'0' || lower == b'1' || SPECIAL_BIT_CHARS.contains(&lower) {
+                Ok(lower)
+            } else {
+                Err(Error::invalid(format!(
+                    "'{}' is not a valid bit state for a fixed-width vector",
+                    byte.escape_ascii()
+                )))
+            }
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, Copy)]
 enum BitValue {
     Zero,
@@ -1092,25 +2079,27 @@ struct PendingChange {
 #[derive(Debug, Default)]
 struct FrameState {
     entries: Vec<Option<FrameValue>>,
+    /// Whether each handle has received at least one value change.
+    driven: Vec<bool>,
 }
 
 impl FrameState {
-    fn register_handle(&mut self, handle: u32, geom: &GeomEntry) {
+    fn register_handle(&mut self, handle: u32, geom: &GeomEntry, fill: u8) {
         let idx = handle as usize;
         if self.entries.len() < idx {
             self.entries.resize(idx, None);
         }
         match geom {
             GeomEntry::Fixed(1) => {
-                // default uninitialised bit left as None; frame builder will emit 'x'.
+                // default uninitialised bit left as None; frame builder will emit `fill`.
             }
             GeomEntry::Fixed(len) => {
                 let len_usize = *len as usize;
                 if let Some(slot) = self.entries.get_mut(idx - 1) {
-                    slot.get_or_insert_with(|| FrameValue::Vector(vec![b'x'; len_usize]));
+                    slot.get_or_insert_with(|| FrameValue::Vector(vec![fill; len_usize]));
                 }
             }
-            GeomEntry::Real => {
+            GeomEntry::Real | GeomEntry::ShortReal => {
                 if let Some(slot) = self.entries.get_mut(idx - 1) {
                     slot.get_or_insert_with(|| FrameValue::Real(f64::NAN));
                 }
@@ -1126,6 +2115,10 @@ impl FrameState {
         if self.entries.len() < idx {
             self.entries.resize(idx, None);
         }
+        if self.driven.len() < idx {
+            self.driven.resize(idx, false);
+        }
+        self.driven[idx - 1] = true;
         let slot = &mut self.entries[idx - 1];
         match value {
             OwnedValue::Bit(bit) => {
@@ -1137,6 +2130,9 @@ impl FrameState {
             OwnedValue::Real(val) => {
                 *slot = Some(FrameValue::Real(*val));
             }
+            OwnedValue::ShortReal(val) => {
+                *slot = Some(FrameValue::Real(f64::from(*val)));
+            }
             OwnedValue::VarLen(_) => {
                 // Variable-length signals do not participate in the initial frame.
             }
@@ -1144,7 +2140,12 @@ impl FrameState {
         Ok(())
     }
 
-    fn build_frame_bytes(&self, geometry: &[GeomEntry], max_handle: u32) -> Result<Vec<u8>> {
+    fn build_frame_bytes(
+        &self,
+        geometry: &[GeomEntry],
+        max_handle: u32,
+        fill: u8,
+    ) -> Result<Vec<u8>> {
         if max_handle == 0 {
             return Ok(Vec::new());
         }
@@ -1161,7 +2162,7 @@ impl FrameState {
                         .get(idx)
                         .and_then(|opt| opt.as_ref())
                         .map(FrameValue::as_bit_char)
-                        .unwrap_or('x');
+                        .unwrap_or(fill as char);
                     buf.push(ch as u8);
                 }
                 GeomEntry::Fixed(len) => {
@@ -1176,11 +2177,11 @@ impl FrameState {
                             buf.extend_from_slice(data);
                         } else {
                             let fill_start = buf.len();
-                            buf.resize(fill_start + len_usize, b'x');
+                            buf.resize(fill_start + len_usize, fill);
                         }
                     } else {
                         let fill_start = buf.len();
-                        buf.resize(fill_start + len_usize, b'x');
+                        buf.resize(fill_start + len_usize, fill);
                     }
                 }
                 GeomEntry::Real => {
@@ -1190,12 +2191,16 @@ impl FrameState {
                         .and_then(|opt| opt.as_ref())
                         .and_then(FrameValue::as_real)
                         .unwrap_or(f64::NAN);
-                    let bytes = if cfg!(target_endian = "little") {
-                        value.to_le_bytes()
-                    } else {
-                        value.to_be_bytes()
-                    };
-                    buf.extend_from_slice(&bytes);
+                    buf.extend_from_slice(&Endianness::native().f64_to_bytes(value));
+                }
+                GeomEntry::ShortReal => {
+                    let value = self
+                        .entries
+                        .get(idx)
+                        .and_then(|opt| opt.as_ref())
+                        .and_then(FrameValue::as_real)
+                        .unwrap_or(f64::NAN);
+                    buf.extend_from_slice(&Endianness::native().f32_to_bytes(value as f32));
                 }
                 GeomEntry::Variable => {
                     // Variable-length signals have no fixed frame contribution.
@@ -1216,8 +2221,26 @@ impl FrameState {
         }
         let value = self.entries.get(src_idx).cloned().unwrap_or(None);
         self.entries[dst_idx] = value;
+        if self.driven.get(src_idx).copied().unwrap_or(false) {
+            self.driven
+                .resize(self.driven.len().max(dst_idx + 1), false);
+            self.driven[dst_idx] = true;
+        }
         Ok(())
     }
+
+    /// Returns the handles in `1..=max_handle` that never received a value change.
+    fn undriven_handles(&self, max_handle: u32) -> Vec<u32> {
+        (1..=max_handle)
+            .filter(|handle| {
+                !self
+                    .driven
+                    .get(*handle as usize - 1)
+                    .copied()
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1260,37 +2283,42 @@ enum OwnedValue {
         packed: Option<Vec<u8>>,
     },
     Real(f64),
+    ShortReal(f32),
     VarLen(Vec<u8>),
 }
 
-fn pack_ascii_bits(data: &[u8], width: u32) -> Option<Vec<u8>> {
-    let len = packed_len(width);
+impl OwnedValue {
+    /// Approximate number of uncompressed chain bytes this change contributes to its block,
+    /// assuming a one-byte time-delta prefix.
+    fn chain_memory_estimate(&self) -> u64 {
+        let payload = match self {
+            OwnedValue::Bit(_) => 0,
+            OwnedValue::Vector { data, packed, .. } => packed.as_ref().unwrap_or(data).len(),
+            OwnedValue::Real(_) => 8,
+            OwnedValue::ShortReal(_) => 4,
+            OwnedValue::VarLen(bytes) => bytes.len() + 1,
+        };
+        payload as u64 + 1
+    }
 
-    #[cfg(feature = "simd")]
-    {
-        if let Some(result) = crate::simd::pack_ascii_bits(data, width, len) {
-            return Some(result);
+    /// Returns the value of a real change, widened to `f64` for shortreals.
+    fn as_real(&self) -> Option<f64> {
+        match self {
+            OwnedValue::Real(value) => Some(*value),
+            OwnedValue::ShortReal(value) => Some(f64::from(*value)),
+            _ => None,
         }
     }
-
-    pack_ascii_bits_scalar(data, len)
 }
 
-fn pack_ascii_bits_scalar(data: &[u8], len: usize) -> Option<Vec<u8>> {
-    if data.iter().any(|b| *b != b'0' && *b != b'1') {
-        return None;
-    }
-    let mut out = vec![0u8; len];
-    for (idx, byte) in data.iter().enumerate() {
-        if *byte == b'1' {
-            let byte_index = idx / 8;
-            let bit_index = 7 - (idx % 8);
-            if let Some(slot) = out.get_mut(byte_index) {
-                *slot |= 1 << bit_index;
-            }
-        }
+/// Bytes a signal occupies in the uncompressed frame section.
+fn frame_memory_estimate(entry: &GeomEntry) -> u64 {
+    match entry {
+        GeomEntry::Fixed(width) => u64::from(*width),
+        GeomEntry::Real => 8,
+        GeomEntry::ShortReal => 4,
+        GeomEntry::Variable => 0,
     }
-    Some(out)
 }
 
 fn normalize_packed_bits(width: u32, bits: &[u8]) -> Result<Vec<u8>> {
@@ -1317,22 +2345,13 @@ fn normalize_packed_bits(width: u32, bits: &[u8]) -> Result<Vec<u8>> {
 }
 
 fn unpack_packed_bits(width: u32, bits: &[u8]) -> Result<Vec<u8>> {
-    let len = packed_len(width);
-    if bits.len() < len {
+    if bits.len() < packed_len(width) {
         return Err(Error::invalid(
             "packed bit payload shorter than required length",
         ));
     }
-    let width_usize = usize::try_from(width)
-        .map_err(|_| Error::invalid("vector width exceeds addressable range"))?;
-    let mut out = Vec::with_capacity(width_usize);
-    for idx in 0..width_usize {
-        let byte = bits[idx / 8];
-        let bit_index = 7 - (idx % 8);
-        let bit = (byte >> bit_index) & 1;
-        out.push(if bit == 1 { b'1' } else { b'0' });
-    }
-    Ok(out)
+    unpack_to_ascii(bits, width)
+        .ok_or_else(|| Error::invalid("vector width exceeds addressable range"))
 }
 
 fn packed_len(width: u32) -> usize {
@@ -1,12 +1,20 @@
 #![cfg(feature = "async")]
 
-use std::path::PathBuf;
+use std::fs::File;
+use std::future::Future;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Waker};
+use std::thread::{self, ThreadId};
 
 use anyhow::Result;
 use tempfile::tempdir;
 use tokio::runtime::Runtime;
 use wavefst::{
-    ChainCompression, GeomEntry, Header, ScopeType, SignalValue, VarDir, VarType,
+    ChainCompression, FstWriter, GeomEntry, Header, ReaderOptions, ScopeType, SignalValue,
+    TimeCompression, VarDir, VarType,
     async_support::{AsyncReader, AsyncWriter},
 };
 
@@ -19,7 +27,7 @@ fn async_reader_loads_fixture() -> Result<()> {
     let rt = Runtime::new()?;
     rt.block_on(async {
         let reader = AsyncReader::from_file(fixture_path()).await?;
-        assert!(reader.reader().header().vc_section_count > 0);
+        assert!(reader.reader().await.header().vc_section_count > 0);
         Result::<()>::Ok(())
     })?;
     Ok(())
@@ -62,7 +70,7 @@ fn async_writer_round_trip() -> Result<()> {
         assert!(!produced.is_empty(), "async writer should produce bytes");
 
         // Ensure the generated trace can be parsed by the async reader.
-        let mut reader = AsyncReader::from_file(&path).await?;
+        let mut reader = AsyncReader::from_file(&path).await?.into_reader()?;
         let mut changes = reader
             .next_value_changes()?
             .expect("expected value change block");
@@ -85,3 +93,218 @@ fn async_writer_round_trip() -> Result<()> {
     })?;
     Ok(())
 }
+
+#[test]
+fn async_writer_streams_blocks_with_flush_async() -> Result<()> {
+    let rt = Runtime::new()?;
+    rt.block_on(async {
+        let dir = tempdir()?;
+        let path = dir.path().join("async-streamed.fst");
+        let sink = tokio::fs::File::create(&path).await?;
+
+        let mut writer = AsyncWriter::builder(sink).build()?;
+        writer.begin_scope(ScopeType::VcdModule, "tb", None)?;
+        let bus = writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            "bus",
+            GeomEntry::Fixed(8),
+        )?;
+        writer.end_scope()?;
+        writer.write_header(Header {
+            version: "async-streamed".into(),
+            end_time: 990,
+            vc_section_count: 10,
+            ..Header::default()
+        })?;
+
+        let mut expected = Vec::new();
+        let mut on_disk = 0;
+        for block in 0..10u64 {
+            for step in 0..10u64 {
+                let time = block * 100 + step * 10;
+                writer.emit_change(time, bus, SignalValue::from_u64(time % 256, 8))?;
+                expected.push((time, Some(time % 256)));
+            }
+            writer.flush()?;
+            writer.flush_async().await?;
+            let len = tokio::fs::metadata(&path).await?.len();
+            assert!(len > on_disk, "each flush_async should reach the file");
+            on_disk = len;
+        }
+        writer.finish().await?;
+
+        let mut reader = AsyncReader::from_file(&path).await?.into_reader()?;
+        let mut changes = Vec::new();
+        while let Some(block) = reader.next_value_changes()? {
+            for change in block {
+                let change = change?;
+                changes.push((change.timestamp, change.value.as_u64()));
+            }
+        }
+        assert_eq!(changes, expected);
+        Result::<()>::Ok(())
+    })?;
+    Ok(())
+}
+
+/// File wrapper that counts how many bytes the reader pulls.
+struct CountingFile {
+    file: File,
+    read: Arc<AtomicU64>,
+}
+
+impl Read for CountingFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.file.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+impl Seek for CountingFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+fn write_large_trace(path: &Path) -> Result<()> {
+    let mut writer = FstWriter::builder(File::create(path)?)
+        .chain_compression(ChainCompression::Raw)
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "tb", None)?;
+    let handles = (0..16)
+        .map(|idx| {
+            writer.add_variable(
+                VarType::VcdWire,
+                VarDir::Implicit,
+                format!("bus{idx}"),
+                GeomEntry::Fixed(64),
+            )
+        })
+        .collect::<wavefst::Result<Vec<_>>>()?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    for block in 0..32u64 {
+        for step in 0..500u64 {
+            let time = block * 1000 + step;
+            for (idx, handle) in handles.iter().enumerate() {
+                let value = time.wrapping_mul(idx as u64 + 1);
+                writer.emit_change(time, *handle, SignalValue::from_u64(value, 64))?;
+            }
+        }
+        writer.flush()?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+#[test]
+fn lazy_async_reader_fetches_single_blocks() -> Result<()> {
+    let rt = Runtime::new()?;
+    rt.block_on(async {
+        let dir = tempdir()?;
+        let path = dir.path().join("large.fst");
+        write_large_trace(&path)?;
+        let file_len = std::fs::metadata(&path)?.len();
+
+        let read = Arc::new(AtomicU64::new(0));
+        let source = CountingFile {
+            file: File::open(&path)?,
+            read: Arc::clone(&read),
+        };
+        let reader = AsyncReader::from_seekable(source, ReaderOptions::default()).await?;
+        let index = reader.block_index().await?;
+        assert_eq!(index.len(), 32);
+
+        let before = read.load(Ordering::Relaxed);
+        let block = reader.read_block_at(index[20].offset).await?;
+        assert_eq!(block.header.begin_time, 20_000);
+        assert_eq!(block.time_table.timestamps.len(), 500);
+        let fetched = read.load(Ordering::Relaxed) - before;
+        assert!(
+            fetched < file_len / 8,
+            "fetched {fetched} of {file_len} bytes for one block"
+        );
+        assert!(read.load(Ordering::Relaxed) < file_len / 2);
+
+        let lazy = AsyncReader::from_file_lazy(&path).await?;
+        let block = lazy.read_block_at(index[31].offset).await?;
+        assert_eq!(block.header.end_time, 31_499);
+        Result::<()>::Ok(())
+    })?;
+    Ok(())
+}
+
+struct ThreadRecordingFile {
+    file: File,
+    threads: Arc<Mutex<Vec<ThreadId>>>,
+}
+
+impl Read for ThreadRecordingFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.threads.lock().unwrap().push(thread::current().id());
+        self.file.read(buf)
+    }
+}
+
+impl Seek for ThreadRecordingFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+#[test]
+fn lazy_async_reader_reads_blocks_off_the_calling_thread() -> Result<()> {
+    let rt = tokio::runtime::Builder::new_current_thread().build()?;
+    rt.block_on(async {
+        let dir = tempdir()?;
+        let path = dir.path().join("large.fst");
+        write_large_trace(&path)?;
+
+        let threads = Arc::new(Mutex::new(Vec::new()));
+        let source = ThreadRecordingFile {
+            file: File::open(&path)?,
+            threads: Arc::clone(&threads),
+        };
+        let reader = AsyncReader::from_seekable(source, ReaderOptions::default()).await?;
+        threads.lock().unwrap().clear();
+
+        let index = reader.block_index().await?;
+        let block = reader.read_block_at(index[3].offset).await?;
+        assert_eq!(block.header.begin_time, 3_000);
+
+        let caller = thread::current().id();
+        let threads = threads.lock().unwrap();
+        assert!(!threads.is_empty());
+        assert!(threads.iter().all(|thread| *thread != caller));
+        Result::<()>::Ok(())
+    })?;
+    Ok(())
+}
+
+#[test]
+fn cancelled_block_read_keeps_the_reader() -> Result<()> {
+    let rt = Runtime::new()?;
+    rt.block_on(async {
+        let dir = tempdir()?;
+        let path = dir.path().join("large.fst");
+        write_large_trace(&path)?;
+
+        let reader = AsyncReader::from_file_lazy(&path).await?;
+        {
+            let mut pending = Box::pin(reader.block_index());
+            let mut cx = Context::from_waker(Waker::noop());
+            let _ = pending.as_mut().poll(&mut cx);
+        }
+
+        let index = reader.block_index().await?;
+        assert_eq!(index.len(), 32);
+        let block = reader.read_block_at(index[5].offset).await?;
+        assert_eq!(block.header.begin_time, 5_000);
+        assert!(reader.reader().await.header().vc_section_count > 0);
+        Result::<()>::Ok(())
+    })?;
+    Ok(())
+}
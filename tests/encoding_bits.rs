@@ -0,0 +1,41 @@
+use std::borrow::Cow;
+
+use wavefst::SignalValue;
+use wavefst::encoding::{pack_bits, unpack_bits};
+
+/// `1011001110001`, 13 bits wide.
+const BITS: [bool; 13] = [
+    true, false, true, true, false, false, true, true, true, false, false, false, true,
+];
+
+#[test]
+fn msb_first_packing_at_width_13() {
+    let packed = pack_bits(&BITS, true);
+    assert_eq!(packed, [0b1011_0011, 0b1000_1000]);
+    assert_eq!(unpack_bits(&packed, 13, true), BITS);
+
+    // The msb-first layout is what `PackedBits` payloads use.
+    let value = SignalValue::PackedBits {
+        width: 13,
+        bits: Cow::Owned(packed),
+    };
+    assert_eq!(value.to_ascii_vector().as_deref(), Some("1011001110001"));
+    assert_eq!(
+        SignalValue::Vector(Cow::Borrowed("1011001110001")).to_packed(),
+        Some((13, pack_bits(&BITS, true)))
+    );
+}
+
+#[test]
+fn lsb_first_packing_at_width_13() {
+    let packed = pack_bits(&BITS, false);
+    assert_eq!(packed, [0b1100_1101, 0b0001_0001]);
+    assert_eq!(unpack_bits(&packed, 13, false), BITS);
+    assert_ne!(unpack_bits(&packed, 13, true), BITS);
+}
+
+#[test]
+fn unpack_reads_missing_bytes_as_zero() {
+    assert_eq!(unpack_bits(&[0xFF], 10, true)[8..], [false, false]);
+    assert!(pack_bits(&[], true).is_empty());
+}
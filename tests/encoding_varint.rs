@@ -0,0 +1,145 @@
+use anyhow::Result;
+use wavefst::encoding::{
+    VARINT_MAX_LEN, decode_n_varints, decode_sign_extended_varint, decode_svarint, decode_varint,
+    decode_varint_with_len, decode_varints_into, encode_svarint, encode_varint, varint_len,
+};
+
+fn boundary_values() -> Vec<u64> {
+    let mut values = vec![0, 1, u64::MAX];
+    for bits in (7..64).step_by(7) {
+        let threshold = 1u64 << bits;
+        values.push(threshold - 1);
+        values.push(threshold);
+    }
+    values
+}
+
+#[test]
+fn varint_len_matches_encoded_length_at_boundaries() {
+    for value in boundary_values() {
+        let mut buf = Vec::new();
+        let written = encode_varint(value, &mut buf);
+        assert_eq!(written, buf.len());
+        assert_eq!(varint_len(value), written, "length mismatch for {value}");
+        assert!(written <= VARINT_MAX_LEN);
+    }
+}
+
+#[test]
+fn varint_len_steps_at_each_seven_bit_threshold() {
+    assert_eq!(varint_len(0), 1);
+    for (idx, bits) in (7..64).step_by(7).enumerate() {
+        let threshold = 1u64 << bits;
+        assert_eq!(varint_len(threshold - 1), idx + 1);
+        assert_eq!(varint_len(threshold), idx + 2);
+    }
+    assert_eq!(varint_len(u64::MAX), VARINT_MAX_LEN);
+}
+
+#[test]
+fn varint_round_trips_at_boundaries() -> Result<()> {
+    for value in boundary_values() {
+        let mut buf = Vec::new();
+        encode_varint(value, &mut buf);
+
+        let mut slice = buf.as_slice();
+        assert_eq!(decode_varint(&mut slice)?, value);
+        assert!(slice.is_empty());
+
+        let (decoded, consumed) = decode_varint_with_len(&buf)?;
+        assert_eq!(decoded, value);
+        assert_eq!(consumed, buf.len());
+    }
+    Ok(())
+}
+
+#[test]
+fn svarint_round_trips_at_extremes() -> Result<()> {
+    for value in [0, 1, -1, 63, -64, 64, -65, i64::MAX, i64::MIN] {
+        let mut buf = Vec::new();
+        encode_svarint(value, &mut buf);
+        let mut slice = buf.as_slice();
+        assert_eq!(decode_svarint(&mut slice)?, value);
+        assert!(slice.is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn sign_extended_varint_matches_libfst_encoding() -> Result<()> {
+    let cases: [(&[u8], i64); 6] = [
+        (&[0x03], 3),
+        (&[0x3f], 63),
+        (&[0x7f], -1),
+        (&[0x40], -64),
+        (&[0xc0, 0x00], 64),
+        (&[0xbf, 0x7f], -65),
+    ];
+    for (bytes, expected) in cases {
+        let mut slice = bytes;
+        assert_eq!(decode_sign_extended_varint(&mut slice)?, expected);
+        assert!(slice.is_empty());
+    }
+    let mut truncated: &[u8] = &[0x80];
+    assert!(decode_sign_extended_varint(&mut truncated).is_err());
+    Ok(())
+}
+
+#[test]
+fn varint_decode_rejects_truncated_and_overlong_input() {
+    let mut truncated: &[u8] = &[0x80, 0x80];
+    assert!(decode_varint(&mut truncated).is_err());
+    assert!(decode_varint_with_len(&[0xff]).is_err());
+
+    let overlong = [0x80u8; VARINT_MAX_LEN + 1];
+    let mut slice = &overlong[..];
+    assert!(decode_varint(&mut slice).is_err());
+    assert!(decode_varint_with_len(&overlong).is_err());
+}
+
+#[test]
+fn batch_decoding_handles_empty_input() -> Result<()> {
+    let mut out = Vec::new();
+    assert_eq!(decode_varints_into(&[], &mut out)?, 0);
+    assert!(out.is_empty());
+    assert_eq!(decode_n_varints(&[], 0)?, (Vec::new(), 0));
+    assert!(decode_n_varints(&[], 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn batch_decoding_matches_single_value_decoding() -> Result<()> {
+    let values = boundary_values();
+    let mut encoded = Vec::new();
+    for value in &values {
+        encode_varint(*value, &mut encoded);
+    }
+
+    let mut out = vec![7];
+    assert_eq!(decode_varints_into(&encoded, &mut out)?, encoded.len());
+    assert_eq!(out[0], 7, "existing entries are kept");
+    assert_eq!(&out[1..], values.as_slice());
+
+    let (all, consumed) = decode_n_varints(&encoded, values.len())?;
+    assert_eq!(all, values);
+    assert_eq!(consumed, encoded.len());
+
+    let (prefix, consumed) = decode_n_varints(&encoded, 3)?;
+    assert_eq!(prefix, values[..3]);
+    let mut rest = &encoded[consumed..];
+    assert_eq!(decode_varint(&mut rest)?, values[3]);
+    Ok(())
+}
+
+#[test]
+fn batch_decoding_rejects_truncated_final_varint() {
+    let mut encoded = Vec::new();
+    encode_varint(5, &mut encoded);
+    encode_varint(300, &mut encoded);
+    encoded.pop();
+
+    let mut out = Vec::new();
+    assert!(decode_varints_into(&encoded, &mut out).is_err());
+    assert!(decode_n_varints(&encoded, 2).is_err());
+    assert_eq!(decode_n_varints(&encoded, 1).unwrap(), (vec![5], 1));
+}
@@ -0,0 +1,116 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use wavefst::export::vcd::write_vcd;
+use wavefst::{
+    FstWriter, GeomEntry, Header, ReaderBuilder, ScopeType, SignalValue, VarDir, VarType,
+};
+
+#[test]
+fn vcd_export_reconstructs_declarations_and_changes() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).timescale_exponent(-10).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let data = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "data",
+        GeomEntry::Fixed(4),
+    )?;
+    writer.begin_scope(ScopeType::VcdModule, "sub", None)?;
+    let temp = writer.add_variable(VarType::VcdReal, VarDir::Implicit, "temp", GeomEntry::Real)?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "clk_alias", clk)?;
+    writer.end_scope()?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "vcd-export".into(),
+        end_time: 10,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, clk, SignalValue::Bit('0'))?;
+    writer.emit_change(0, data, SignalValue::Vector(Cow::Borrowed("0101")))?;
+    writer.emit_change(0, temp, SignalValue::Real(2.75))?;
+    writer.emit_change(5, clk, SignalValue::Bit('1'))?;
+    writer.emit_change(10, data, SignalValue::Vector(Cow::Borrowed("1x01")))?;
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut out = Vec::new();
+    write_vcd(&mut reader, &mut out)?;
+    let vcd = String::from_utf8(out)?;
+
+    assert!(vcd.contains("$timescale\n\t100ps\n$end"));
+    assert!(vcd.contains("$scope module top $end"));
+    assert!(vcd.contains("$var wire 1 ! clk $end"));
+    assert!(vcd.contains("$var reg 4 \" data $end"));
+    assert!(vcd.contains("$scope module sub $end"));
+    assert!(vcd.contains("$var real 64 # temp $end"));
+    assert!(vcd.contains("$var wire 1 ! clk_alias $end"));
+    assert_eq!(vcd.matches("$upscope $end").count(), 2);
+    assert!(vcd.contains("$enddefinitions $end"));
+
+    let body = vcd.split("$enddefinitions $end\n").nth(1).unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    let at = |stamp: &str| lines.iter().position(|line| *line == stamp).unwrap();
+    let (t0, t5, t10) = (at("#0"), at("#5"), at("#10"));
+    assert!(t0 < t5 && t5 < t10);
+    assert_eq!(t0, 0);
+    assert_eq!(
+        &lines[t0 + 1..t5],
+        &["$dumpvars", "0!", "b0101 \"", "r2.75 #", "$end"]
+    );
+    assert_eq!(&lines[t5 + 1..t10], &["1!"]);
+    assert_eq!(&lines[t10 + 1..], &["b1x01 \""]);
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "gzip"), ignore = "requires gzip feature")]
+#[test]
+fn vcd_export_dumps_initial_values_of_the_fixture() -> Result<()> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/hdl-example.fst");
+    let mut reader = ReaderBuilder::new(File::open(path)?).build()?;
+    let mut out = Vec::new();
+    write_vcd(&mut reader, &mut out)?;
+    let vcd = String::from_utf8(out)?;
+
+    let (declarations, body) = vcd
+        .split_once("$enddefinitions $end\n")
+        .expect("declaration section present");
+    let declared: BTreeSet<&str> = declarations
+        .lines()
+        .filter(|line| line.starts_with("$var "))
+        .filter_map(|line| line.split_whitespace().nth(3))
+        .collect();
+    let dumpvars = body
+        .split_once("$dumpvars\n")
+        .and_then(|(_, rest)| rest.split_once("$end\n"))
+        .map(|(values, _)| values)
+        .expect("$dumpvars section present");
+    let dumped: BTreeSet<&str> = dumpvars
+        .lines()
+        .map(|line| match line.split_once(' ') {
+            Some((_, id)) => id,
+            None => &line[1..],
+        })
+        .collect();
+    assert!(!declared.is_empty());
+    assert_eq!(dumped, declared);
+
+    Ok(())
+}
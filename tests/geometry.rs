@@ -0,0 +1,209 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use wavefst::{FstReader, FstWriter, GeomEntry, GeomInfo, Header, ScopeType, VarDir, VarType};
+
+#[test]
+fn scalar_entry_classification() {
+    let entry = GeomEntry::Fixed(1);
+    assert_eq!(entry.bit_width(), Some(1));
+    assert!(entry.is_scalar());
+    assert!(!entry.is_vector());
+    assert!(!entry.is_real());
+    assert!(!entry.is_variable());
+}
+
+#[test]
+fn vector_entry_classification() {
+    let entry = GeomEntry::Fixed(32);
+    assert_eq!(entry.bit_width(), Some(32));
+    assert!(!entry.is_scalar());
+    assert!(entry.is_vector());
+    assert!(!entry.is_real());
+    assert!(!entry.is_variable());
+}
+
+#[test]
+fn real_entry_classification() {
+    let entry = GeomEntry::Real;
+    assert_eq!(entry.bit_width(), None);
+    assert!(!entry.is_scalar());
+    assert!(!entry.is_vector());
+    assert!(entry.is_real());
+    assert!(!entry.is_variable());
+}
+
+#[test]
+fn variable_entry_classification() {
+    let entry = GeomEntry::Variable;
+    assert_eq!(entry.bit_width(), None);
+    assert!(!entry.is_scalar());
+    assert!(!entry.is_vector());
+    assert!(!entry.is_real());
+    assert!(entry.is_variable());
+}
+
+#[test]
+fn total_bits_sums_fixed_widths_only() -> Result<()> {
+    let geom = GeomInfo::from_runs([
+        (3, GeomEntry::Fixed(1)),
+        (1, GeomEntry::Real),
+        (2, GeomEntry::Fixed(16)),
+        (1, GeomEntry::Variable),
+    ])?;
+    assert_eq!(geom.total_bits(), 35);
+    assert_eq!(GeomInfo::default().total_bits(), 0);
+    Ok(())
+}
+
+#[test]
+fn to_runs_inverts_from_runs() -> Result<()> {
+    let runs = vec![
+        (4, GeomEntry::Fixed(1)),
+        (1, GeomEntry::Fixed(8)),
+        (2, GeomEntry::Real),
+        (1, GeomEntry::Fixed(1)),
+        (3, GeomEntry::Variable),
+    ];
+    let geom = GeomInfo::from_runs(runs.clone())?;
+    assert_eq!(geom.to_runs(), runs);
+    assert_eq!(GeomInfo::from_runs(geom.to_runs())?.entries, geom.entries);
+    assert!(GeomInfo::default().to_runs().is_empty());
+    Ok(())
+}
+
+#[test]
+fn streaming_decode_matches_buffered_decode() -> Result<()> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let runs: Vec<(u32, GeomEntry)> = (0..20_000)
+        .map(|_| {
+            let count = (next() % 50 + 1) as u32;
+            let entry = match next() % 4 {
+                0 => GeomEntry::Real,
+                1 => GeomEntry::Variable,
+                _ => GeomEntry::Fixed((next() % 128 + 1) as u32),
+            };
+            (count, entry)
+        })
+        .collect();
+    let geom = GeomInfo::from_runs(runs)?;
+
+    let encodings = [
+        geom.encode_block(false, None)?,
+        geom.encode_block(cfg!(feature = "gzip"), None)?,
+        geom.encode_block_rle(cfg!(feature = "gzip"), None)?,
+    ];
+    for encoded in encodings {
+        let mut bytes = Vec::new();
+        encoded.write_to(&mut bytes)?;
+        let buffered =
+            GeomInfo::decode_block(&mut Cursor::new(&bytes[8..]), encoded.section_length)?;
+        let mut stream = Cursor::new(&bytes[8..]);
+        let streamed = GeomInfo::decode_block_streaming(&mut stream, encoded.section_length)?;
+        assert_eq!(streamed.max_handle, buffered.max_handle);
+        assert_eq!(streamed.entries, buffered.entries);
+        assert_eq!(streamed.entries, geom.entries);
+        assert_eq!(stream.position() as usize, bytes.len() - 8);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn run_length_geometry_round_trips_and_shrinks() -> Result<()> {
+    let geom = GeomInfo::from_runs([
+        (10_000, GeomEntry::Fixed(1)),
+        (3, GeomEntry::Real),
+        (2, GeomEntry::Fixed(32)),
+    ])?;
+
+    let plain = geom.encode_block(false, None)?;
+    let rle = geom.encode_block_rle(false, None)?;
+    assert!(rle.run_length);
+    assert!(rle.section_length * 100 < plain.section_length);
+
+    let mut bytes = Vec::new();
+    rle.write_to(&mut bytes)?;
+    let mut cursor = Cursor::new(&bytes[8..]);
+    let decoded = GeomInfo::decode_block(&mut cursor, rle.section_length)?;
+    assert_eq!(decoded.max_handle, geom.max_handle);
+    assert_eq!(decoded.entries, geom.entries);
+
+    // Without repeated entries the runs cannot win, so the libfst layout is kept.
+    let distinct = GeomInfo::from_runs((1..=64u32).map(|width| (1, GeomEntry::Fixed(width))))?;
+    assert!(!distinct.encode_block_rle(false, None)?.run_length);
+
+    Ok(())
+}
+
+#[test]
+fn writer_geometry_rle_is_readable() -> Result<()> {
+    let build = |rle: bool| -> Result<Vec<u8>> {
+        let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+            .compress_geometry(false)
+            .geometry_rle(rle)
+            .build()?;
+        writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+        for idx in 0..2_000 {
+            writer.add_variable(
+                VarType::VcdWire,
+                VarDir::Implicit,
+                format!("bit{idx}"),
+                GeomEntry::Fixed(1),
+            )?;
+        }
+        writer.end_scope()?;
+        writer.write_header(Header::default())?;
+        Ok(writer.finish()?.into_inner())
+    };
+
+    let plain = build(false)?;
+    let rle = build(true)?;
+    assert!(rle.len() < plain.len());
+
+    let reader = FstReader::from_bytes(rle)?;
+    let geom = reader.geometry().expect("geometry loaded eagerly");
+    assert_eq!(geom.max_handle, 2_000);
+    assert!(geom.entries.iter().all(GeomEntry::is_scalar));
+
+    Ok(())
+}
+
+#[test]
+fn alias_handles_resolve_to_their_target_geometry() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let base = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "base",
+        GeomEntry::Fixed(12),
+    )?;
+    let alias = writer.add_alias(VarType::VcdReg, VarDir::Implicit, "alias", base)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    let reader = FstReader::from_bytes(writer.finish()?.into_inner())?;
+
+    // The alias owns a handle and therefore a geometry entry of its own.
+    let geom = reader.geometry().expect("geometry loaded eagerly");
+    assert_eq!(geom.max_handle, 2);
+    assert_eq!(geom.entry(base), Some(&GeomEntry::Fixed(12)));
+    assert_eq!(geom.entry(alias), Some(&GeomEntry::Fixed(12)));
+
+    // The hierarchy names the alias by its target handle; both resolve to the same geometry.
+    let hierarchy = reader.hierarchy().expect("hierarchy loaded eagerly");
+    for var in &hierarchy.variables {
+        assert_eq!(var.handle, base, "{}", var.name);
+        assert_eq!(geom.entry(var.handle), Some(&GeomEntry::Fixed(12)));
+        assert_eq!(var.length, Some(12));
+    }
+    assert_eq!(hierarchy.variables.len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+#[cfg(feature = "lz4")]
+use wavefst::HierarchyCompression;
+use wavefst::{
+    EnumDef, FstWriter, GeomEntry, Header, HierarchyBlock, HierarchyEvent, ReaderBuilder,
+    ScopeType, VarDir, VarType,
+};
+
+fn nested_hierarchy() -> Result<HierarchyBlock> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    writer.add_variable(VarType::VcdWire, VarDir::Input, "clk", GeomEntry::Fixed(1))?;
+    writer.begin_scope(ScopeType::VcdModule, "sub", None)?;
+    let data = writer.add_variable(VarType::VcdReg, VarDir::Output, "data", GeomEntry::Fixed(8))?;
+    writer.begin_scope(ScopeType::VcdBegin, "inner", None)?;
+    writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.end_scope()?;
+    writer.add_alias(VarType::VcdReg, VarDir::Implicit, "data_alias", data)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    let bytes = writer.finish()?.into_inner();
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    Ok(reader
+        .hierarchy()
+        .cloned()
+        .expect("hierarchy block should be present"))
+}
+
+#[test]
+fn resolve_path_walks_nested_scopes() -> Result<()> {
+    let hier = nested_hierarchy()?;
+
+    assert_eq!(hier.resolve_path("top.clk"), Some(1));
+    assert_eq!(hier.resolve_path("top.sub.data"), Some(2));
+    assert_eq!(hier.resolve_path("top.sub.inner.clk"), Some(3));
+    assert_eq!(hier.resolve_path("top.data_alias"), Some(2));
+    assert_eq!(hier.resolve_path("top.sub"), None);
+    assert_eq!(hier.resolve_path("sub.data"), None);
+
+    Ok(())
+}
+
+#[test]
+fn handle_path_round_trips_with_resolve_path() -> Result<()> {
+    let hier = nested_hierarchy()?;
+
+    for handle in 1..=3 {
+        let path = hier
+            .handle_path(handle)
+            .expect("every declared handle has a path");
+        assert_eq!(hier.resolve_path(&path), Some(handle));
+    }
+    assert_eq!(hier.handle_path(2).as_deref(), Some("top.sub.data"));
+    assert_eq!(hier.handle_path(4), None);
+    assert_eq!(hier.scope_path(2).as_deref(), Some("top.sub.inner"));
+
+    Ok(())
+}
+
+#[test]
+fn writer_emits_scoped_attributes() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    writer.begin_scope(ScopeType::VcdModule, "dut", None)?;
+    writer.begin_attribute(2, 4, "rtl/dut.v", 17)?;
+    writer.add_variable(VarType::VcdWire, VarDir::Input, "clk", GeomEntry::Fixed(1))?;
+    writer.end_attribute()?;
+    writer.end_scope()?;
+    writer.end_scope()?;
+    assert!(writer.end_attribute().is_err());
+    writer.write_header(Header::default())?;
+    assert!(writer.begin_attribute(0, 0, "late", 0).is_err());
+
+    let bytes = writer.finish()?.into_inner();
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let hier = reader
+        .hierarchy()
+        .expect("hierarchy block should be present");
+
+    assert_eq!(hier.attributes.len(), 1);
+    let attr = &hier.attributes[0];
+    assert_eq!(attr.attr_type, 2);
+    assert_eq!(attr.subtype, 4);
+    assert_eq!(attr.name, "rtl/dut.v");
+    assert_eq!(attr.argument, 17);
+    assert_eq!(
+        attr.scope.and_then(|idx| hier.scope_path(idx)).as_deref(),
+        Some("top.dut")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn attributes_group_by_scope() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_attribute(0, 0, "generator", 1)?;
+    writer.end_attribute()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    writer.begin_scope(ScopeType::VcdModule, "dut", None)?;
+    writer.begin_attribute(2, 4, "rtl/dut.v", 17)?;
+    writer.end_attribute()?;
+    writer.add_variable(VarType::VcdWire, VarDir::Input, "clk", GeomEntry::Fixed(1))?;
+    writer.end_scope()?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    let bytes = writer.finish()?.into_inner();
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let hier = reader
+        .hierarchy()
+        .expect("hierarchy block should be present");
+
+    let root: Vec<&str> = hier
+        .root_attributes()
+        .map(|attr| attr.name.as_str())
+        .collect();
+    assert_eq!(root, ["generator"]);
+
+    let dut: Vec<(&str, u64)> = hier
+        .attributes_for_scope(1)
+        .map(|attr| (attr.name.as_str(), attr.argument))
+        .collect();
+    assert_eq!(dut, [("rtl/dut.v", 17)]);
+    assert_eq!(hier.scope_path(1).as_deref(), Some("top.dut"));
+    assert_eq!(hier.attributes_for_scope(0).count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn walk_streams_items_in_document_order() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    writer.begin_attribute(2, 4, "rtl/top.v", 3)?;
+    writer.add_variable(VarType::VcdWire, VarDir::Input, "clk", GeomEntry::Fixed(1))?;
+    writer.end_attribute()?;
+    writer.begin_scope(ScopeType::VcdModule, "sub", None)?;
+    writer.add_variable(VarType::VcdReg, VarDir::Output, "data", GeomEntry::Fixed(8))?;
+    writer.end_scope()?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    let bytes = writer.finish()?.into_inner();
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let hier = reader
+        .hierarchy()
+        .expect("hierarchy block should be present");
+
+    let events: Vec<String> = hier
+        .walk()
+        .map(|event| match event {
+            HierarchyEvent::EnterScope(scope) => format!("enter {}", scope.name),
+            HierarchyEvent::LeaveScope => "leave".to_string(),
+            HierarchyEvent::Variable(var) => format!("var {}", var.name),
+            HierarchyEvent::Attribute(attr) => format!("attr {}", attr.name),
+            HierarchyEvent::AttributeEnd => "attr end".to_string(),
+        })
+        .collect();
+    assert_eq!(
+        events,
+        [
+            "enter top",
+            "attr rtl/top.v",
+            "var clk",
+            "attr end",
+            "enter sub",
+            "var data",
+            "leave",
+            "leave",
+        ]
+    );
+    assert_eq!(events.len(), hier.items.len());
+
+    Ok(())
+}
+
+#[test]
+fn enum_definitions_round_trip() -> Result<()> {
+    let members: Vec<(String, String)> = [("IDLE", "00"), ("RUN STATE", "01"), ("DONE?", "10")]
+        .into_iter()
+        .map(|(literal, value)| (literal.to_string(), value.to_string()))
+        .collect();
+
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let table = writer.add_enum_definition("fsm_state", &members)?;
+    writer.add_variable(
+        VarType::SvEnum,
+        VarDir::Implicit,
+        "state",
+        GeomEntry::Fixed(2),
+    )?;
+    writer.end_scope()?;
+    assert!(writer.add_enum_definition("", &members).is_err());
+    writer.write_header(Header::default())?;
+
+    let bytes = writer.finish()?.into_inner();
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let hier = reader
+        .hierarchy()
+        .expect("hierarchy block should be present");
+
+    assert_eq!(
+        hier.attributes[0].name,
+        "fsm_state 3 IDLE RUN\\040STATE DONE\\? 00 01 10"
+    );
+    assert_eq!(
+        hier.enum_definitions(),
+        vec![EnumDef {
+            handle: table,
+            name: "fsm_state".into(),
+            members,
+        }]
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "lz4")]
+fn wide_hierarchy_trace(compression: HierarchyCompression) -> Result<Vec<u8>> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .hierarchy_compression(compression)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    for module in 0..32 {
+        writer.begin_scope(ScopeType::VcdModule, format!("lane_{module}"), None)?;
+        for signal in 0..64 {
+            writer.add_variable(
+                VarType::VcdWire,
+                VarDir::Implicit,
+                format!("data_signal_{signal}"),
+                GeomEntry::Fixed(8),
+            )?;
+        }
+        writer.end_scope()?;
+    }
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    Ok(writer.finish()?.into_inner())
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn writer_compresses_hierarchy_with_lz4() -> Result<()> {
+    let raw = wide_hierarchy_trace(HierarchyCompression::Raw)?;
+    let lz4 = wide_hierarchy_trace(HierarchyCompression::Lz4)?;
+    assert!(
+        lz4.len() < raw.len(),
+        "lz4 trace ({} bytes) should be smaller than raw trace ({} bytes)",
+        lz4.len(),
+        raw.len()
+    );
+
+    let raw_reader = ReaderBuilder::new(Cursor::new(raw)).build()?;
+    let lz4_reader = ReaderBuilder::new(Cursor::new(lz4)).build()?;
+    let raw_hier = raw_reader.hierarchy().expect("raw hierarchy present");
+    let lz4_hier = lz4_reader.hierarchy().expect("lz4 hierarchy present");
+    assert_eq!(format!("{raw_hier:?}"), format!("{lz4_hier:?}"));
+    assert_eq!(
+        lz4_hier.resolve_path("top.lane_31.data_signal_63"),
+        Some(2048)
+    );
+
+    Ok(())
+}
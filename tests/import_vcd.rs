@@ -0,0 +1,99 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use wavefst::export::vcd::write_vcd;
+use wavefst::import::vcd::read_vcd;
+use wavefst::{BlackoutEvent, FstWriter, ReaderBuilder, SignalValue};
+
+const SAMPLE: &str = "\
+$date today $end
+$version handwritten $end
+$timescale 10ns $end
+$scope module top $end
+$var wire 1 ! clk $end
+$var reg 4 \" data [3:0] $end
+$scope module sub $end
+$var real 64 # temp $end
+$var wire 1 ! clk_alias $end
+$upscope $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+0!
+b101 \"
+r1.5 #
+$end
+#5
+1!
+$dumpoff
+#8
+$dumpon
+#10
+bx1 \"
+0!
+";
+
+#[test]
+fn vcd_import_round_trips_through_export() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    read_vcd(SAMPLE.as_bytes(), &mut writer)?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.header().timescale_exponent, -8);
+    assert_eq!(reader.header().version, "handwritten");
+    let hierarchy = reader.hierarchy().expect("hierarchy must be present");
+    assert_eq!(hierarchy.resolve_path("top.data [3:0]"), Some(2));
+    assert_eq!(hierarchy.resolve_path("top.sub.clk_alias"), Some(1));
+    assert_eq!(
+        reader.value_at(2, 7)?.and_then(|v| v.as_u64()),
+        Some(0b0101)
+    );
+    assert_eq!(reader.value_at(3, 0)?, Some(SignalValue::Real(1.5)));
+
+    let mut out = Vec::new();
+    write_vcd(&mut reader, &mut out)?;
+    let vcd = String::from_utf8(out)?;
+    assert_eq!(
+        reader.blackout().map(|block| block.events.clone()),
+        Some(vec![
+            BlackoutEvent {
+                is_on: false,
+                time: 5,
+            },
+            BlackoutEvent {
+                is_on: true,
+                time: 8,
+            },
+        ])
+    );
+
+    assert!(vcd.contains("$timescale\n\t10ns\n$end"));
+    assert!(vcd.contains("$var wire 1 ! clk $end"));
+    assert!(vcd.contains("$var reg 4 \" data [3:0] $end"));
+    assert!(vcd.contains("$var real 64 # temp $end"));
+    assert!(vcd.contains("$var wire 1 ! clk_alias $end"));
+
+    let body = vcd.split("$enddefinitions $end\n").nth(1).unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    let at = |stamp: &str| lines.iter().position(|line| *line == stamp).unwrap();
+    let (t0, t5, t10) = (at("#0"), at("#5"), at("#10"));
+    assert!(lines[t0..t5].contains(&"0!"));
+    assert!(lines[t0..t5].contains(&"b0101 \""));
+    assert!(lines[t0..t5].contains(&"r1.5 #"));
+    assert_eq!(&lines[t5 + 1..t10], &["1!"]);
+    assert!(lines[t10..].contains(&"bxxx1 \""));
+    assert!(lines[t10..].contains(&"0!"));
+
+    Ok(())
+}
+
+#[test]
+fn vcd_import_rejects_undeclared_identifiers() -> Result<()> {
+    let input = "$scope module top $end\n$var wire 1 ! a $end\n$upscope $end\n\
+                 $enddefinitions $end\n#0\n1?\n";
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    assert!(read_vcd(input.as_bytes(), &mut writer).is_err());
+    Ok(())
+}
@@ -0,0 +1,2045 @@
+use std::collections::HashSet;
+use std::io::Cursor;
+
+use anyhow::Result;
+use wavefst::block::{
+    ChainIndexEntry, FrameSection, TimeSection, TimeTable, decode_chain_index_entries,
+    encode_chain_index, encode_chain_index_dyn_alias2, encode_time_section,
+};
+use wavefst::compression::decompressor_for;
+use wavefst::encoding::{decode_varint_with_len, encode_varint};
+use wavefst::reader::ValueChange;
+use wavefst::{
+    BlockType, ChainCompression, Endianness, FstWriter, GeomEntry, GeomInfo, Header, PackType,
+    ReaderBuilder, ScopeType, SignalValue, TimeCompression, VarDir, VarType,
+};
+
+fn sample_trace() -> Result<Vec<u8>> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let handle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "reader-blocks".into(),
+        end_time: 10,
+        vc_section_count: 2,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, handle, SignalValue::Bit('0'))?;
+    writer.flush()?;
+    writer.emit_change(10, handle, SignalValue::Bit('1'))?;
+
+    Ok(writer.finish()?.into_inner())
+}
+
+fn skip_block(payload_len: usize) -> Vec<u8> {
+    let mut block = vec![BlockType::Skip as u8];
+    block.extend_from_slice(&(payload_len as u64 + 8).to_be_bytes());
+    block.resize(block.len() + payload_len, 0);
+    block
+}
+
+#[test]
+fn skip_remaining_tolerates_trailing_skip_blocks() -> Result<()> {
+    let mut bytes = sample_trace()?;
+    bytes.extend_from_slice(&skip_block(0));
+    bytes.extend_from_slice(&skip_block(16));
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    reader.skip_remaining()?;
+    assert!(reader.geometry().is_some());
+    assert!(reader.hierarchy().is_some());
+    assert!(reader.next_vc_block()?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn skip_remaining_after_partial_iteration() -> Result<()> {
+    let bytes = sample_trace()?;
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+
+    let first = reader
+        .next_vc_block()?
+        .expect("first value-change block must be present");
+    assert_eq!(first.header.begin_time, 0);
+
+    reader.skip_remaining()?;
+    assert!(reader.next_vc_block()?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn skip_remaining_rejects_duplicate_header() -> Result<()> {
+    let mut bytes = sample_trace()?;
+    bytes.push(BlockType::Header as u8);
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let err = reader
+        .skip_remaining()
+        .expect_err("a second header block must be rejected");
+    assert!(err.to_string().contains("duplicate header"), "{err}");
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn reader_unwraps_zlib_envelope() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).wrap_with_zlib(true).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let handle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "zwrapper-round-trip".into(),
+        end_time: 7,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+    writer.emit_change(3, handle, SignalValue::Bit('1'))?;
+    writer.emit_change(7, handle, SignalValue::Bit('0'))?;
+
+    let bytes = writer.finish()?.into_inner();
+    assert_eq!(bytes[0], BlockType::ZWrapper as u8);
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert!(reader.is_zlib_wrapped());
+    assert_eq!(reader.header().version, "zwrapper-round-trip");
+    assert!(reader.geometry().is_some());
+    assert!(reader.hierarchy().is_some());
+
+    let mut events = Vec::new();
+    while let Some(block) = reader.next_value_changes()? {
+        for event in block {
+            let event = event?;
+            events.push((event.timestamp, event.value.into_owned()));
+        }
+    }
+    assert_eq!(
+        events,
+        vec![(3, SignalValue::Bit('1')), (7, SignalValue::Bit('0'))]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn reader_reports_plain_files_as_unwrapped() -> Result<()> {
+    let reader = ReaderBuilder::new(Cursor::new(sample_trace()?)).build()?;
+    assert!(!reader.is_zlib_wrapped());
+    Ok(())
+}
+
+#[test]
+fn all_value_changes_spans_every_block_in_order() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let a = writer.add_variable(VarType::VcdWire, VarDir::Implicit, "a", GeomEntry::Fixed(1))?;
+    let b = writer.add_variable(VarType::VcdWire, VarDir::Implicit, "b", GeomEntry::Fixed(1))?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "merged".into(),
+        end_time: 30,
+        vc_section_count: 3,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, a, SignalValue::Bit('0'))?;
+    writer.emit_change(5, b, SignalValue::Bit('1'))?;
+    writer.emit_change(10, a, SignalValue::Bit('1'))?;
+    writer.flush()?;
+    writer.emit_change(10, b, SignalValue::Bit('0'))?;
+    writer.emit_change(20, a, SignalValue::Bit('0'))?;
+    writer.flush()?;
+    writer.emit_change(30, b, SignalValue::Bit('1'))?;
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+
+    let events = reader
+        .all_value_changes()?
+        .map(|event| event.map(|event| (event.timestamp, event.handle, event.value)))
+        .collect::<wavefst::Result<Vec<_>>>()?;
+
+    assert_eq!(
+        events,
+        vec![
+            (0, a, SignalValue::Bit('0')),
+            (5, b, SignalValue::Bit('1')),
+            (10, a, SignalValue::Bit('1')),
+            (10, b, SignalValue::Bit('0')),
+            (20, a, SignalValue::Bit('0')),
+            (30, b, SignalValue::Bit('1')),
+        ]
+    );
+    assert!(
+        events.windows(2).all(|pair| pair[0].0 <= pair[1].0),
+        "merged stream must be globally monotonic"
+    );
+    assert!(reader.next_vc_block()?.is_none());
+
+    Ok(())
+}
+
+fn three_block_trace() -> Result<(Vec<u8>, u32)> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let handle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "three-blocks".into(),
+        end_time: 50,
+        vc_section_count: 3,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    for (begin, end) in [(0u64, 10u64), (20, 30), (40, 50)] {
+        writer.emit_change(begin, handle, SignalValue::Bit('1'))?;
+        writer.emit_change(end, handle, SignalValue::Bit('0'))?;
+        writer.flush()?;
+    }
+
+    Ok((writer.finish()?.into_inner(), handle))
+}
+
+#[test]
+fn seek_to_time_finds_covering_block() -> Result<()> {
+    let (bytes, handle) = three_block_trace()?;
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+
+    let block = reader
+        .seek_to_time(25)?
+        .expect("time 25 lies inside the second block");
+    assert_eq!(block.header.begin_time, 20);
+    assert_eq!(block.header.end_time, 30);
+
+    let mut changes = reader
+        .next_value_changes()?
+        .expect("stream should be positioned on the located block");
+    let first = changes.next().expect("block has changes")?;
+    assert_eq!(first.timestamp, 20);
+    assert_eq!(first.handle, handle);
+    drop(changes);
+
+    let block = reader
+        .seek_to_time(40)?
+        .expect("time 40 starts the third block");
+    assert_eq!(block.header.begin_time, 40);
+
+    let block = reader
+        .seek_to_time(5)?
+        .expect("seeking backwards restarts from the first block");
+    assert_eq!(block.header.begin_time, 0);
+
+    Ok(())
+}
+
+#[test]
+fn seek_to_time_handles_gaps_and_bounds() -> Result<()> {
+    let (bytes, _) = three_block_trace()?;
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+
+    let block = reader
+        .seek_to_time(0)?
+        .expect("earliest time resolves to the first block");
+    assert_eq!(block.header.begin_time, 0);
+
+    let block = reader
+        .seek_to_time(15)?
+        .expect("a gap resolves to the following block");
+    assert_eq!(block.header.begin_time, 20);
+
+    assert!(reader.seek_to_time(51)?.is_none());
+    assert!(reader.next_value_changes()?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn block_index_records_time_ordered_offsets() -> Result<()> {
+    let (bytes, _) = three_block_trace()?;
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+
+    let index = reader.build_block_index()?;
+    assert_eq!(index.len(), 3);
+    assert!(
+        index
+            .windows(2)
+            .all(|pair| pair[0].end_time <= pair[1].begin_time && pair[0].offset < pair[1].offset),
+        "index entries must be ordered by time and offset"
+    );
+    assert!(index.iter().all(|entry| entry.vc_max_handle == 1));
+
+    for entry in index.iter().rev() {
+        let block = reader.read_block_at(entry.offset)?;
+        assert_eq!(block.header.begin_time, entry.begin_time);
+        assert_eq!(block.header.end_time, entry.end_time);
+        assert_eq!(block.header.vc_max_handle, entry.vc_max_handle);
+    }
+
+    assert!(
+        reader.read_block_at(0).is_err(),
+        "offset 0 holds the header"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn block_index_preserves_stream_position() -> Result<()> {
+    let (bytes, _) = three_block_trace()?;
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+
+    let first = reader
+        .next_vc_block()?
+        .expect("first value-change block must be present");
+    assert_eq!(first.header.begin_time, 0);
+
+    let index = reader.build_block_index()?;
+    assert_eq!(index[0].begin_time, 0);
+
+    let second = reader
+        .next_vc_block()?
+        .expect("iteration should resume after indexing");
+    assert_eq!(second.header.begin_time, 20);
+
+    Ok(())
+}
+
+#[test]
+fn value_at_returns_latest_change_before_time() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    let other = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "other",
+        GeomEntry::Fixed(1),
+    )?;
+    let alias = writer.add_alias(VarType::VcdWire, VarDir::Implicit, "bit_alias", bit)?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "value-at".into(),
+        end_time: 20,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, bit, SignalValue::Bit('0'))?;
+    writer.emit_change(10, bit, SignalValue::Bit('1'))?;
+    writer.emit_change(20, bit, SignalValue::Bit('z'))?;
+    writer.emit_change(5, other, SignalValue::Bit('1'))?;
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+
+    assert_eq!(reader.value_at(bit, 15)?, Some(SignalValue::Bit('1')));
+    assert_eq!(reader.value_at(bit, 10)?, Some(SignalValue::Bit('1')));
+    assert_eq!(reader.value_at(bit, 9)?, Some(SignalValue::Bit('0')));
+    assert_eq!(reader.value_at(bit, 100)?, Some(SignalValue::Bit('z')));
+    assert_eq!(reader.value_at(alias, 15)?, Some(SignalValue::Bit('1')));
+    assert_eq!(reader.value_at(other, 7)?, Some(SignalValue::Bit('1')));
+    assert!(reader.value_at(0, 15).is_err());
+
+    let changes = reader
+        .next_value_changes()?
+        .expect("point queries must not consume the block");
+    assert_eq!(changes.count(), 7);
+
+    // Frames hold each block's final state, so a time between a block's last change and the next
+    // block must come from the earlier block's chain rather than the next block's frame.
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let count = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "count",
+        GeomEntry::Fixed(4),
+    )?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, count, SignalValue::Vector("0101".into()))?;
+    writer.emit_change(0, clk, SignalValue::Bit('0'))?;
+    writer.emit_change(5, clk, SignalValue::Bit('1'))?;
+    writer.flush()?;
+    writer.emit_change(12, count, SignalValue::Vector("0001".into()))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let text = |value: Option<SignalValue<'static>>| value.and_then(|v| v.to_ascii_vector());
+    assert_eq!(text(reader.value_at(count, 3)?).as_deref(), Some("0101"));
+    assert_eq!(text(reader.value_at(count, 9)?).as_deref(), Some("0101"));
+    assert_eq!(text(reader.value_at(count, 11)?).as_deref(), Some("0101"));
+    assert_eq!(text(reader.value_at(count, 12)?).as_deref(), Some("0001"));
+    assert_eq!(reader.value_at(clk, 3)?, Some(SignalValue::Bit('0')));
+    assert_eq!(reader.value_at(clk, 11)?, Some(SignalValue::Bit('1')));
+    assert_eq!(reader.value_at(clk, 40)?, Some(SignalValue::Bit('1')));
+
+    // Before a handle's first change the end-of-block frame does not apply.
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let late = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "late",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(10, late, SignalValue::Bit('1'))?;
+    writer.emit_change(20, late, SignalValue::Bit('0'))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.value_at(late, 5)?, None);
+    assert_eq!(reader.value_at(late, 15)?, Some(SignalValue::Bit('1')));
+    assert_eq!(reader.value_at(late, 25)?, Some(SignalValue::Bit('0')));
+
+    Ok(())
+}
+
+#[test]
+fn value_at_walks_back_past_blocks_without_frames() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+        .emit_frame(false)
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let a = writer.add_variable(VarType::VcdWire, VarDir::Implicit, "a", GeomEntry::Fixed(1))?;
+    let b = writer.add_variable(VarType::VcdWire, VarDir::Implicit, "b", GeomEntry::Fixed(1))?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, a, SignalValue::Bit('1'))?;
+    writer.flush()?;
+    writer.emit_change(10, b, SignalValue::Bit('1'))?;
+    writer.flush()?;
+    writer.emit_change(20, b, SignalValue::Bit('0'))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.build_block_index()?.len(), 3);
+    assert_eq!(reader.value_at(a, 15)?, Some(SignalValue::Bit('1')));
+    assert_eq!(reader.value_at(a, 25)?, Some(SignalValue::Bit('1')));
+    assert_eq!(reader.value_at(b, 5)?, None);
+    assert_eq!(reader.value_at(b, 15)?, Some(SignalValue::Bit('1')));
+
+    Ok(())
+}
+
+#[test]
+fn changes_filtered_yields_only_requested_handles() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let mut handles = Vec::new();
+    for idx in 0..10 {
+        handles.push(writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            format!("sig{idx}"),
+            GeomEntry::Fixed(1),
+        )?);
+    }
+    let alias = writer.add_alias(VarType::VcdWire, VarDir::Implicit, "sig7_alias", handles[7])?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "filtered".into(),
+        end_time: 9,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    for t in 0..10u64 {
+        for &handle in &handles {
+            let bit = if (t + handle as u64).is_multiple_of(2) {
+                '0'
+            } else {
+                '1'
+            };
+            writer.emit_change(t, handle, SignalValue::Bit(bit))?;
+        }
+    }
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let block = reader
+        .next_vc_block()?
+        .expect("value-change block must be present");
+    let geom = reader.geometry().expect("geometry present");
+
+    let wanted: HashSet<u32> = [handles[2], alias].into_iter().collect();
+    let mut events = Vec::new();
+    for evt in block.changes_filtered(geom, reader.header().time_zero, &wanted)? {
+        let evt = evt?;
+        events.push((evt.timestamp, evt.handle, evt.alias_of));
+    }
+
+    assert_eq!(events.len(), 20);
+    assert!(events.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    assert!(events.iter().all(|(_, handle, _)| wanted.contains(handle)));
+    assert_eq!(
+        events
+            .iter()
+            .filter(|(_, handle, alias_of)| *handle == alias && *alias_of == Some(handles[7]))
+            .count(),
+        10
+    );
+
+    Ok(())
+}
+
+#[test]
+fn windowed_changes_carry_state_into_range() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let fast = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "fast",
+        GeomEntry::Fixed(1),
+    )?;
+    let slow = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "slow",
+        GeomEntry::Fixed(1),
+    )?;
+    let once = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "once",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "windowed".into(),
+        end_time: 99,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    for t in 0..100u64 {
+        let bit = if t.is_multiple_of(2) { '0' } else { '1' };
+        writer.emit_change(t, fast, SignalValue::Bit(bit))?;
+        if t.is_multiple_of(7) {
+            writer.emit_change(t, slow, SignalValue::Bit(bit))?;
+        }
+    }
+    writer.emit_change(3, once, SignalValue::Bit('z'))?;
+
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let mut all = Vec::new();
+    for evt in reader.next_value_changes()?.expect("block present") {
+        let evt = evt?;
+        all.push((evt.timestamp, evt.handle, evt.value.into_owned()));
+    }
+
+    let (start, end) = (20u64, 40u64);
+    let mut expected = Vec::new();
+    for handle in [fast, slow, once] {
+        let touched_at_start = all.iter().any(|(t, h, _)| *t == start && *h == handle);
+        let carried = all.iter().rfind(|(t, h, _)| *t < start && *h == handle);
+        if let (false, Some((_, _, value))) = (touched_at_start, carried) {
+            expected.push((start, handle, value.clone()));
+        }
+    }
+    expected.extend(
+        all.iter()
+            .filter(|(t, _, _)| (start..=end).contains(t))
+            .cloned(),
+    );
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut windowed = Vec::new();
+    for evt in reader
+        .next_value_changes()?
+        .expect("block present")
+        .within(start, end)
+    {
+        let evt = evt?;
+        windowed.push((evt.timestamp, evt.handle, evt.value.into_owned()));
+    }
+
+    assert!(windowed.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    assert!(windowed.iter().all(|(t, _, _)| (start..=end).contains(t)));
+    assert!(windowed.contains(&(start, once, SignalValue::Bit('z'))));
+    let sort = |events: &mut Vec<(u64, u32, SignalValue<'static>)>| {
+        events.sort_by_key(|(t, h, _)| (*t, *h));
+    };
+    sort(&mut expected);
+    sort(&mut windowed);
+    assert_eq!(windowed, expected);
+
+    Ok(())
+}
+
+#[test]
+fn windowed_changes_carry_values_from_earlier_blocks() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let steady = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "steady",
+        GeomEntry::Fixed(1),
+    )?;
+    let toggle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "toggle",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, steady, SignalValue::Bit('1'))?;
+    writer.flush()?;
+    for time in 10..20u64 {
+        let bit = if time.is_multiple_of(2) { '0' } else { '1' };
+        writer.emit_change(time, toggle, SignalValue::Bit(bit))?;
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let (start, end) = (15u64, 17u64);
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let index = reader.build_block_index()?;
+    let before = index[1].begin_time - 1;
+    let prior = [steady, toggle]
+        .into_iter()
+        .map(|handle| {
+            Ok(reader
+                .value_at(handle, before)?
+                .map(|value| (handle, value)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(
+        prior,
+        [
+            Some((steady, SignalValue::Bit('1'))),
+            Some((toggle, SignalValue::Bit('x'))),
+        ]
+    );
+
+    reader.next_vc_block()?;
+    let block_only = reader
+        .next_value_changes()?
+        .expect("second block present")
+        .within(start, end)
+        .map(|change| change.map(|change| (change.timestamp, change.handle)))
+        .collect::<wavefst::Result<Vec<_>>>()?;
+    assert!(block_only.iter().all(|(_, handle)| *handle != steady));
+
+    reader.seek_to_time(10)?;
+    let seeded = reader
+        .next_value_changes()?
+        .expect("second block present")
+        .within(start, end)
+        .with_prior_values(prior.into_iter().flatten())
+        .map(|change| {
+            change.map(|change| (change.timestamp, change.handle, change.value.into_owned()))
+        })
+        .collect::<wavefst::Result<Vec<_>>>()?;
+    assert_eq!(
+        seeded,
+        [
+            (15, steady, SignalValue::Bit('1')),
+            (15, toggle, SignalValue::Bit('1')),
+            (16, toggle, SignalValue::Bit('0')),
+            (17, toggle, SignalValue::Bit('1')),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn next_time_table_matches_full_block_decode() -> Result<()> {
+    let (bytes, _) = three_block_trace()?;
+
+    let mut full = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let mut expected = Vec::new();
+    while let Some(block) = full.next_vc_block()? {
+        expected.push(block.time_table.timestamps);
+    }
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut actual = Vec::new();
+    while let Some(table) = reader.next_time_table()? {
+        actual.push(table.timestamps);
+    }
+
+    assert_eq!(expected.len(), 3);
+    assert_eq!(actual, expected);
+    assert_eq!(actual[1], vec![20, 30]);
+
+    Ok(())
+}
+
+#[test]
+fn frame_section_decodes_typed_initial_values() -> Result<()> {
+    let geom = GeomInfo::from_runs([
+        (1, GeomEntry::Fixed(1)),
+        (1, GeomEntry::Fixed(8)),
+        (1, GeomEntry::Variable),
+        (1, GeomEntry::Real),
+    ])?;
+    let mut data = b"1".to_vec();
+    data.extend_from_slice(b"10x0z011");
+    data.extend_from_slice(&1.25f64.to_be_bytes());
+    let frame = FrameSection {
+        data: data.clone(),
+        max_handle: 4,
+    };
+
+    assert_eq!(
+        frame.decode_values(&geom, Endianness::Big)?,
+        vec![
+            (1, SignalValue::Bit('1')),
+            (2, SignalValue::Vector("10x0z011".into())),
+            (4, SignalValue::Real(1.25)),
+        ]
+    );
+
+    let truncated = FrameSection {
+        data: data[..5].to_vec(),
+        max_handle: 4,
+    };
+    assert!(truncated.decode_values(&geom, Endianness::Big).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn owned_changes_outlive_the_reader() -> Result<()> {
+    let (bytes, handle) = three_block_trace()?;
+
+    let mut collected: Vec<ValueChange<'static>> = Vec::new();
+    {
+        let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+        while let Some(block) = reader.next_value_changes()? {
+            collected.extend(block.owned().collect::<wavefst::Result<Vec<_>>>()?);
+        }
+    }
+
+    let timeline: Vec<(u64, u32, SignalValue<'static>)> = collected
+        .into_iter()
+        .map(|change| (change.timestamp, change.handle, change.value))
+        .collect();
+    assert_eq!(timeline.len(), 6);
+    assert_eq!(timeline[0], (0, handle, SignalValue::Bit('1')));
+    assert_eq!(timeline[5], (50, handle, SignalValue::Bit('0')));
+    assert!(timeline.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+    Ok(())
+}
+
+#[test]
+fn self_referential_alias_is_a_decode_error() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .chain_compression(ChainCompression::Raw)
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "sig_alias", sig)?;
+    writer.end_scope()?;
+    writer.write_header(Header {
+        version: "alias-cycle".into(),
+        end_time: 0,
+        vc_section_count: 1,
+        ..Header::default()
+    })?;
+    writer.emit_change(0, sig, SignalValue::Bit('1'))?;
+    let mut bytes = writer.finish()?.into_inner();
+
+    // Index: data for handle 1, then `0, 1` aliasing handle 2 to handle 1, followed by the
+    // 8-byte index length. Rewrite the alias target so handle 2 points at itself.
+    let mut needle = vec![0x03, 0x00, 0x01];
+    needle.extend_from_slice(&3u64.to_be_bytes());
+    let at = bytes
+        .windows(needle.len())
+        .position(|window| window == needle.as_slice())
+        .expect("chain index must be present");
+    bytes[at + 2] = 0x02;
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let err = reader
+        .next_vc_block()
+        .expect_err("self-alias must not decode");
+    assert!(err.to_string().contains("alias cycle"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn big_endian_reals_decode_via_header_marker() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .chain_compression(ChainCompression::Raw)
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let real = writer.add_variable(VarType::VcdReal, VarDir::Implicit, "r", GeomEntry::Real)?;
+    writer.end_scope()?;
+    writer.write_header(Header {
+        version: "big-endian".into(),
+        end_time: 10,
+        vc_section_count: 1,
+        ..Header::default()
+    })?;
+    writer.emit_change(0, real, SignalValue::Real(1.5))?;
+    writer.emit_change(10, real, SignalValue::Real(-6.25))?;
+    let mut bytes = writer.finish()?.into_inner();
+
+    // Rewrite the file as a big-endian producer would: the endian marker after the block tag,
+    // section length, and start/end times, plus every stored real.
+    let native = Endianness::native();
+    let swapped = match native {
+        Endianness::Little => Endianness::Big,
+        Endianness::Big => Endianness::Little,
+    };
+    let marker = 1 + 8 + 8 + 8;
+    bytes[marker..marker + 8].reverse();
+    for value in [f64::NAN, 1.5, -6.25] {
+        let needle = native.f64_to_bytes(value);
+        let mut found = false;
+        let mut at = 0;
+        while let Some(pos) = bytes[at..]
+            .windows(8)
+            .position(|window| window == needle.as_slice())
+        {
+            bytes[at + pos..at + pos + 8].copy_from_slice(&swapped.f64_to_bytes(value));
+            at += pos + 8;
+            found = true;
+        }
+        assert!(found || value.is_nan(), "real {value} not stored raw");
+    }
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.header().endianness, swapped);
+    let values: Vec<(u64, SignalValue<'static>)> = reader
+        .all_value_changes()?
+        .map(|change| change.map(|change| (change.timestamp, change.value)))
+        .collect::<wavefst::Result<_>>()?;
+    assert_eq!(
+        values,
+        vec![(0, SignalValue::Real(1.5)), (10, SignalValue::Real(-6.25)),]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn truncated_final_block_is_tolerated_on_request() -> Result<()> {
+    let (bytes, handle) = three_block_trace()?;
+    let second = ReaderBuilder::new(Cursor::new(bytes.clone()))
+        .build()?
+        .build_block_index()?[1]
+        .offset;
+    let truncated = bytes[..second as usize + 20].to_vec();
+
+    let mut strict = ReaderBuilder::new(Cursor::new(truncated.clone())).build()?;
+    assert!(strict.next_vc_block()?.is_some());
+    assert!(strict.next_vc_block().is_err());
+
+    let mut reader = ReaderBuilder::new(Cursor::new(truncated))
+        .tolerate_truncation(true)
+        .build()?;
+    let mut recovered = Vec::new();
+    while let Some(block) = reader.next_value_changes()? {
+        for change in block {
+            let change = change?;
+            recovered.push((change.timestamp, change.handle, change.value.into_owned()));
+        }
+    }
+    assert_eq!(
+        recovered,
+        vec![
+            (0, handle, SignalValue::Bit('1')),
+            (10, handle, SignalValue::Bit('0')),
+        ]
+    );
+    assert!(reader.next_vc_block()?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn max_block_memory_rejects_oversized_blocks() -> Result<()> {
+    let (mut bytes, _) = three_block_trace()?;
+    let first = ReaderBuilder::new(Cursor::new(bytes.clone()))
+        .build()?
+        .build_block_index()?[0]
+        .offset as usize;
+    // tag, section length, begin time, end time, then required_memory
+    let memory_at = first + 1 + 8 + 16;
+    bytes[memory_at..memory_at + 8].copy_from_slice(&(1u64 << 60).to_be_bytes());
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes))
+        .max_block_memory(Some(64 << 20))
+        .build()?;
+    let err = reader.next_vc_block().unwrap_err();
+    assert!(err.to_string().contains("byte limit"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn verify_reports_corrupted_blocks() -> Result<()> {
+    let (bytes, _) = three_block_trace()?;
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let report = reader.verify()?;
+    assert_eq!(report.blocks_checked, 3);
+    assert!(report.is_ok(), "{:?}", report.failures);
+    assert!(reader.next_vc_block()?.is_some());
+
+    let offsets: Vec<u64> = reader
+        .build_block_index()?
+        .iter()
+        .map(|entry| entry.offset)
+        .collect();
+    let mut corrupted = bytes;
+    // Low byte of the second block's time-section uncompressed length, which sits 24 bytes
+    // before the next block's tag.
+    corrupted[offsets[2] as usize - 17] ^= 0x40;
+
+    let mut reader = ReaderBuilder::new(Cursor::new(corrupted)).build()?;
+    let report = reader.verify()?;
+    assert_eq!(report.blocks_checked, 3);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].offset, offsets[1]);
+
+    let report = reader.verify_with(true)?;
+    assert_eq!(report.blocks_checked, 2);
+    assert_eq!(report.failures.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn chain_bytes_exposes_uncompressed_chains() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    let real = writer.add_variable(VarType::VcdReal, VarDir::Implicit, "real", GeomEntry::Real)?;
+    let alias = writer.add_alias(VarType::VcdReal, VarDir::Implicit, "real_alias", real)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    for (time, value) in [(0u64, 1.5f64), (5, 2.5), (9, -4.0)] {
+        writer.emit_change(
+            time,
+            bit,
+            SignalValue::Bit(if time % 2 == 0 { '0' } else { '1' }),
+        )?;
+        writer.emit_change(time, real, SignalValue::Real(value))?;
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let block = reader.next_vc_block()?.expect("one block");
+    let chain = block.chain_bytes(real).expect("real handle has a chain");
+
+    // Real chains are a `(delta << 1) | 1` varint followed by the 8-byte value.
+    let mut decoded = Vec::new();
+    let mut time_index = 0;
+    let mut rest = chain.as_ref();
+    while !rest.is_empty() {
+        let (marker, used) = decode_varint_with_len(rest)?;
+        time_index += marker >> 1;
+        let raw: [u8; 8] = rest[used..used + 8].try_into()?;
+        decoded.push((
+            block.time_table.timestamps[time_index as usize],
+            f64::from_ne_bytes(raw),
+        ));
+        rest = &rest[used + 8..];
+    }
+
+    let geom = reader.geometry().expect("geometry loaded").clone();
+    let expected: Vec<(u64, f64)> = block
+        .changes(&geom, 0)?
+        .filter_map(|change| change.ok())
+        .filter(|change| change.handle == real)
+        .filter_map(|change| match change.value {
+            SignalValue::Real(value) => Some((change.timestamp, value)),
+            _ => None,
+        })
+        .collect();
+    assert!(!decoded.is_empty());
+    assert_eq!(decoded, expected);
+    assert_eq!(block.chain_bytes(alias), Some(chain.clone()));
+    assert_ne!(block.chain_bytes(bit), Some(chain));
+    assert_eq!(block.chain_bytes(0), None);
+    assert_eq!(block.chain_bytes(99), None);
+
+    Ok(())
+}
+
+fn real_trace() -> Result<(Vec<u8>, u32)> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+        .chain_compression(ChainCompression::Raw)
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let real = writer.add_variable(VarType::VcdReal, VarDir::Implicit, "real", GeomEntry::Real)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    for (time, value) in [(0u64, 0.0f64), (3, 1234.5678), (7, -2.0), (9, 0.0)] {
+        writer.emit_change(time, real, SignalValue::Real(value))?;
+    }
+    Ok((writer.finish()?.into_inner(), real))
+}
+
+#[test]
+fn real_changes_always_decode_as_reals() -> Result<()> {
+    let (bytes, real) = real_trace()?;
+    let changes = collect_changes(bytes.clone())?;
+    let values: Vec<f64> = changes
+        .iter()
+        .filter(|change| change.handle == real)
+        .map(|change| match change.value {
+            SignalValue::Real(value) => Ok(value),
+            ref other => Err(anyhow::anyhow!("expected a real, got {other:?}")),
+        })
+        .collect::<Result<_>>()?;
+    assert_eq!(values, [0.0, 1234.5678, -2.0, 0.0]);
+
+    // Clearing the low marker bit selects the packed form, which cannot hold a double.
+    let pattern = 1234.5678f64.to_ne_bytes();
+    let at = bytes
+        .windows(8)
+        .position(|window| window == pattern)
+        .expect("raw chain holds the double");
+    let mut corrupted = bytes;
+    corrupted[at - 1] &= !1;
+    assert!(collect_changes(corrupted).is_err());
+
+    Ok(())
+}
+
+fn collect_changes(bytes: Vec<u8>) -> Result<Vec<ValueChange<'static>>> {
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut changes = Vec::new();
+    while let Some(block) = reader.next_value_changes()? {
+        changes.extend(block.owned().collect::<wavefst::Result<Vec<_>>>()?);
+    }
+    Ok(changes)
+}
+
+#[test]
+fn apply_time_zero_can_be_disabled() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let handle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header {
+        time_zero: 1_000,
+        ..Header::default()
+    })?;
+    for (time, bit) in [(0u64, '0'), (5, '1'), (9, '0')] {
+        writer.emit_change(time, handle, SignalValue::Bit(bit))?;
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let timestamps = |apply: bool| -> Result<Vec<u64>> {
+        let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone()))
+            .apply_time_zero(apply)
+            .build()?;
+        let mut times = Vec::new();
+        while let Some(block) = reader.next_value_changes()? {
+            for change in block {
+                times.push(change?.timestamp);
+            }
+        }
+        Ok(times)
+    };
+    assert_eq!(timestamps(true)?, [1_000, 1_005, 1_009]);
+    assert_eq!(timestamps(false)?, [0, 5, 9]);
+
+    Ok(())
+}
+
+#[test]
+fn dyn_alias_block_resolves_aliases() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    let idle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "idle",
+        GeomEntry::Fixed(1),
+    )?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(4),
+    )?;
+    let sig_alias = writer.add_alias(VarType::VcdWire, VarDir::Implicit, "sig_alias", sig)?;
+    let bus_alias = writer.add_alias(VarType::VcdWire, VarDir::Implicit, "bus_alias", bus)?;
+    writer.end_scope()?;
+    writer.write_header(Header {
+        version: "dyn-alias".into(),
+        ..Header::default()
+    })?;
+    writer.emit_change(0, sig, SignalValue::Bit('1'))?;
+    writer.emit_change(0, bus, SignalValue::Vector("10x1".into()))?;
+    writer.emit_change(10, sig, SignalValue::Bit('0'))?;
+    let legacy = writer.finish()?.into_inner();
+
+    // Format 5 stores its chain index with the same `0, alias` convention as format 1, so
+    // relabelling the block must not change what is decoded.
+    let offset = ReaderBuilder::new(Cursor::new(legacy.clone()))
+        .build()?
+        .build_block_index()?[0]
+        .offset as usize;
+    assert_eq!(legacy[offset], u8::from(BlockType::VcData));
+    let mut dyn_alias = legacy.clone();
+    dyn_alias[offset] = u8::from(BlockType::VcDataDynAlias);
+
+    let mut reader = ReaderBuilder::new(Cursor::new(dyn_alias.clone())).build()?;
+    let block = reader.next_vc_block()?.expect("one block");
+    assert_eq!(block.index.resolve_alias(sig_alias), sig);
+    assert_eq!(block.index.resolve_alias(bus_alias), bus);
+    assert_eq!(block.index.resolve_alias(idle), idle);
+
+    let changes: Vec<_> = collect_changes(dyn_alias)?
+        .into_iter()
+        .map(|change| {
+            (
+                change.timestamp,
+                change.handle,
+                change.alias_of,
+                change.value,
+            )
+        })
+        .collect();
+    let expected: Vec<_> = collect_changes(legacy)?
+        .into_iter()
+        .map(|change| {
+            (
+                change.timestamp,
+                change.handle,
+                change.alias_of,
+                change.value,
+            )
+        })
+        .collect();
+    assert_eq!(changes, expected);
+    assert!(changes.contains(&(0, sig_alias, Some(sig), SignalValue::Bit('1'))));
+    assert!(changes.contains(&(10, sig_alias, Some(sig), SignalValue::Bit('0'))));
+    assert!(changes.contains(&(0, bus_alias, Some(bus), SignalValue::Vector("10x1".into()))));
+
+    Ok(())
+}
+
+#[test]
+fn change_count_matches_iterated_changes() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(6),
+    )?;
+    let real = writer.add_variable(VarType::VcdReal, VarDir::Implicit, "real", GeomEntry::Real)?;
+    let text = writer.add_variable(
+        VarType::GenString,
+        VarDir::Implicit,
+        "text",
+        GeomEntry::Variable,
+    )?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "bus_alias", bus)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    for time in 0..20u64 {
+        writer.emit_change(
+            time,
+            bit,
+            SignalValue::Bit(if time % 2 == 0 { '0' } else { '1' }),
+        )?;
+        if time % 3 == 0 {
+            let value = if time % 2 == 0 { "10x1z0" } else { "101100" };
+            writer.emit_change(time, bus, SignalValue::Vector(value.into()))?;
+        }
+        if time % 5 == 0 {
+            writer.emit_change(time, real, SignalValue::Real(time as f64 * 0.5))?;
+            writer.emit_change(
+                time,
+                text,
+                SignalValue::Bytes(format!("t{time}").into_bytes().into()),
+            )?;
+        }
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let block = reader.next_vc_block()?.expect("one block");
+    let geom = reader.geometry().expect("geometry loaded").clone();
+    let yielded = block
+        .changes(&geom, 0)?
+        .collect::<wavefst::Result<Vec<_>>>()?;
+    assert_eq!(block.change_count(&geom)?, yielded.len());
+    assert!(yielded.iter().any(|change| change.alias_of == Some(bus)));
+
+    Ok(())
+}
+
+#[test]
+fn size_hint_is_a_lower_bound_on_remaining_changes() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(3),
+    )?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "bus_alias", bus)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    for time in 0..12u64 {
+        let bit = if time % 2 == 0 { '0' } else { '1' };
+        writer.emit_change(time, clk, SignalValue::Bit(bit))?;
+        if time % 4 == 1 {
+            writer.emit_change(time, bus, SignalValue::Vector("1x0".into()))?;
+        }
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let block = reader.next_vc_block()?.expect("one block");
+    let geom = reader.geometry().expect("geometry loaded").clone();
+    let total = block.change_count(&geom)?;
+    let mut changes = block.changes(&geom, 0)?;
+    let (lower, upper) = changes.size_hint();
+    assert!(lower > 0);
+    assert!(lower <= total, "{lower} > {total}");
+    assert_eq!(upper, None);
+
+    let mut remaining = total;
+    while changes.next().transpose()?.is_some() {
+        remaining -= 1;
+        assert!(changes.size_hint().0 <= remaining);
+    }
+    assert_eq!(remaining, 0);
+    assert_eq!(changes.size_hint().0, 0);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_pack_marker_reports_its_offset() -> Result<()> {
+    let bytes = sample_trace()?;
+    let block_offset = ReaderBuilder::new(Cursor::new(bytes.clone()))
+        .build()?
+        .build_block_index()?[0]
+        .offset;
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let header = reader.next_vc_block()?.expect("first block").header;
+
+    let mut varints = Vec::new();
+    for value in [
+        header.frame_uncompressed_len,
+        header.frame_compressed_len,
+        header.frame_max_handle,
+    ] {
+        encode_varint(value, &mut varints);
+    }
+    let frame_end = 1 + 8 + 24 + varints.len() as u64 + header.frame_compressed_len;
+    let mut vc_max = Vec::new();
+    encode_varint(header.vc_max_handle, &mut vc_max);
+    let marker_offset = block_offset + frame_end + vc_max.len() as u64;
+    assert_eq!(bytes[marker_offset as usize], header.pack_marker.marker);
+
+    let mut corrupted = bytes;
+    corrupted[marker_offset as usize] = b'?';
+    let mut reader = ReaderBuilder::new(Cursor::new(corrupted)).build()?;
+    let err = reader.next_vc_block().expect_err("marker is unknown");
+    assert_eq!(err.offset(), Some(marker_offset));
+    assert!(err.to_string().contains("unknown pack marker 3f"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn initial_values_match_first_iterated_values() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(4),
+    )?;
+    let real = writer.add_variable(VarType::VcdReal, VarDir::Implicit, "real", GeomEntry::Real)?;
+    let text = writer.add_variable(
+        VarType::GenString,
+        VarDir::Implicit,
+        "text",
+        GeomEntry::Variable,
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, bit, SignalValue::Bit('1'))?;
+    writer.emit_change(0, bus, SignalValue::Vector("1010".into()))?;
+    writer.emit_change(0, real, SignalValue::Real(2.5))?;
+    writer.emit_change(0, text, SignalValue::Bytes(b"boot".as_slice().into()))?;
+    writer.flush()?;
+    for time in 1..10u64 {
+        writer.emit_change(
+            time,
+            bit,
+            SignalValue::Bit(if time % 2 == 0 { '1' } else { '0' }),
+        )?;
+        writer.emit_change(time, bus, SignalValue::Vector("0x01".into()))?;
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes))
+        .expand_packed_bits(true)
+        .build()?;
+    let initial = reader.initial_values()?;
+
+    let mut first = std::collections::BTreeMap::new();
+    for change in reader.all_value_changes()? {
+        let change = change?;
+        first.entry(change.handle).or_insert(change.value);
+    }
+    assert_eq!(
+        initial
+            .iter()
+            .map(|(handle, _)| *handle)
+            .collect::<Vec<_>>(),
+        [bit, bus, real]
+    );
+    for (handle, value) in initial {
+        assert_eq!(Some(&value), first.get(&handle), "handle {handle}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn initial_values_use_changes_at_the_first_block_begin() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let idle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "idle",
+        GeomEntry::Fixed(1),
+    )?;
+    let late = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "late",
+        GeomEntry::Fixed(1),
+    )?;
+    let floating = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "floating",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, clk, SignalValue::Bit('0'))?;
+    writer.emit_change(0, idle, SignalValue::Bit('1'))?;
+    writer.emit_change(5, clk, SignalValue::Bit('1'))?;
+    writer.emit_change(10, late, SignalValue::Bit('1'))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let initial = reader.initial_values()?;
+    assert_eq!(
+        initial,
+        [
+            (clk, SignalValue::Bit('0')),
+            (idle, SignalValue::Bit('1')),
+            (floating, SignalValue::Bit('x')),
+        ]
+    );
+    assert!(initial.iter().all(|(handle, _)| *handle != late));
+    assert_eq!(reader.value_at(late, 0)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn timestamps_only_time_table_matches_full_decode() -> Result<()> {
+    let deltas = [0u64, 3, 1, 250, 70_000, 1, 1 << 40];
+    let mut raw = Vec::new();
+    for delta in deltas {
+        encode_varint(delta, &mut raw);
+    }
+    let mut packs = vec![PackType::None];
+    #[cfg(feature = "gzip")]
+    packs.push(PackType::Zlib);
+    for pack in packs {
+        let encoded = encode_time_section(raw.clone(), deltas.len() as u64, pack, None)?;
+        let section = TimeSection {
+            uncompressed_len: encoded.uncompressed_len,
+            compressed_len: encoded.compressed_len,
+            item_count: encoded.item_count,
+        };
+        let full = TimeTable::decode(&section, encoded.payload.clone())?;
+        let light = TimeTable::decode_timestamps_only(&section, encoded.payload)?;
+        assert_eq!(full.deltas, deltas);
+        assert_eq!(light.timestamps(), full.timestamps());
+        assert!(light.deltas.is_empty());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn decode_all_parallel_matches_serial_iteration() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+        .max_pending_changes(Some(50))
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let count = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "count",
+        GeomEntry::Fixed(8),
+    )?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "clk_alias", clk)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    for time in 0..300u64 {
+        writer.emit_change(
+            time,
+            clk,
+            SignalValue::Bit(if time % 2 == 0 { '1' } else { '0' }),
+        )?;
+        if time % 3 == 0 {
+            writer.emit_change(time, count, SignalValue::from_u64(time / 3, 8))?;
+        }
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert!(reader.build_block_index()?.len() > 1);
+    let parallel = reader.decode_all_parallel()?;
+    let serial = reader
+        .all_value_changes()?
+        .collect::<wavefst::Result<Vec<_>>>()?;
+    let key = |change: &ValueChange<'static>| {
+        (
+            change.timestamp,
+            change.handle,
+            change.alias_of,
+            change.value.clone(),
+        )
+    };
+    assert_eq!(
+        parallel.iter().map(key).collect::<Vec<_>>(),
+        serial.iter().map(key).collect::<Vec<_>>()
+    );
+    assert!(!parallel.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn decompressor_for_none_passes_bytes_through() -> Result<()> {
+    let input = b"\x00\x01chain bytes\xff".to_vec();
+    let mut out = Vec::new();
+    decompressor_for(PackType::None)?.decompress_exact(&input, input.len(), &mut out)?;
+    assert_eq!(out, input);
+    assert!(decompressor_for(PackType::Custom(b'Q')).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn decompressor_for_lz4_decodes_raw_blocks() -> Result<()> {
+    let input = b"abcabcabcabcabcabcabcabc".repeat(4);
+    let compressed = lz4_flex::block::compress(&input);
+    let mut out = Vec::new();
+    decompressor_for(PackType::Lz4)?.decompress_exact(&compressed, input.len(), &mut out)?;
+    assert_eq!(out, input);
+    Ok(())
+}
+
+#[test]
+fn chain_index_layouts_round_trip_random_entries() -> Result<()> {
+    let mut state = 0xD1B5_4A32_D192_ED03u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for _ in 0..200 {
+        let len = (next() % 64) as usize;
+        let mut offset = 0u64;
+        let mut last_target = 1u32;
+        let entries: Vec<ChainIndexEntry> = (0..len)
+            .map(|_| match next() % 4 {
+                0 => ChainIndexEntry::Empty,
+                1 => {
+                    offset += 1 + next() % 1_000;
+                    ChainIndexEntry::Data { offset }
+                }
+                2 => ChainIndexEntry::Alias {
+                    target: last_target,
+                },
+                _ => {
+                    last_target = 1 + (next() % 500) as u32;
+                    ChainIndexEntry::Alias {
+                        target: last_target,
+                    }
+                }
+            })
+            .collect();
+
+        let dyn_alias2 = encode_chain_index_dyn_alias2(&entries)?;
+        assert_eq!(
+            decode_chain_index_entries(BlockType::VcDataDynAlias2, &dyn_alias2)?,
+            entries
+        );
+        let legacy = encode_chain_index(&entries)?;
+        assert_eq!(
+            decode_chain_index_entries(BlockType::VcData, &legacy)?,
+            entries
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn dyn_alias2_chain_index_rejects_non_increasing_offsets() {
+    let entries = [
+        ChainIndexEntry::Data { offset: 4 },
+        ChainIndexEntry::Data { offset: 4 },
+    ];
+    assert!(encode_chain_index_dyn_alias2(&entries).is_err());
+}
+
+#[test]
+fn header_write_to_round_trips_through_read() -> Result<()> {
+    for endianness in [Endianness::Little, Endianness::Big] {
+        let header = Header {
+            section_length: 329,
+            start_time: 12,
+            end_time: 3_456,
+            memory_used: 1 << 20,
+            scope_count: 4,
+            var_count: 9,
+            max_handle: 7,
+            vc_section_count: 3,
+            timescale_exponent: -12,
+            version: "round-trip".into(),
+            date: "Sat Oct 17 12:00:00 2026".into(),
+            file_type: 2,
+            time_zero: 500,
+            endianness,
+        };
+        let mut bytes = Vec::new();
+        header.write_to(&mut bytes)?;
+        assert_eq!(bytes.len(), 1 + 329);
+
+        let parsed = Header::read(&mut Cursor::new(bytes))?;
+        assert_eq!(parsed.section_length, header.section_length);
+        assert_eq!(parsed.start_time, header.start_time);
+        assert_eq!(parsed.end_time, header.end_time);
+        assert_eq!(parsed.memory_used, header.memory_used);
+        assert_eq!(parsed.scope_count, header.scope_count);
+        assert_eq!(parsed.var_count, header.var_count);
+        assert_eq!(parsed.max_handle, header.max_handle);
+        assert_eq!(parsed.vc_section_count, header.vc_section_count);
+        assert_eq!(parsed.timescale_exponent, header.timescale_exponent);
+        assert_eq!(parsed.version, header.version);
+        assert_eq!(parsed.date, header.date);
+        assert_eq!(parsed.file_type, header.file_type);
+        assert_eq!(parsed.time_zero, header.time_zero);
+        assert_eq!(parsed.endianness, endianness);
+    }
+    Ok(())
+}
+
+/// Splits a trace into its top-level blocks, each returned with its tag and length word.
+fn split_blocks(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let length = u64::from_be_bytes(bytes[offset + 1..offset + 9].try_into().unwrap());
+        let end = offset + 1 + length as usize;
+        blocks.push(&bytes[offset..end]);
+        offset = end;
+    }
+    blocks
+}
+
+#[test]
+fn trailing_hierarchy_is_loaded_after_value_changes() -> Result<()> {
+    let bytes = sample_trace()?;
+    let is_hierarchy = |block: &[u8]| {
+        matches!(
+            BlockType::try_from(block[0]),
+            Ok(BlockType::Hierarchy | BlockType::HierarchyLz4 | BlockType::HierarchyLz4Duo)
+        )
+    };
+    let blocks = split_blocks(&bytes);
+    let mut reordered: Vec<u8> = blocks
+        .iter()
+        .filter(|block| !is_hierarchy(block))
+        .flat_map(|block| block.iter().copied())
+        .collect();
+    let hierarchy = blocks
+        .iter()
+        .find(|block| is_hierarchy(block))
+        .expect("writer emits a hierarchy block");
+    reordered.extend_from_slice(hierarchy);
+
+    let mut reader = ReaderBuilder::new(Cursor::new(reordered)).build()?;
+    assert!(reader.hierarchy().is_none());
+    let mut blocks_read = 0;
+    while reader.next_vc_block()?.is_some() {
+        blocks_read += 1;
+    }
+    assert_eq!(blocks_read, 2);
+    let hier = reader
+        .hierarchy()
+        .expect("trailing hierarchy block is loaded");
+    assert_eq!(hier.resolve_path("top.sig"), Some(1));
+
+    Ok(())
+}
+
+#[test]
+fn summary_leaves_alias_count_unknown_until_hierarchy_loads() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let data = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "data",
+        GeomEntry::Fixed(8),
+    )?;
+    writer.add_alias(VarType::VcdReg, VarDir::Implicit, "data_alias", data)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, data, SignalValue::from_u64(7, 8))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    assert_eq!(reader.summary().alias_count, Some(1));
+
+    let is_hierarchy = |block: &[u8]| {
+        matches!(
+            BlockType::try_from(block[0]),
+            Ok(BlockType::Hierarchy | BlockType::HierarchyLz4 | BlockType::HierarchyLz4Duo)
+        )
+    };
+    let blocks = split_blocks(&bytes);
+    let mut reordered: Vec<u8> = blocks
+        .iter()
+        .filter(|block| !is_hierarchy(block))
+        .flat_map(|block| block.iter().copied())
+        .collect();
+    reordered.extend(
+        blocks
+            .iter()
+            .filter(|block| is_hierarchy(block))
+            .flat_map(|block| block.iter().copied()),
+    );
+
+    let mut reader = ReaderBuilder::new(Cursor::new(reordered)).build()?;
+    let from_header = reader.summary();
+    assert_eq!(from_header.var_count, 2);
+    assert_eq!(from_header.max_handle, 2);
+    assert_eq!(from_header.alias_count, None);
+    reader.skip_remaining()?;
+    assert_eq!(reader.summary().alias_count, Some(1));
+
+    Ok(())
+}
+
+#[test]
+fn grouped_changes_collect_each_timestamp() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let rst = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "rst",
+        GeomEntry::Fixed(1),
+    )?;
+    let clk_alias = writer.add_alias(VarType::VcdWire, VarDir::Implicit, "clk_alias", clk)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, clk, SignalValue::Bit('0'))?;
+    writer.emit_change(0, rst, SignalValue::Bit('1'))?;
+    writer.emit_change(5, clk, SignalValue::Bit('1'))?;
+    writer.emit_change(8, rst, SignalValue::Bit('0'))?;
+    writer.emit_change(8, clk, SignalValue::Bit('0'))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let groups: Vec<(u64, Vec<u32>)> = reader
+        .next_value_changes()?
+        .expect("one value-change block")
+        .grouped()
+        .map(|group| {
+            group.map(|(time, changes)| {
+                let mut handles: Vec<u32> = changes.iter().map(|change| change.handle).collect();
+                handles.sort_unstable();
+                (time, handles)
+            })
+        })
+        .collect::<wavefst::Result<_>>()?;
+    assert_eq!(
+        groups,
+        [
+            (0, vec![clk, rst, clk_alias]),
+            (5, vec![clk, clk_alias]),
+            (8, vec![clk, rst, clk_alias]),
+        ]
+    );
+
+    Ok(())
+}
+
+/// Replaces the geometry block of `bytes` with an encoding of `geom`.
+fn with_geometry(bytes: &[u8], geom: &GeomInfo) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    for block in split_blocks(bytes) {
+        if block[0] == BlockType::Geometry as u8 {
+            out.push(BlockType::Geometry as u8);
+            geom.encode_block(false, None)?.write_to(&mut out)?;
+        } else {
+            out.extend_from_slice(block);
+        }
+    }
+    Ok(out)
+}
+
+#[test]
+fn validate_metadata_flags_geometry_mismatches() -> Result<()> {
+    let bytes = sample_trace()?;
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    reader.skip_remaining()?;
+    reader.validate_metadata()?;
+    let (reals, _) = real_trace()?;
+    let mut reader = ReaderBuilder::new(Cursor::new(reals)).build()?;
+    reader.skip_remaining()?;
+    reader.validate_metadata()?;
+
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bus = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(4),
+    )?;
+    writer.add_alias(VarType::VcdReg, VarDir::Implicit, "bus_alias", bus)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, bus, SignalValue::from_u64(3, 4))?;
+    let aliased = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(aliased)).build()?;
+    reader.skip_remaining()?;
+    reader.validate_metadata()?;
+
+    let widened = with_geometry(&bytes, &GeomInfo::from_runs([(1, GeomEntry::Fixed(4))])?)?;
+    let mut reader = ReaderBuilder::new(Cursor::new(widened)).build()?;
+    reader.skip_remaining()?;
+    let err = reader
+        .validate_metadata()
+        .expect_err("width disagrees with the hierarchy");
+    assert!(err.to_string().contains("'sig'"), "{err}");
+
+    let extra = with_geometry(&bytes, &GeomInfo::from_runs([(2, GeomEntry::Fixed(1))])?)?;
+    let mut reader = ReaderBuilder::new(Cursor::new(extra)).build()?;
+    reader.skip_remaining()?;
+    let err = reader
+        .validate_metadata()
+        .expect_err("entry count disagrees with the hierarchy");
+    assert!(err.to_string().contains("2 entries"), "{err}");
+
+    Ok(())
+}
+
+/// Source wrapper counting how often the reader seeks the underlying stream.
+struct SeekCounter<R> {
+    inner: R,
+    seeks: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl<R: std::io::Read> std::io::Read for SeekCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<R: std::io::Seek> std::io::Seek for SeekCounter<R> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.seeks.set(self.seeks.get() + 1);
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn prefetched_blocks_need_fewer_seeks() -> Result<()> {
+    let (bytes, _) = three_block_trace()?;
+    let read_blocks = |limit: Option<u64>| -> Result<(usize, Vec<Vec<u64>>)> {
+        let seeks = std::rc::Rc::new(std::cell::Cell::new(0));
+        let source = SeekCounter {
+            inner: Cursor::new(bytes.clone()),
+            seeks: seeks.clone(),
+        };
+        let mut reader = ReaderBuilder::new(source).prefetch_limit(limit).build()?;
+        seeks.set(0);
+        let mut times = Vec::new();
+        while let Some(block) = reader.next_vc_block()? {
+            times.push(block.time_table.timestamps.clone());
+        }
+        Ok((seeks.get(), times))
+    };
+
+    let (direct_seeks, direct) = read_blocks(None)?;
+    let (prefetched_seeks, prefetched) = read_blocks(Some(1 << 20))?;
+    assert_eq!(direct.len(), 3);
+    assert_eq!(prefetched, direct);
+    assert!(
+        prefetched_seeks * 2 < direct_seeks,
+        "{prefetched_seeks} seeks with prefetch, {direct_seeks} without"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn constant_signals_lists_handles_without_changes() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let tie = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "tie",
+        GeomEntry::Fixed(4),
+    )?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "tie_alias", tie)?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "clk_alias", clk)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    for t in 0..4u64 {
+        let bit = if t % 2 == 0 { '0' } else { '1' };
+        writer.emit_change(t, clk, SignalValue::Bit(bit))?;
+        if t == 1 {
+            writer.flush()?;
+        }
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.constant_signals()?, [tie]);
+    // The scan leaves the stream where it was.
+    assert_eq!(reader.all_value_changes()?.count(), 8);
+
+    Ok(())
+}
+
+#[test]
+fn handle_sizes_rank_largest_chains_first() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let small = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "small",
+        GeomEntry::Fixed(1),
+    )?;
+    let large = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "large",
+        GeomEntry::Fixed(32),
+    )?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "large_alias", large)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, small, SignalValue::Bit('1'))?;
+    for t in 0..64u64 {
+        let text: String = (0..32)
+            .map(|bit| if (t >> (bit % 6)) & 1 == 1 { 'x' } else { 'z' })
+            .collect();
+        writer.emit_change(t, large, SignalValue::Vector(text.into()))?;
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let block = reader.next_vc_block()?.expect("one block");
+    let sizes = block.handle_sizes();
+    assert_eq!(sizes.len(), 2);
+    assert_eq!(sizes[0].0, large);
+    assert_eq!(sizes[1].0, small);
+    assert!(sizes[0].1 > sizes[1].1);
+
+    Ok(())
+}
+
+#[test]
+fn strict_mode_rejects_inconsistent_block_layout() -> Result<()> {
+    let (bytes, _) = three_block_trace()?;
+    let read_all = |bytes: Vec<u8>, strict: bool| -> wavefst::Result<usize> {
+        let mut reader = ReaderBuilder::new(Cursor::new(bytes))
+            .strict(strict)
+            .build()?;
+        let mut blocks = 0;
+        while reader.next_vc_block()?.is_some() {
+            blocks += 1;
+        }
+        Ok(blocks)
+    };
+    assert_eq!(read_all(bytes.clone(), true)?, 3);
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let offset = reader.build_block_index()?[0].offset as usize;
+    let section_length = u64::from_be_bytes(bytes[offset + 1..offset + 9].try_into()?) as usize;
+    let block_end = offset + 1 + section_length;
+    let time_compressed_len =
+        u64::from_be_bytes(bytes[block_end - 16..block_end - 8].try_into()?) as usize;
+    let index_length_pos = block_end - 24 - time_compressed_len - 8;
+    // Growing or shrinking the recorded index length by a byte shifts the index boundary into
+    // the chains (or the chains into the index). Lenient parsing absorbs it; strict does not.
+    for delta in [1i64, -1] {
+        let mut corrupted = bytes.clone();
+        let field = &mut corrupted[index_length_pos..index_length_pos + 8];
+        let length = u64::from_be_bytes((&*field).try_into()?);
+        field.copy_from_slice(&length.wrapping_add_signed(delta).to_be_bytes());
+        assert_eq!(read_all(corrupted.clone(), false)?, 3);
+        let err = read_all(corrupted, true).expect_err("strict mode rejects the block");
+        assert!(
+            matches!(err, wavefst::Error::DecodeAt { .. }),
+            "delta {delta}: {err:?}"
+        );
+    }
+
+    Ok(())
+}
@@ -3,8 +3,8 @@ use std::fs::{File, read};
 use std::path::PathBuf;
 
 use anyhow::Result;
-use wavefst::ReaderBuilder;
 use wavefst::encoding::decode_varint_with_len;
+use wavefst::{ReaderBuilder, TraceSummary};
 
 fn fixture_path() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/hdl-example.fst")
@@ -112,14 +112,16 @@ fn iterate_hdl_example_changes() -> Result<()> {
             .expect("expected value-change block");
 
         let mut count = 0usize;
+        let mut last_timestamp = 0u64;
         for change in &mut changes {
-            change?;
+            let change = change?;
+            assert!(change.timestamp >= last_timestamp);
+            assert!(change.timestamp <= 929);
+            last_timestamp = change.timestamp;
             count += 1;
         }
-        assert_eq!(
-            count, 0,
-            "fixture is expected to contain only the initial frame"
-        );
+        // The fixture stores its chain index in the dyn-alias2 form with sign-extended varints.
+        assert_eq!(count, 1049);
     }
 
     assert!(
@@ -133,3 +135,157 @@ fn iterate_hdl_example_changes() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg_attr(not(feature = "gzip"), ignore = "requires gzip feature")]
+#[test]
+fn skip_remaining_walks_hdl_example() -> Result<()> {
+    let path = fixture_path();
+    let file = File::open(&path)?;
+    let mut reader = ReaderBuilder::new(file).build()?;
+
+    reader.skip_remaining()?;
+
+    assert!(
+        reader.geometry().is_some(),
+        "geometry metadata should be loaded after walking the file"
+    );
+    assert!(
+        reader.hierarchy().is_some(),
+        "hierarchy metadata should be loaded after walking the file"
+    );
+    assert!(reader.next_vc_block()?.is_none());
+
+    Ok(())
+}
+
+#[cfg(all(feature = "mmap", feature = "gzip"))]
+#[test]
+fn mmap_reader_walks_hdl_example() -> Result<()> {
+    let mut mapped = wavefst::FstReader::from_mmap(fixture_path())?;
+    let mut buffered = ReaderBuilder::new(File::open(fixture_path())?).build()?;
+    assert_eq!(mapped.header().end_time, buffered.header().end_time);
+
+    let mut blocks = 0usize;
+    while let Some(block) = mapped.next_vc_block()? {
+        let expected = buffered
+            .next_vc_block()?
+            .expect("buffered reader should yield the same blocks");
+        assert_eq!(block.header.begin_time, expected.header.begin_time);
+        assert_eq!(block.header.end_time, expected.header.end_time);
+        assert_eq!(block.frame.as_slice(), expected.frame.as_slice());
+        blocks += 1;
+    }
+    assert!(
+        blocks > 0,
+        "fixture should contain at least one value-change block"
+    );
+    assert!(buffered.next_vc_block()?.is_none());
+    assert!(mapped.hierarchy().is_some());
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "gzip"), ignore = "requires gzip feature")]
+#[test]
+fn vcd_export_loads_trailing_hierarchy() -> Result<()> {
+    let mut reader = ReaderBuilder::new(File::open(fixture_path())?).build()?;
+    assert!(reader.hierarchy().is_none());
+
+    let mut out = Vec::new();
+    wavefst::export::vcd::write_vcd(&mut reader, &mut out)?;
+    let vcd = String::from_utf8(out)?;
+
+    assert!(vcd.contains("$var wire 1 ! clk $end"));
+    let body = vcd.split("$enddefinitions $end\n").nth(1).unwrap();
+    assert!(body.starts_with("#0\n"));
+    assert!(body.contains("#5\n0!\n#10\n1!\n"));
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "gzip"), ignore = "requires gzip feature")]
+#[test]
+fn open_reads_fixture_from_path() -> Result<()> {
+    let mut reader = wavefst::FstReader::open(fixture_path())?;
+    let mut buffered = ReaderBuilder::new(File::open(fixture_path())?).build()?;
+    assert_eq!(reader.header().end_time, buffered.header().end_time);
+    assert_eq!(
+        reader.next_vc_block()?.map(|block| block.header.end_time),
+        buffered.next_vc_block()?.map(|block| block.header.end_time)
+    );
+
+    let options = wavefst::ReaderOptions {
+        eager_geometry: false,
+        ..Default::default()
+    };
+    let reader = wavefst::FstReader::open_with_options(fixture_path(), options)?;
+    assert!(!reader.options().eager_geometry);
+
+    let missing = wavefst::FstReader::open(fixture_path().with_extension("missing"));
+    assert!(matches!(missing, Err(wavefst::Error::Io(_))));
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "gzip"), ignore = "requires gzip feature")]
+#[test]
+fn summary_matches_header_and_hierarchy() -> Result<()> {
+    let mut reader = ReaderBuilder::new(File::open(fixture_path())?).build()?;
+    let header = reader.header().clone();
+    let from_header = reader.summary();
+    assert_eq!(from_header.scope_count, header.scope_count);
+    assert_eq!(from_header.var_count, header.var_count);
+    assert_eq!(from_header.max_handle, header.max_handle);
+    assert_eq!(from_header.start_time, header.start_time);
+    assert_eq!(from_header.end_time, header.end_time);
+    assert_eq!(from_header.timescale_exponent, header.timescale_exponent);
+    assert_eq!(from_header.vc_section_count, header.vc_section_count);
+    assert_eq!(from_header.alias_count, None);
+
+    reader.skip_remaining()?;
+    let hier = reader.hierarchy().expect("hierarchy loaded").clone();
+    let from_hierarchy = reader.summary();
+    assert_eq!(from_hierarchy.scope_count, hier.scopes.len() as u64);
+    assert_eq!(from_hierarchy.var_count, hier.variables.len() as u64);
+    assert_eq!(
+        from_hierarchy.alias_count,
+        Some(hier.variables.iter().filter(|var| var.is_alias).count() as u64)
+    );
+    assert_eq!(
+        TraceSummary {
+            alias_count: None,
+            ..from_hierarchy
+        },
+        from_header
+    );
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "gzip"), ignore = "requires gzip feature")]
+#[test]
+fn initial_values_load_trailing_hdl_example_metadata() -> Result<()> {
+    let file = File::open(fixture_path())?;
+    let mut reader = ReaderBuilder::new(file).build()?;
+
+    let initial = reader.initial_values()?;
+    assert!(!initial.is_empty());
+    assert!(reader.geometry().is_some());
+
+    let start_time = reader.header().start_time;
+    let mut first = std::collections::BTreeMap::new();
+    for change in reader.all_value_changes()? {
+        let change = change?;
+        if change.timestamp > start_time {
+            break;
+        }
+        first.entry(change.handle).or_insert(change.value);
+    }
+    for (handle, value) in &initial {
+        if let Some(changed) = first.get(handle) {
+            assert_eq!(changed, value, "handle {handle}");
+        }
+    }
+
+    Ok(())
+}
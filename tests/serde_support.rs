@@ -1,11 +1,14 @@
 #![cfg(feature = "serde")]
 
 use std::fs::File;
+use std::io::Cursor;
 use std::path::PathBuf;
 
 use anyhow::Result;
 use wavefst::{
-    HierarchyBlock, HierarchySnapshot, ReaderBuilder, collect_value_changes, snapshot_hierarchy,
+    FstWriter, GeomEntry, Header, HierarchyBlock, HierarchyCompression, HierarchySnapshot,
+    ReaderBuilder, ScopeType, VarDir, VarType, collect_value_changes, rebuild_hierarchy,
+    snapshot_hierarchy,
 };
 
 fn fixture_path() -> PathBuf {
@@ -63,3 +66,36 @@ fn collect_value_changes_produces_owned_values() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn hierarchy_snapshot_round_trips_through_json() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    writer.begin_attribute(2, 4, "rtl/top.v", 3)?;
+    writer.end_attribute()?;
+    let clk = writer.add_variable(VarType::VcdWire, VarDir::Input, "clk", GeomEntry::Fixed(1))?;
+    writer.begin_scope(ScopeType::VcdModule, "sub", Some("core".into()))?;
+    writer.add_variable(VarType::VcdReg, VarDir::Output, "data", GeomEntry::Fixed(8))?;
+    writer.add_alias(VarType::VcdWire, VarDir::Input, "clk_in", clk)?;
+    writer.end_scope()?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    let reader = ReaderBuilder::new(Cursor::new(writer.finish()?.into_inner())).build()?;
+    let original = reader
+        .hierarchy()
+        .expect("hierarchy block should be present");
+
+    let json = serde_json::to_string(&snapshot_hierarchy(original))?;
+    let snapshot: HierarchySnapshot = serde_json::from_str(&json)?;
+    let rebuilt = rebuild_hierarchy(&snapshot);
+
+    assert_eq!(rebuilt.scopes[1].parent, Some(0));
+    assert_eq!(rebuilt.variables[2].handle, clk);
+    assert_eq!(
+        rebuilt.encode_block(HierarchyCompression::Raw)?.data,
+        original.encode_block(HierarchyCompression::Raw)?.data
+    );
+
+    Ok(())
+}
@@ -0,0 +1,396 @@
+use std::borrow::Cow;
+use std::io::Cursor;
+
+use anyhow::Result;
+use wavefst::reader::ValueChange;
+use wavefst::util::{
+    pack_ascii_bits, pack_ascii_bits_scalar, unpack_to_ascii, unpack_to_ascii_scalar,
+};
+use wavefst::{
+    FstWriter, GeomEntry, Header, LogicState, ReaderBuilder, ScopeType, SignalValue, VarDir,
+    VarType,
+};
+
+#[test]
+fn from_u64_formats_msb_first() {
+    assert_eq!(
+        SignalValue::from_u64(1, 1),
+        SignalValue::Vector(Cow::Borrowed("1"))
+    );
+    assert_eq!(
+        SignalValue::from_u64(0xA5, 8),
+        SignalValue::Vector(Cow::Borrowed("10100101"))
+    );
+    assert_eq!(
+        SignalValue::from_u64(0x1FF, 4),
+        SignalValue::Vector(Cow::Borrowed("1111"))
+    );
+    assert_eq!(
+        SignalValue::from_u64(3, 66),
+        SignalValue::Vector(Cow::Owned(format!("{}11", "0".repeat(64))))
+    );
+    assert_eq!(SignalValue::from_f64(2.5), SignalValue::Real(2.5));
+}
+
+#[test]
+fn as_u64_round_trips_two_state_values() {
+    for (value, width) in [
+        (1u64, 1u32),
+        (0, 1),
+        (0xA5, 8),
+        (u64::MAX, 64),
+        (0x8000_0000_0000_0001, 64),
+    ] {
+        assert_eq!(SignalValue::from_u64(value, width).as_u64(), Some(value));
+    }
+    assert_eq!(SignalValue::Bit('1').as_u64(), Some(1));
+    assert_eq!(
+        SignalValue::PackedBits {
+            width: 12,
+            bits: Cow::Borrowed(&[0xAB, 0xC0]),
+        }
+        .as_u64(),
+        Some(0xABC)
+    );
+}
+
+#[test]
+fn as_i64_sign_extends_signed_values() {
+    let all_ones = SignalValue::Vector(Cow::Borrowed("1111"));
+    assert_eq!(all_ones.as_i64(true, 4), Some(-1));
+    assert_eq!(all_ones.as_i64(false, 4), Some(15));
+    assert_eq!(SignalValue::from_u64(0x80, 8).as_i64(true, 8), Some(-128));
+    assert_eq!(SignalValue::from_u64(0x7F, 8).as_i64(true, 8), Some(127));
+    assert_eq!(
+        SignalValue::PackedBits {
+            width: 12,
+            bits: Cow::Borrowed(&[0xFF, 0xE0]),
+        }
+        .as_i64(true, 12),
+        Some(-2)
+    );
+    assert_eq!(
+        SignalValue::from_u64(u64::MAX, 64).as_i64(true, 64),
+        Some(-1)
+    );
+    assert_eq!(SignalValue::from_u64(u64::MAX, 64).as_i64(false, 64), None);
+    assert_eq!(
+        SignalValue::Vector(Cow::Borrowed("1x11")).as_i64(true, 4),
+        None
+    );
+    assert_eq!(all_ones.as_i64(true, 0), None);
+}
+
+#[test]
+fn var_entries_report_signedness_from_var_type() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    for (var_type, name, width) in [
+        (VarType::VcdInteger, "count", 8),
+        (VarType::SvInt, "delta", 32),
+        (VarType::SvByte, "b", 8),
+        (VarType::SvLogic, "bus", 8),
+        (VarType::SvBit, "flag", 8),
+    ] {
+        writer.add_variable(var_type, VarDir::Implicit, name, GeomEntry::Fixed(width))?;
+    }
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    let reader = ReaderBuilder::new(Cursor::new(writer.finish()?.into_inner())).build()?;
+    let hier = reader
+        .hierarchy()
+        .expect("hierarchy block should be present");
+    let signed: Vec<bool> = hier.variables.iter().map(|var| var.is_signed()).collect();
+    assert_eq!(signed, [true, true, true, false, false]);
+
+    Ok(())
+}
+
+#[test]
+fn as_u64_rejects_unknown_and_wide_values() {
+    assert_eq!(SignalValue::Vector(Cow::Borrowed("10x1")).as_u64(), None);
+    assert_eq!(SignalValue::Bit('z').as_u64(), None);
+    assert_eq!(SignalValue::Real(1.0).as_u64(), None);
+    assert_eq!(
+        SignalValue::Vector(Cow::Owned(format!("1{}", "0".repeat(64)))).as_u64(),
+        None
+    );
+    assert_eq!(
+        SignalValue::Vector(Cow::Owned(format!("0{}", "1".repeat(64)))).as_u64(),
+        Some(u64::MAX)
+    );
+}
+
+#[test]
+fn convenience_values_round_trip_through_writer() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    let byte = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "byte",
+        GeomEntry::Fixed(8),
+    )?;
+    let word = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "word",
+        GeomEntry::Fixed(64),
+    )?;
+    let real = writer.add_variable(VarType::VcdReal, VarDir::Implicit, "real", GeomEntry::Real)?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "signal-value".into(),
+        end_time: 10,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, bit, SignalValue::from_u64(1, 1))?;
+    writer.emit_change(0, byte, SignalValue::from_u64(0x5A, 8))?;
+    writer.emit_change(0, word, SignalValue::from_u64(0xDEAD_BEEF_0123_4567, 64))?;
+    writer.emit_change(0, real, SignalValue::from_f64(-0.75))?;
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+
+    let value_of = |value: Option<SignalValue<'static>>| value.and_then(|v| v.as_u64());
+    assert_eq!(value_of(reader.value_at(bit, 5)?), Some(1));
+    assert_eq!(value_of(reader.value_at(byte, 5)?), Some(0x5A));
+    assert_eq!(
+        value_of(reader.value_at(word, 5)?),
+        Some(0xDEAD_BEEF_0123_4567)
+    );
+    assert_eq!(reader.value_at(real, 5)?, Some(SignalValue::Real(-0.75)));
+
+    Ok(())
+}
+
+#[test]
+fn logic_state_classifies_unknown_and_high_impedance_bits() {
+    assert_eq!(
+        SignalValue::Vector(Cow::Borrowed("01x0")).logic_state(),
+        LogicState::HasX
+    );
+    assert_eq!(
+        SignalValue::Vector(Cow::Borrowed("zzzz")).logic_state(),
+        LogicState::HasZ
+    );
+    assert_eq!(
+        SignalValue::Vector(Cow::Borrowed("10110010")).logic_state(),
+        LogicState::TwoState
+    );
+    assert_eq!(SignalValue::Bit('h').logic_state(), LogicState::HasOther);
+    assert_eq!(
+        SignalValue::Vector(Cow::Borrowed("1hz0")).logic_state(),
+        LogicState::HasZ
+    );
+    assert_eq!(
+        SignalValue::Vector(Cow::Borrowed("zX")).logic_state(),
+        LogicState::HasX
+    );
+    assert_eq!(
+        SignalValue::PackedBits {
+            width: 8,
+            bits: Cow::Borrowed(&[0xA5]),
+        }
+        .logic_state(),
+        LogicState::TwoState
+    );
+    assert_eq!(SignalValue::Real(0.5).logic_state(), LogicState::TwoState);
+}
+
+#[test]
+fn value_changes_report_two_state_values() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bus = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(4),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header {
+        version: "logic-state".into(),
+        end_time: 10,
+        vc_section_count: 1,
+        ..Header::default()
+    })?;
+    writer.emit_change(0, bus, SignalValue::Vector(Cow::Borrowed("0101")))?;
+    writer.emit_change(10, bus, SignalValue::Vector(Cow::Borrowed("01x1")))?;
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let flags: Vec<(u64, bool)> = reader
+        .all_value_changes()?
+        .map(|change| change.map(|change| (change.timestamp, change.is_two_state())))
+        .collect::<wavefst::Result<_>>()?;
+    assert_eq!(flags, vec![(0, true), (10, false)]);
+
+    Ok(())
+}
+
+#[test]
+fn unpack_bits_matches_scalar_path() {
+    let packed = [0xA5, 0x3C, 0xFF, 0x00, 0x81, 0x7E, 0x12, 0xED, 0x55];
+    for width in [1u32, 7, 8, 31, 64] {
+        let bytes = &packed[..(width as usize).div_ceil(8)];
+        let fast = unpack_to_ascii(bytes, width).expect("payload covers width");
+        let scalar = unpack_to_ascii_scalar(bytes, width).expect("payload covers width");
+        assert_eq!(fast, scalar, "width {width}");
+        assert_eq!(fast.len(), width as usize);
+    }
+    assert_eq!(
+        unpack_to_ascii(&[0xA5], 8).as_deref(),
+        Some(&b"10100101"[..])
+    );
+    assert!(unpack_to_ascii(&[0xFF], 9).is_none());
+}
+
+#[test]
+fn reader_expands_packed_vectors_on_request() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(12),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header {
+        version: "expand".into(),
+        ..Header::default()
+    })?;
+    writer.emit_change(0, bus, SignalValue::Vector("101100111000".into()))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut packed = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let change = packed
+        .next_value_changes()?
+        .expect("one block")
+        .next()
+        .expect("one change")?;
+    assert!(matches!(
+        change.value,
+        SignalValue::PackedBits { width: 12, .. }
+    ));
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes))
+        .expand_packed_bits(true)
+        .build()?;
+    let change = reader
+        .next_value_changes()?
+        .expect("one block")
+        .next()
+        .expect("one change")?;
+    assert_eq!(change.value, SignalValue::Vector("101100111000".into()));
+
+    Ok(())
+}
+
+#[test]
+fn pack_bits_matches_scalar_path_on_random_strings() {
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for width in (1u32..=80).chain([127, 128, 129, 255, 1000]) {
+        for _ in 0..4 {
+            let text: Vec<u8> = (0..width)
+                .map(|_| if next() & 1 == 1 { b'1' } else { b'0' })
+                .collect();
+            let fast = pack_ascii_bits(&text, width);
+            assert_eq!(fast, pack_ascii_bits_scalar(&text, width), "width {width}");
+            assert_eq!(
+                unpack_to_ascii(&fast.expect("valid bits"), width),
+                Some(text)
+            );
+        }
+    }
+
+    let mut invalid = vec![b'0'; 40];
+    for position in [0, 15, 16, 33, 39] {
+        invalid[position] = b'x';
+        assert_eq!(pack_ascii_bits(&invalid, 40), None);
+        assert_eq!(pack_ascii_bits_scalar(&invalid, 40), None);
+        invalid[position] = b'0';
+    }
+}
+
+#[test]
+fn packed_conversions_round_trip() {
+    let vector = SignalValue::Vector(Cow::Borrowed("10100101"));
+    let (width, bits) = vector.to_packed().expect("two-state vector");
+    assert_eq!((width, bits.as_slice()), (8, &[0xA5][..]));
+
+    let packed = SignalValue::PackedBits {
+        width,
+        bits: Cow::Owned(bits),
+    };
+    assert_eq!(packed.to_ascii_vector().as_deref(), Some("10100101"));
+    assert_eq!(packed.to_packed(), Some((8, vec![0xA5])));
+
+    assert_eq!(SignalValue::Bit('1').to_packed(), Some((1, vec![0x80])));
+    assert_eq!(SignalValue::Vector(Cow::Borrowed("10x1")).to_packed(), None);
+    assert_eq!(SignalValue::Real(1.5).to_ascii_vector(), None);
+}
+
+#[test]
+fn display_renders_each_variant() {
+    assert_eq!(SignalValue::Bit('z').to_string(), "z");
+    assert_eq!(
+        SignalValue::Vector(Cow::Borrowed("01xz")).to_string(),
+        "b01xz"
+    );
+    assert_eq!(
+        SignalValue::PackedBits {
+            width: 4,
+            bits: Cow::Borrowed(&[0x50]),
+        }
+        .to_string(),
+        "b0101"
+    );
+    assert_eq!(
+        SignalValue::PackedBits {
+            width: 10,
+            bits: Cow::Borrowed(&[0xff]),
+        }
+        .to_string(),
+        "b11111111xx"
+    );
+    assert_eq!(SignalValue::Real(2.5).to_string(), "2.5");
+    assert_eq!(
+        SignalValue::Bytes(Cow::Borrowed(b"IDLE")).to_string(),
+        "IDLE"
+    );
+    assert_eq!(
+        SignalValue::Bytes(Cow::Borrowed(&[0xff, 0x01])).to_string(),
+        "0xff01"
+    );
+
+    let change = ValueChange {
+        timestamp: 42,
+        handle: 3,
+        alias_of: None,
+        value: SignalValue::Vector(Cow::Borrowed("1010")),
+    };
+    assert_eq!(change.to_string(), "#42 3 = b1010");
+}
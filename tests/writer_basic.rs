@@ -1,14 +1,18 @@
 use std::borrow::Cow;
 use std::convert::TryInto;
 use std::io::Cursor;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use anyhow::Result;
 #[cfg(feature = "gzip")]
 use flate2::read::GzDecoder;
 use wavefst::encoding::decode_varint_with_len;
+use wavefst::io::SequentialWriter;
 use wavefst::{
-    ChainCompression, FstWriter, GeomEntry, Header, PackType, ReaderBuilder, ScopeType,
-    SignalValue, TimeCompression, VarDir, VarType,
+    BlackoutEvent, ChainCompression, Compressor, CustomCompressor, CustomDecompressor,
+    Decompressor, DuplicateChangePolicy, FileType, FstWriter, GeomEntry, Header, PackType,
+    ReaderBuilder, ScopeType, SignalValue, TimeCompression, VarDir, VarType,
 };
 
 #[test]
@@ -187,6 +191,109 @@ fn writer_fastlz_chain_compression() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "zstd")]
+#[test]
+fn writer_zstd_chain_compression() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .chain_compression(ChainCompression::Zstd)
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let handle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "fst-zstd-test".into(),
+        end_time: 512,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    for step in 0..512u64 {
+        let bit = if (step & 3) == 0 { '1' } else { '0' };
+        writer.emit_change(step, handle, SignalValue::Bit(bit))?;
+    }
+
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let block = reader
+        .next_vc_block()?
+        .expect("value-change block must be present");
+    assert_eq!(block.header.pack_type(), PackType::Zstd);
+    assert_eq!(block.frame.as_slice(), b"0");
+
+    let mut iter_reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let iter = iter_reader
+        .next_value_changes()?
+        .expect("change iterator should decode zstd payloads");
+    let mut count = 0u64;
+    for evt in iter {
+        let evt = evt?;
+        let expected = if (evt.timestamp & 3) == 0 { '1' } else { '0' };
+        assert_eq!(evt.value, SignalValue::Bit(expected));
+        count += 1;
+    }
+    assert_eq!(count, 512);
+    Ok(())
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn writer_lz4_time_compression_round_trips_irregular_times() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .chain_compression(ChainCompression::Raw)
+        .time_compression(TimeCompression::Lz4)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let handle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let deltas = [1u64, 7, 3, 250, 1, 1, 42, 9, 100_000, 2, 5, 13, 640];
+    let mut times = Vec::new();
+    let mut now = 0u64;
+    for step in 0..20_000usize {
+        times.push(now);
+        now += deltas[(step * 5 + step / deltas.len()) % deltas.len()];
+    }
+
+    let header = Header {
+        version: "fst-lz4-time-test".into(),
+        end_time: *times.last().unwrap(),
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+    for (step, time) in times.iter().enumerate() {
+        let bit = if step % 2 == 0 { '1' } else { '0' };
+        writer.emit_change(*time, handle, SignalValue::Bit(bit))?;
+    }
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let block = reader
+        .next_vc_block()?
+        .expect("value-change block must be present");
+    assert!(block.time_section.compressed_len < block.time_section.uncompressed_len);
+    assert_eq!(block.time_table.timestamps, times);
+    Ok(())
+}
+
 #[test]
 fn writer_handles_multiple_signals_with_raw_encoding() -> Result<()> {
     let sink = Cursor::new(Vec::new());
@@ -585,3 +692,1904 @@ fn writer_wraps_with_zlib_envelope() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn writer_carries_frame_state_across_blocks() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .chain_compression(ChainCompression::Raw)
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(4),
+    )?;
+    let late = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "late",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "multi-block".into(),
+        end_time: 40,
+        vc_section_count: 2,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, bit, SignalValue::Bit('0'))?;
+    writer.emit_change(5, bit, SignalValue::Bit('1'))?;
+    writer.emit_change(5, bus, SignalValue::Vector(Cow::Borrowed("10z1")))?;
+    writer.flush()?;
+
+    writer.emit_change(20, late, SignalValue::Bit('1'))?;
+    writer.emit_change(30, late, SignalValue::Bit('0'))?;
+
+    let sink = writer.finish()?;
+    let bytes = sink.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let first = reader
+        .next_vc_block()?
+        .expect("first value-change block must be present");
+    let second = reader
+        .next_vc_block()?
+        .expect("second value-change block must be present");
+    assert!(reader.next_vc_block()?.is_none());
+
+    assert_eq!(first.header.begin_time, 0);
+    assert_eq!(first.header.end_time, 5);
+    assert_eq!(first.frame.as_slice(), b"110z1x");
+
+    assert_eq!(second.header.begin_time, 20);
+    assert_eq!(second.header.end_time, 30);
+    assert_eq!(second.header.frame_max_handle, 3);
+    assert_eq!(
+        &second.frame.as_slice()[..5],
+        &first.frame.as_slice()[..5],
+        "handles untouched by the second block must keep the state left by the first"
+    );
+    assert_eq!(second.frame.as_slice(), b"110z10");
+
+    let mut iter_reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    {
+        let first_changes = iter_reader
+            .next_value_changes()?
+            .expect("first block iterator should be available");
+        assert_eq!(first_changes.count(), 3);
+    }
+    let mut second_changes = iter_reader
+        .next_value_changes()?
+        .expect("second block iterator should be available");
+    let event = second_changes.next().expect("second block first event")?;
+    assert_eq!(event.timestamp, 20);
+    assert_eq!(event.handle, late);
+    assert_eq!(event.value, SignalValue::Bit('1'));
+    let event = second_changes.next().expect("second block second event")?;
+    assert_eq!(event.timestamp, 30);
+    assert_eq!(event.value, SignalValue::Bit('0'));
+    assert!(second_changes.next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn writer_round_trips_mixed_length_strings() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let text = writer.add_variable(
+        VarType::GenString,
+        VarDir::Implicit,
+        "text",
+        GeomEntry::Variable,
+    )?;
+    let flag = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "flag",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "varlen-strings".into(),
+        end_time: 50,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    let long = "abcdefghij".repeat(30);
+    let payloads: Vec<(u64, Vec<u8>)> = vec![
+        (0, b"".to_vec()),
+        (10, b"a".to_vec()),
+        (20, long.clone().into_bytes()),
+        (30, b"idle".to_vec()),
+        (40, vec![0, 1, 2, 0xff]),
+    ];
+    for (idx, (timestamp, payload)) in payloads.iter().enumerate() {
+        if idx == 2 {
+            writer.emit_change(*timestamp, text, SignalValue::Vector(Cow::Borrowed(&long)))?;
+        } else {
+            writer.emit_change(*timestamp, text, SignalValue::Bytes(Cow::Borrowed(payload)))?;
+        }
+        let bit = if idx % 2 == 0 { '1' } else { '0' };
+        writer.emit_change(*timestamp, flag, SignalValue::Bit(bit))?;
+    }
+    assert!(
+        writer.emit_change(45, text, SignalValue::Bit('1')).is_err(),
+        "variable-length handles must reject single-bit payloads"
+    );
+
+    let sink = writer.finish()?;
+    let bytes = sink.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut iter = reader
+        .next_value_changes()?
+        .expect("value-change iterator should be available");
+
+    let mut strings = Vec::new();
+    let mut flags = 0usize;
+    for event in &mut iter {
+        let event = event?;
+        if event.handle == text {
+            match event.value {
+                SignalValue::Bytes(bytes) => strings.push((event.timestamp, bytes.into_owned())),
+                other => panic!("unexpected string payload {other:?}"),
+            }
+        } else {
+            assert_eq!(event.handle, flag);
+            flags += 1;
+        }
+    }
+
+    assert_eq!(strings, payloads);
+    assert_eq!(flags, payloads.len());
+
+    Ok(())
+}
+
+#[test]
+fn writer_streams_to_non_seekable_sink() -> Result<()> {
+    let sink = SequentialWriter::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let handle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "sequential".into(),
+        end_time: 20,
+        vc_section_count: 2,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, handle, SignalValue::Bit('0'))?;
+    writer.flush()?;
+    writer.emit_change(10, handle, SignalValue::Bit('1'))?;
+
+    let sink = writer.finish()?;
+    assert_eq!(sink.position(), sink.get_ref().len() as u64);
+    let bytes = sink.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut timestamps = Vec::new();
+    while let Some(block) = reader.next_value_changes()? {
+        for event in block {
+            let event = event?;
+            assert_eq!(event.handle, handle);
+            timestamps.push(event.timestamp);
+        }
+    }
+    assert_eq!(timestamps, vec![0, 10]);
+
+    Ok(())
+}
+
+#[test]
+fn sequential_writer_rejects_repositioning() {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let mut sink = SequentialWriter::new(Vec::new());
+    sink.write_all(b"fst").unwrap();
+    assert_eq!(sink.stream_position().unwrap(), 3);
+    assert_eq!(sink.seek(SeekFrom::Start(3)).unwrap(), 3);
+    assert!(sink.seek(SeekFrom::Start(0)).is_err());
+    assert!(sink.seek(SeekFrom::End(0)).is_err());
+}
+
+#[test]
+fn writer_encodes_change_at_start_time_with_zero_delta() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let handle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "start-time".into(),
+        start_time: 0,
+        end_time: 3,
+        time_zero: 0,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, handle, SignalValue::Bit('1'))?;
+    writer.emit_change(3, handle, SignalValue::Bit('0'))?;
+
+    let sink = writer.finish()?;
+    let bytes = sink.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let block = reader
+        .next_vc_block()?
+        .expect("value-change block must be present");
+    assert_eq!(block.header.begin_time, 0);
+    assert_eq!(block.time_table.deltas, vec![0, 3]);
+    assert_eq!(block.time_table.timestamps, vec![0, 3]);
+
+    let mut iter_reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut iter = iter_reader
+        .next_value_changes()?
+        .expect("value-change iterator should be available");
+    let first = iter.next().expect("change at t=0")?;
+    assert_eq!(first.timestamp, 0);
+    assert_eq!(first.value, SignalValue::Bit('1'));
+    let second = iter.next().expect("change at t=3")?;
+    assert_eq!(second.timestamp, 3);
+    assert_eq!(second.value, SignalValue::Bit('0'));
+    assert!(iter.next().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn writer_resolves_duplicate_changes() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    let sig_alias = writer.add_alias(VarType::VcdWire, VarDir::Implicit, "sig_alias", sig)?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(2),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "duplicates".into(),
+        end_time: 10,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(0, sig, SignalValue::Bit('0'))?;
+    writer.emit_change(0, sig, SignalValue::Bit('x'))?;
+    writer.emit_change(5, bus, SignalValue::Vector(Cow::Borrowed("01")))?;
+    writer.emit_change(5, sig, SignalValue::Bit('1'))?;
+    writer.emit_change(5, bus, SignalValue::Vector(Cow::Borrowed("10")))?;
+    writer.emit_change(5, sig_alias, SignalValue::Bit('0'))?;
+
+    let sink = writer.finish()?;
+    let bytes = sink.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut iter = reader
+        .next_value_changes()?
+        .expect("value-change iterator should be available");
+    let mut events = Vec::new();
+    for event in &mut iter {
+        let event = event?;
+        if event.alias_of.is_none() {
+            events.push((event.timestamp, event.handle, event.value.into_owned()));
+        }
+    }
+    events.sort_by_key(|(timestamp, handle, _)| (*timestamp, *handle));
+
+    assert_eq!(
+        events,
+        vec![
+            (0, sig, SignalValue::Bit('x')),
+            (5, sig, SignalValue::Bit('0')),
+            (
+                5,
+                bus,
+                SignalValue::PackedBits {
+                    width: 2,
+                    bits: Cow::Owned(vec![0b1000_0000])
+                }
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn writer_rejects_duplicate_changes_when_requested() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .duplicate_changes(DuplicateChangePolicy::Reject)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    writer.emit_change(3, sig, SignalValue::Bit('0'))?;
+    writer.emit_change(3, sig, SignalValue::Bit('1'))?;
+    let err = writer
+        .flush()
+        .expect_err("duplicate (time, handle) pairs must be rejected");
+    assert!(err.to_string().contains("timestamp 3"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn writer_accepts_out_of_order_emission() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .chain_compression(ChainCompression::Raw)
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "out-of-order".into(),
+        end_time: 10,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    writer.emit_change(10, bit, SignalValue::Bit('1'))?;
+    writer.emit_change(5, bit, SignalValue::Bit('0'))?;
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let block = reader
+        .next_vc_block()?
+        .expect("value-change block must be present");
+    assert_eq!(block.frame.as_slice(), b"1");
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let changes = reader
+        .next_value_changes()?
+        .expect("value-change iterator should be available");
+    let mut collected = Vec::new();
+    for evt in changes {
+        let evt = evt?;
+        collected.push((evt.timestamp, evt.value.into_owned()));
+    }
+    assert_eq!(
+        collected,
+        vec![(5, SignalValue::Bit('0')), (10, SignalValue::Bit('1'))]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn writer_rejects_changes_before_a_flushed_block() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    writer.emit_change(4, bit, SignalValue::Bit('0'))?;
+    writer.emit_change(10, bit, SignalValue::Bit('1'))?;
+    writer.flush()?;
+    let err = writer
+        .emit_change(2, bit, SignalValue::Bit('0'))
+        .expect_err("change before the flushed block's end");
+    assert!(matches!(err, wavefst::Error::InvalidData(_)), "{err:?}");
+    // The flushed block's final timestamp itself is still accepted.
+    writer.emit_change(10, bit, SignalValue::Bit('0'))?;
+    writer.emit_change(12, bit, SignalValue::Bit('1'))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let times: Vec<u64> = reader
+        .all_value_changes()?
+        .map(|change| change.map(|change| change.timestamp))
+        .collect::<wavefst::Result<_>>()?;
+    assert_eq!(times, [4, 10, 10, 12]);
+
+    Ok(())
+}
+
+fn write_counter_trace(max_pending: Option<usize>) -> Result<Vec<u8>> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .max_pending_changes(max_pending)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let count = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "count",
+        GeomEntry::Fixed(8),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "auto-flush".into(),
+        end_time: 4_999,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    for t in 0..5_000u64 {
+        let bit = if t % 2 == 0 { '0' } else { '1' };
+        writer.emit_change(t, clk, SignalValue::Bit(bit))?;
+        let bits = format!("{:08b}", t % 256);
+        writer.emit_change(t, count, SignalValue::Vector(Cow::Owned(bits)))?;
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+fn collect_block_events(bytes: Vec<u8>) -> Result<(usize, Vec<String>)> {
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut blocks = 0;
+    let mut events = Vec::new();
+    while let Some(changes) = reader.next_value_changes()? {
+        blocks += 1;
+        for evt in changes {
+            let evt = evt?;
+            events.push(format!("{} {} {:?}", evt.timestamp, evt.handle, evt.value));
+        }
+    }
+    Ok((blocks, events))
+}
+
+#[test]
+fn writer_flushes_blocks_at_pending_threshold() -> Result<()> {
+    let single = write_counter_trace(None)?;
+    let split = write_counter_trace(Some(1_000))?;
+
+    let (single_blocks, single_events) = collect_block_events(single)?;
+    let (split_blocks, split_events) = collect_block_events(split)?;
+
+    assert_eq!(single_blocks, 1);
+    assert!(
+        split_blocks > 1,
+        "expected multiple blocks, got {split_blocks}"
+    );
+    assert_eq!(single_events.len(), 10_000);
+    assert_eq!(split_events, single_events);
+
+    Ok(())
+}
+
+fn write_wide_burst_trace(max_block_memory: Option<u64>) -> Result<Vec<u8>> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .max_block_memory(max_block_memory)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let mut handles = Vec::new();
+    for idx in 0..64 {
+        handles.push(writer.add_variable(
+            VarType::VcdReg,
+            VarDir::Implicit,
+            format!("bus{idx}"),
+            GeomEntry::Fixed(32),
+        )?);
+    }
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "memory-flush".into(),
+        end_time: 199,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    for t in 0..200u64 {
+        for (idx, handle) in handles.iter().enumerate() {
+            let value = t.wrapping_mul(0x9E37_79B9).wrapping_add(idx as u64);
+            writer.emit_change(t, *handle, SignalValue::from_u64(value, 32))?;
+        }
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+#[test]
+fn writer_flushes_blocks_at_memory_threshold() -> Result<()> {
+    const LIMIT: u64 = 16 * 1024;
+    let single = write_wide_burst_trace(None)?;
+    let split = write_wide_burst_trace(Some(LIMIT))?;
+
+    let mut reader = ReaderBuilder::new(Cursor::new(split.clone())).build()?;
+    let mut ranges = Vec::new();
+    while let Some(block) = reader.next_vc_block()? {
+        // One timestamp of the burst (64 packed 32-bit values) may overshoot the limit.
+        assert!(block.header.required_memory <= LIMIT + 64 * 5);
+        ranges.push((block.header.begin_time, block.header.end_time));
+    }
+    assert!(ranges.len() > 1, "expected multiple blocks, got {ranges:?}");
+    assert!(ranges.windows(2).all(|pair| pair[0].1 < pair[1].0));
+
+    let (single_blocks, single_events) = collect_block_events(single)?;
+    let (_, split_events) = collect_block_events(split)?;
+    assert_eq!(single_blocks, 1);
+    assert_eq!(single_events.len(), 64 * 200);
+    assert_eq!(split_events, single_events);
+
+    Ok(())
+}
+
+#[test]
+fn writer_rejects_zero_block_memory() {
+    let result = FstWriter::builder(Cursor::new(Vec::new()))
+        .max_block_memory(Some(0))
+        .build();
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn writer_reports_last_block_stats() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .chain_compression(ChainCompression::Zlib)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bus = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(16),
+    )?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "block-stats".into(),
+        end_time: 2_000,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+    assert_eq!(writer.last_block_stats(), None);
+
+    for t in 0..2_000u64 {
+        writer.emit_change(t, clk, SignalValue::Bit(if t % 2 == 0 { '0' } else { '1' }))?;
+        writer.emit_change(t, bus, SignalValue::from_u64(t % 4, 16))?;
+    }
+    writer.flush()?;
+
+    let stats = writer.last_block_stats().expect("a block was flushed");
+    assert_eq!(stats.handle_count, 2);
+    assert!(stats.chain_bytes_raw > 0);
+    assert!(stats.chain_bytes_stored < stats.chain_bytes_raw);
+    assert_eq!(stats.frame_uncompressed_len, 17);
+    assert!(stats.time_uncompressed_len >= 2_000);
+
+    writer.emit_change(2_000, clk, SignalValue::Bit('0'))?;
+    writer.flush()?;
+    let next = writer.last_block_stats().expect("second block was flushed");
+    assert_eq!(next.handle_count, 1);
+    assert_ne!(next, stats);
+
+    writer.finish()?;
+    Ok(())
+}
+
+#[test]
+fn writer_emits_blackout_events() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink).build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bit = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bit",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+
+    writer.add_blackout(false, 10)?;
+    writer.add_blackout(true, 25)?;
+    assert!(writer.add_blackout(false, 20).is_err());
+
+    let header = Header {
+        version: "blackout".into(),
+        end_time: 30,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+    writer.emit_change(0, bit, SignalValue::Bit('1'))?;
+
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert!(reader.next_vc_block()?.is_some());
+
+    let blackout = reader.blackout().expect("blackout block should be decoded");
+    assert_eq!(
+        blackout.events,
+        vec![
+            BlackoutEvent {
+                is_on: false,
+                time: 10,
+            },
+            BlackoutEvent {
+                is_on: true,
+                time: 25,
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+fn wide_geometry_trace(compress: bool) -> Result<Vec<u8>> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .compress_geometry(compress)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    for idx in 0..5_000 {
+        writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            format!("w{idx}"),
+            GeomEntry::Fixed(1),
+        )?;
+    }
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    Ok(writer.finish()?.into_inner())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn writer_compresses_geometry_block() -> Result<()> {
+    let raw = wide_geometry_trace(false)?;
+    let compressed = wide_geometry_trace(true)?;
+    assert!(
+        compressed.len() < raw.len(),
+        "compressed geometry trace ({} bytes) should be smaller than raw ({} bytes)",
+        compressed.len(),
+        raw.len()
+    );
+
+    let raw_reader = ReaderBuilder::new(Cursor::new(raw)).build()?;
+    let compressed_reader = ReaderBuilder::new(Cursor::new(compressed)).build()?;
+    let raw_geom = raw_reader.geometry().expect("raw geometry present");
+    let compressed_geom = compressed_reader
+        .geometry()
+        .expect("compressed geometry present");
+    assert_eq!(compressed_geom.max_handle, 5_000);
+    assert_eq!(compressed_geom.entries, raw_geom.entries);
+    assert!(
+        compressed_geom
+            .entries
+            .iter()
+            .all(|entry| *entry == GeomEntry::Fixed(1))
+    );
+
+    Ok(())
+}
+
+/// Toy codec: run-length encodes bytes as `(count, byte ^ 0x5a)` pairs.
+struct XorRle;
+
+impl Compressor for XorRle {
+    fn compress(&mut self, input: &[u8], output: &mut Vec<u8>) -> wavefst::Result<()> {
+        for run in input.chunk_by(|a, b| a == b) {
+            for chunk in run.chunks(u8::MAX as usize) {
+                output.push(chunk.len() as u8);
+                output.push(chunk[0] ^ 0x5a);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Decompressor for XorRle {
+    fn decompress(&mut self, input: &[u8], output: &mut Vec<u8>) -> wavefst::Result<()> {
+        for pair in input.chunks_exact(2) {
+            output.extend(std::iter::repeat_n(pair[1] ^ 0x5a, pair[0] as usize));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn writer_round_trips_custom_chain_codec() -> Result<()> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .custom_chain_compression(CustomCompressor::new(b'X', || Box::new(XorRle)))
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(64),
+    )?;
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "custom-codec".into(),
+        end_time: 63,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    for t in 0..64u64 {
+        let bits = format!("{:064b}", t % 2);
+        writer.emit_change(t, bus, SignalValue::Vector(Cow::Owned(bits)))?;
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    let mut plain = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    assert!(plain.next_vc_block().is_err());
+
+    let decoded = Arc::new(AtomicUsize::new(0));
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes))
+        .register_chain_decompressor(CustomDecompressor::new(b'X', {
+            let decoded = Arc::clone(&decoded);
+            move || {
+                decoded.fetch_add(1, Ordering::Relaxed);
+                Box::new(XorRle)
+            }
+        }))
+        .build()?;
+    let changes = reader
+        .next_value_changes()?
+        .expect("value-change iterator should be available");
+    let mut count = 0u64;
+    for evt in changes {
+        let evt = evt?;
+        let mut expected = vec![0u8; 8];
+        expected[7] = (evt.timestamp % 2) as u8;
+        assert_eq!(
+            evt.value,
+            SignalValue::PackedBits {
+                width: 64,
+                bits: Cow::Owned(expected)
+            }
+        );
+        count += 1;
+    }
+    assert_eq!(count, 64);
+    assert!(
+        decoded.load(Ordering::Relaxed) > 0,
+        "custom codec never ran"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+fn wide_block_trace() -> Result<Vec<u8>> {
+    let sink = Cursor::new(Vec::new());
+    let mut writer = FstWriter::builder(sink)
+        .chain_compression(ChainCompression::Lz4)
+        .time_compression(TimeCompression::Raw)
+        .build()?;
+
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let mut handles = Vec::with_capacity(1_000);
+    for idx in 0..1_000 {
+        handles.push(writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            format!("sig{idx}"),
+            GeomEntry::Fixed(8),
+        )?);
+    }
+    writer.end_scope()?;
+
+    let header = Header {
+        version: "parallel-chains".into(),
+        end_time: 31,
+        vc_section_count: 1,
+        ..Header::default()
+    };
+    writer.write_header(header)?;
+
+    for t in 0..32u64 {
+        for (idx, &handle) in handles.iter().enumerate() {
+            let bits = format!("{:08b}", (t as usize + idx) % 256);
+            writer.emit_change(t, handle, SignalValue::Vector(Cow::Owned(bits)))?;
+        }
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn writer_parallel_chain_compression_is_deterministic() -> Result<()> {
+    let serial_pool = rayon::ThreadPoolBuilder::new().num_threads(1).build()?;
+    let parallel_pool = rayon::ThreadPoolBuilder::new().num_threads(8).build()?;
+
+    let serial = serial_pool.install(wide_block_trace)?;
+    let parallel = parallel_pool.install(wide_block_trace)?;
+    assert_eq!(serial, parallel);
+
+    let mut reader = ReaderBuilder::new(Cursor::new(parallel)).build()?;
+    let changes = reader
+        .next_value_changes()?
+        .expect("value-change iterator should be available");
+    let mut count = 0usize;
+    for evt in changes {
+        evt?;
+        count += 1;
+    }
+    assert_eq!(count, 32_000);
+
+    Ok(())
+}
+
+#[test]
+fn writer_patches_header_time_range() -> Result<()> {
+    for wrap in [false, cfg!(feature = "gzip")] {
+        let sink = Cursor::new(Vec::new());
+        let mut writer = FstWriter::builder(sink).wrap_with_zlib(wrap).build()?;
+
+        writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+        let handle = writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            "sig",
+            GeomEntry::Fixed(1),
+        )?;
+        writer.end_scope()?;
+
+        writer.write_header(Header {
+            version: "time-range".into(),
+            vc_section_count: 1,
+            ..Header::default()
+        })?;
+        writer.emit_change(97, handle, SignalValue::Bit('0'))?;
+        writer.emit_change(3, handle, SignalValue::Bit('1'))?;
+
+        let bytes = writer.finish()?.into_inner();
+        let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+        assert_eq!(reader.header().start_time, 3);
+        assert_eq!(reader.header().end_time, 97);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn geometry_honours_compression_level() -> Result<()> {
+    let runs = (0..512u32).map(|idx| (1, GeomEntry::Fixed(1 + idx % 16)));
+    let geom = wavefst::GeomInfo::from_runs(runs)?;
+
+    let stored = geom.encode_block(true, Some(0))?;
+    let best = geom.encode_block(true, Some(9))?;
+    assert!(!stored.compressed, "level 0 cannot beat the raw table");
+    assert!(best.compressed);
+    assert!(
+        best.section_length < stored.section_length,
+        "level 9 ({}) should be smaller than level 0 ({})",
+        best.section_length,
+        stored.section_length
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn writer_rejects_out_of_range_compression_level() {
+    let err = FstWriter::builder(Cursor::new(Vec::new()))
+        .compression_level(Some(10))
+        .build()
+        .err()
+        .expect("level 10 is outside the zlib range");
+    assert!(err.to_string().contains("0-9"), "{err}");
+    assert!(
+        FstWriter::builder(Cursor::new(Vec::new()))
+            .compression_level(Some(9))
+            .build()
+            .is_ok()
+    );
+}
+
+#[cfg(all(feature = "gzip", feature = "zstd"))]
+#[test]
+fn zstd_only_writer_accepts_levels_above_the_zlib_range() -> Result<()> {
+    let zstd_only = || {
+        FstWriter::builder(Cursor::new(Vec::new()))
+            .chain_compression(ChainCompression::Zstd)
+            .time_compression(TimeCompression::Raw)
+            .compress_geometry(false)
+            .compression_level(Some(19))
+    };
+    let mut writer = zstd_only().build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(32),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    for time in 0..64u64 {
+        writer.emit_change(time, bus, SignalValue::from_u64(time % 4, 32))?;
+    }
+    let bytes = writer.finish()?.into_inner();
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.all_value_changes()?.count(), 64);
+
+    let err = zstd_only()
+        .time_compression(TimeCompression::Zlib)
+        .build()
+        .err()
+        .expect("zlib time tables cap the level at 9");
+    assert!(err.to_string().contains("0-9"), "{err}");
+
+    Ok(())
+}
+
+#[test]
+fn finish_patches_vc_section_count() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header {
+        version: "vc-count".into(),
+        ..Header::default()
+    })?;
+    writer.emit_change(0, sig, SignalValue::Bit('0'))?;
+    writer.flush()?;
+    writer.emit_change(10, sig, SignalValue::Bit('1'))?;
+    writer.flush()?;
+    assert_eq!(writer.vc_section_count(), 2);
+    let bytes = writer.finish()?.into_inner();
+
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.header().vc_section_count, 2);
+    assert_eq!(reader.header().end_time, 10);
+
+    Ok(())
+}
+
+#[test]
+fn emit_clock_expands_into_alternating_edges() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(2),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header {
+        version: "clock".into(),
+        ..Header::default()
+    })?;
+    assert!(writer.emit_clock(bus, 0, 10, 5, 1).is_err());
+    assert!(writer.emit_clock(clk, 0, 10, 10, 1).is_err());
+    assert!(writer.emit_clock(clk, u64::MAX - 4, 10, 5, 1).is_err());
+    writer.emit_clock(clk, 100, 10, 4, 3)?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let edges: Vec<(u64, SignalValue<'static>)> = reader
+        .all_value_changes()?
+        .map(|change| change.map(|change| (change.timestamp, change.value)))
+        .collect::<wavefst::Result<_>>()?;
+    let expected: Vec<(u64, SignalValue<'static>)> = [100, 104, 110, 114, 120, 124]
+        .into_iter()
+        .zip(['1', '0'].into_iter().cycle())
+        .map(|(time, bit)| (time, SignalValue::Bit(bit)))
+        .collect();
+    assert_eq!(edges, expected);
+
+    Ok(())
+}
+
+#[test]
+fn reject_nonfinite_reals_is_opt_in() -> Result<()> {
+    for reject in [false, true] {
+        let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+            .reject_nonfinite_reals(reject)
+            .build()?;
+        writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+        let real = writer.add_variable(
+            VarType::VcdReal,
+            VarDir::Implicit,
+            "real_sig",
+            GeomEntry::Real,
+        )?;
+        writer.end_scope()?;
+        writer.write_header(Header::default())?;
+
+        writer.emit_change(0, real, SignalValue::Real(1.5))?;
+        let nan = writer.emit_change(1, real, SignalValue::Real(f64::NAN));
+        let inf = writer.emit_change(
+            2,
+            real,
+            SignalValue::Bytes(Cow::Owned(f64::INFINITY.to_ne_bytes().to_vec())),
+        );
+        assert_eq!(nan.is_err(), reject);
+        assert_eq!(inf.is_err(), reject);
+        writer.finish()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn create_writes_a_readable_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("created.fst");
+
+    let mut writer = FstWriter::create(&path)?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, sig, SignalValue::Bit('1'))?;
+    writer.emit_change(7, sig, SignalValue::Bit('0'))?;
+    writer.finish()?;
+
+    let mut reader = wavefst::FstReader::open(&path)?;
+    let times: Vec<u64> = reader
+        .all_value_changes()?
+        .map(|change| change.map(|change| change.timestamp))
+        .collect::<wavefst::Result<_>>()?;
+    assert_eq!(times, [0, 7]);
+
+    let missing_dir = dir.path().join("absent").join("out.fst");
+    assert!(matches!(
+        FstWriter::create(missing_dir),
+        Err(wavefst::Error::Io(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn finish_checked_reports_undriven_handles() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let bus = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(4),
+    )?;
+    let idle = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "idle",
+        GeomEntry::Fixed(4),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, clk, SignalValue::Bit('1'))?;
+    writer.emit_change(5, bus, SignalValue::from_u64(3, 4))?;
+
+    let (sink, undriven) = writer.finish_checked()?;
+    assert_eq!(undriven, vec![idle]);
+    let reader = ReaderBuilder::new(Cursor::new(sink.into_inner())).build()?;
+    assert_eq!(reader.header().max_handle, 3);
+
+    Ok(())
+}
+
+#[test]
+fn std_logic_vectors_are_normalized_and_validated() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VhdlArchitecture, "top", None)?;
+    let bus = writer.add_variable(
+        VarType::SvLogic,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(8),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, bus, SignalValue::Vector("01HLWZ-?".into()))?;
+    writer.emit_change(4, bus, SignalValue::Bytes(Cow::Borrowed(b"UX0011zz")))?;
+    assert!(
+        writer
+            .emit_change(8, bus, SignalValue::Vector("0101012a".into()))
+            .is_err()
+    );
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let values: Vec<SignalValue<'static>> = reader
+        .all_value_changes()?
+        .map(|change| change.map(|change| change.value))
+        .collect::<wavefst::Result<_>>()?;
+    assert_eq!(
+        values,
+        [
+            SignalValue::Vector("01hlwz-?".into()),
+            SignalValue::Vector("ux0011zz".into())
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn readers_open_from_owned_and_borrowed_bytes() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, sig, SignalValue::Bit('0'))?;
+    writer.emit_change(3, sig, SignalValue::Bit('1'))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut borrowed = wavefst::FstReader::from_slice(&bytes)?;
+    let from_slice: Vec<u64> = borrowed
+        .all_value_changes()?
+        .map(|change| change.map(|change| change.timestamp))
+        .collect::<wavefst::Result<_>>()?;
+
+    let mut owned = wavefst::FstReader::from_bytes(bytes.clone())?;
+    assert_eq!(owned.header().var_count, borrowed.header().var_count);
+    let from_bytes: Vec<u64> = owned
+        .all_value_changes()?
+        .map(|change| change.map(|change| change.timestamp))
+        .collect::<wavefst::Result<_>>()?;
+
+    assert_eq!(from_slice, [0, 3]);
+    assert_eq!(from_bytes, from_slice);
+    Ok(())
+}
+
+#[test]
+fn deferred_header_interleaves_declarations_and_changes() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+        .deferred_header(true)
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let first = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "first",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.emit_change(0, first, SignalValue::Bit('1'))?;
+    let second = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "second",
+        GeomEntry::Fixed(4),
+    )?;
+    writer.emit_change(2, second, SignalValue::Vector("10x1".into()))?;
+    writer.emit_change(5, first, SignalValue::Bit('0'))?;
+    writer.end_scope()?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.header().var_count, 2);
+    assert_eq!(
+        (reader.header().start_time, reader.header().end_time),
+        (0, 5)
+    );
+    let changes: Vec<(u64, u32, SignalValue<'static>)> = reader
+        .all_value_changes()?
+        .map(|change| change.map(|change| (change.timestamp, change.handle, change.value)))
+        .collect::<wavefst::Result<_>>()?;
+    assert_eq!(
+        changes,
+        [
+            (0, first, SignalValue::Bit('1')),
+            (2, second, SignalValue::Vector("10x1".into())),
+            (5, first, SignalValue::Bit('0')),
+        ]
+    );
+
+    let mut eager = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    eager.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = eager.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    assert!(eager.emit_change(0, sig, SignalValue::Bit('1')).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn forbid_duplicate_names_rejects_collisions_within_a_scope() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+        .forbid_duplicate_names(true)
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "rst",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "clk_a", clk)?;
+    writer.add_alias(VarType::VcdWire, VarDir::Implicit, "clk_b", clk)?;
+    let err = writer
+        .add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            "clk",
+            GeomEntry::Fixed(1),
+        )
+        .expect_err("duplicate variable");
+    assert!(matches!(err, wavefst::Error::InvalidData(_)), "{err}");
+    assert!(
+        writer
+            .add_alias(VarType::VcdWire, VarDir::Implicit, "rst", clk)
+            .is_err()
+    );
+
+    // The same name is fine in a different scope.
+    writer.begin_scope(ScopeType::VcdModule, "sub", None)?;
+    writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.end_scope()?;
+
+    let mut lenient = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    lenient.begin_scope(ScopeType::VcdModule, "top", None)?;
+    lenient.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    lenient.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn auto_chain_compression_never_loses_to_a_fixed_choice() -> Result<()> {
+    let build = |compression: ChainCompression| -> Result<Vec<u8>> {
+        let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+            .chain_compression(compression)
+            .build()?;
+        writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+        let constant = writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            "constant",
+            GeomEntry::Fixed(32),
+        )?;
+        let noisy = writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            "noisy",
+            GeomEntry::Fixed(16),
+        )?;
+        writer.end_scope()?;
+        writer.write_header(Header::default())?;
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        for time in 0..400u64 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            writer.emit_change(time, constant, SignalValue::from_u64(0xDEAD_BEEF, 32))?;
+            writer.emit_change(time, noisy, SignalValue::from_u64(state & 0xFFFF, 16))?;
+            if time % 100 == 99 {
+                writer.flush()?;
+            }
+        }
+        Ok(writer.finish()?.into_inner())
+    };
+
+    let mut fixed = vec![ChainCompression::Raw];
+    #[cfg(feature = "gzip")]
+    fixed.push(ChainCompression::Zlib);
+    #[cfg(feature = "lz4")]
+    fixed.push(ChainCompression::Lz4);
+    #[cfg(feature = "fastlz")]
+    fixed.push(ChainCompression::FastLz);
+
+    let auto = build(ChainCompression::Auto)?;
+    for compression in fixed {
+        let bytes = build(compression)?;
+        assert!(
+            auto.len() <= bytes.len(),
+            "auto ({}) larger than {compression:?} ({})",
+            auto.len(),
+            bytes.len()
+        );
+    }
+
+    let mut reader = ReaderBuilder::new(Cursor::new(auto.clone())).build()?;
+    while let Some(block) = reader.next_vc_block()? {
+        assert_ne!(block.header.pack_type(), PackType::Zstd);
+    }
+    let mut reader = ReaderBuilder::new(Cursor::new(auto)).build()?;
+    let count = reader.all_value_changes()?.count();
+    assert_eq!(count, 800);
+
+    Ok(())
+}
+
+#[test]
+fn skip_blocks_are_transparent_to_readers() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let sig = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    assert!(writer.write_skip(16).is_err());
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, sig, SignalValue::Bit('1'))?;
+    writer.write_skip(5_000)?;
+    writer.emit_change(4, sig, SignalValue::Bit('0'))?;
+    assert_eq!(writer.vc_section_count(), 1);
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.build_block_index()?.len(), 2);
+    let changes: Vec<(u64, SignalValue<'static>)> = reader
+        .all_value_changes()?
+        .map(|change| change.map(|change| (change.timestamp, change.value)))
+        .collect::<wavefst::Result<_>>()?;
+    assert_eq!(
+        changes,
+        [(0, SignalValue::Bit('1')), (4, SignalValue::Bit('0'))]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn disabled_frame_leaves_frame_section_empty() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+        .emit_frame(false)
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let bus = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(4),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, clk, SignalValue::Bit('0'))?;
+    writer.emit_change(0, bus, SignalValue::Vector(Cow::Borrowed("0000")))?;
+    writer.emit_change(5, clk, SignalValue::Bit('1'))?;
+    writer.emit_change(7, bus, SignalValue::Vector(Cow::Borrowed("1010")))?;
+    writer.flush()?;
+    let stats = writer.last_block_stats().expect("a block was flushed");
+    assert_eq!(stats.frame_uncompressed_len, 0);
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let block = reader.next_vc_block()?.expect("one value-change block");
+    assert_eq!(block.header.frame_uncompressed_len, 0);
+    assert_eq!(block.header.frame_max_handle, 0);
+    assert!(reader.initial_values()?.is_empty());
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let mut changes: Vec<(u64, u32, String)> = reader
+        .all_value_changes()?
+        .map(|change| {
+            change.map(|change| {
+                let text = change.value.to_ascii_vector().expect("bit-valued change");
+                (change.timestamp, change.handle, text)
+            })
+        })
+        .collect::<wavefst::Result<_>>()?;
+    changes.sort();
+    let expected: Vec<(u64, u32, String)> = [
+        (0, clk, "0"),
+        (0, bus, "0000"),
+        (5, clk, "1"),
+        (7, bus, "1010"),
+    ]
+    .into_iter()
+    .map(|(time, handle, text)| (time, handle, text.to_string()))
+    .collect();
+    assert_eq!(changes, expected);
+
+    Ok(())
+}
+
+#[test]
+fn undriven_fill_sets_frame_default() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+        .undriven_fill('0')
+        .build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "idle",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(4),
+    )?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, clk, SignalValue::Bit('1'))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let block = reader.next_vc_block()?.expect("one value-change block");
+    assert_eq!(block.frame.data, b"000001");
+
+    let result = FstWriter::builder(Cursor::new(Vec::new()))
+        .undriven_fill('q')
+        .build();
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn file_type_round_trips_through_header() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+        .file_type(FileType::Vhdl)
+        .build()?;
+    writer.begin_scope(ScopeType::VhdlArchitecture, "top", None)?;
+    writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "sig",
+        GeomEntry::Fixed(1),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    let bytes = writer.finish()?.into_inner();
+
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.header().file_type, 1);
+    assert_eq!(reader.header().file_type_enum(), Some(FileType::Vhdl));
+
+    let unknown = Header {
+        file_type: 7,
+        ..Header::default()
+    };
+    assert_eq!(unknown.file_type_enum(), None);
+
+    Ok(())
+}
+
+#[test]
+fn builder_date_and_version_round_trip() -> Result<()> {
+    fn write_with(date: &str, version: &str) -> Result<Vec<u8>> {
+        let mut writer = FstWriter::builder(Cursor::new(Vec::new()))
+            .date(date)
+            .version(version)
+            .build()?;
+        writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+        writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            "sig",
+            GeomEntry::Fixed(1),
+        )?;
+        writer.end_scope()?;
+        writer.write_header(Header::default())?;
+        Ok(writer.finish()?.into_inner())
+    }
+
+    let bytes = write_with("Fri Oct 16 09:30:00 2026", "wavefst test")?;
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.header().date, "Fri Oct 16 09:30:00 2026");
+    assert_eq!(reader.header().version, "wavefst test");
+
+    // Overlong strings are cut at the field boundary, leaving room for the NUL terminator; a
+    // multi-byte character straddling the boundary is dropped whole.
+    let long_date = format!("{}\u{e9}", "d".repeat(117));
+    let long_version = "v".repeat(200);
+    let bytes = write_with(&long_date, &long_version)?;
+    let reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.header().date, "d".repeat(117));
+    assert_eq!(reader.header().version, "v".repeat(127));
+
+    Ok(())
+}
+
+#[test]
+fn add_alias_by_name_resolves_scope_variables() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let bus = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "bus",
+        GeomEntry::Fixed(8),
+    )?;
+    let mirror =
+        writer.add_alias_by_name(VarType::VcdReg, VarDir::Implicit, "bus_mirror", "bus")?;
+    assert!(
+        writer
+            .add_alias_by_name(VarType::VcdReg, VarDir::Implicit, "ghost_a", "ghost")
+            .is_err()
+    );
+    for _ in 0..2 {
+        writer.add_variable(
+            VarType::VcdWire,
+            VarDir::Implicit,
+            "dup",
+            GeomEntry::Fixed(1),
+        )?;
+    }
+    let err = writer
+        .add_alias_by_name(VarType::VcdWire, VarDir::Implicit, "dup_a", "dup")
+        .expect_err("ambiguous alias target");
+    assert!(err.to_string().contains("ambiguous"), "{err}");
+    writer.begin_scope(ScopeType::VcdModule, "sub", None)?;
+    // Names only resolve within the active scope.
+    assert!(
+        writer
+            .add_alias_by_name(VarType::VcdReg, VarDir::Implicit, "bus_b", "bus")
+            .is_err()
+    );
+    writer.end_scope()?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, bus, SignalValue::from_u64(0x3c, 8))?;
+    writer.emit_change(3, bus, SignalValue::from_u64(0xa5, 8))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes))
+        .expand_packed_bits(true)
+        .build()?;
+    let changes: Vec<_> = reader
+        .all_value_changes()?
+        .collect::<wavefst::Result<_>>()?;
+    let values_of = |handle: u32| -> Vec<(u64, SignalValue<'static>)> {
+        changes
+            .iter()
+            .filter(|change| change.handle == handle)
+            .map(|change| (change.timestamp, change.value.clone()))
+            .collect()
+    };
+    assert_eq!(values_of(bus).len(), 2);
+    assert_eq!(values_of(mirror), values_of(bus));
+    assert!(
+        changes
+            .iter()
+            .filter(|change| change.handle == mirror)
+            .all(|change| change.alias_of == Some(bus))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn shortreal_signals_round_trip_as_f32() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let gain = writer.add_variable(
+        VarType::SvShortReal,
+        VarDir::Implicit,
+        "gain",
+        GeomEntry::ShortReal,
+    )?;
+    let alias = writer.add_alias(VarType::SvShortReal, VarDir::Implicit, "gain_a", gain)?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, gain, SignalValue::Real(1.1))?;
+    writer.flush()?;
+    writer.emit_change(5, gain, SignalValue::Real(-2.5e-3))?;
+    writer.emit_change(
+        9,
+        gain,
+        SignalValue::Bytes(Cow::Owned(0.75f32.to_ne_bytes().to_vec())),
+    )?;
+    assert!(
+        writer
+            .emit_change(10, gain, SignalValue::Bytes(Cow::Borrowed(&[0; 8])))
+            .is_err()
+    );
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    let initial = reader.initial_values()?;
+    assert_eq!(initial.len(), 2);
+    assert!(
+        initial
+            .iter()
+            .all(|(_, value)| *value == SignalValue::Real(f64::from(1.1f32)))
+    );
+    let changes: Vec<(u64, u32, f64)> = reader
+        .all_value_changes()?
+        .map(|change| {
+            change.map(|change| match change.value {
+                SignalValue::Real(value) => (change.timestamp, change.handle, value),
+                other => panic!("unexpected value {other:?}"),
+            })
+        })
+        .collect::<wavefst::Result<_>>()?;
+    let expected = [(0, 1.1), (5, -2.5e-3), (9, 0.75)];
+    assert_eq!(changes.len(), expected.len() * 2);
+    for (time, value) in expected {
+        for handle in [gain, alias] {
+            let (_, _, decoded) = changes
+                .iter()
+                .find(|(t, h, _)| *t == time && *h == handle)
+                .expect("change present");
+            assert_eq!(*decoded, f64::from(value as f32));
+            assert!((decoded - value).abs() <= value.abs() * f64::from(f32::EPSILON));
+        }
+    }
+    assert_eq!(
+        reader.value_at(alias, 6)?,
+        Some(SignalValue::Real(f64::from(-2.5e-3f32)))
+    );
+    assert_eq!(
+        reader.geometry().and_then(|geom| geom.entry(gain)),
+        Some(&GeomEntry::ShortReal)
+    );
+    reader.validate_metadata()?;
+
+    Ok(())
+}
+
+#[test]
+fn append_adds_value_change_blocks_to_existing_trace() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    let clk = writer.add_variable(
+        VarType::VcdWire,
+        VarDir::Implicit,
+        "clk",
+        GeomEntry::Fixed(1),
+    )?;
+    let count = writer.add_variable(
+        VarType::VcdReg,
+        VarDir::Implicit,
+        "count",
+        GeomEntry::Fixed(4),
+    )?;
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+    writer.emit_change(0, clk, SignalValue::Bit('0'))?;
+    writer.emit_change(0, count, SignalValue::Vector(Cow::Borrowed("0101")))?;
+    writer.emit_change(5, clk, SignalValue::Bit('1'))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes.clone())).build()?;
+    let final_values = reader.final_values()?;
+    let header = reader.header().clone();
+    let geometry = reader.geometry().expect("geometry loaded").clone();
+    // The cursor starts at offset 0; `append` moves it to the end of the trace.
+    let mut writer = FstWriter::append(Cursor::new(bytes), header, geometry, &final_values)?;
+    let err = writer
+        .emit_change(4, clk, SignalValue::Bit('0'))
+        .expect_err("change before the existing trace end");
+    assert!(err.to_string().contains("precedes time 5"), "{err}");
+    writer.emit_change(10, clk, SignalValue::Bit('0'))?;
+    writer.flush()?;
+    writer.emit_change(12, count, SignalValue::Vector(Cow::Borrowed("0001")))?;
+    let bytes = writer.finish()?.into_inner();
+
+    let mut reader = ReaderBuilder::new(Cursor::new(bytes)).build()?;
+    assert_eq!(reader.header().vc_section_count, 3);
+    assert_eq!(reader.header().start_time, 0);
+    assert_eq!(reader.header().end_time, 12);
+    let changes: Vec<(u64, u32, String)> = reader
+        .all_value_changes()?
+        .map(|change| {
+            change.map(|change| {
+                let text = change.value.to_ascii_vector().expect("bit-valued change");
+                (change.timestamp, change.handle, text)
+            })
+        })
+        .collect::<wavefst::Result<_>>()?;
+    let expected: Vec<(u64, u32, String)> = [
+        (0, count, "0101"),
+        (0, clk, "0"),
+        (5, clk, "1"),
+        (10, clk, "0"),
+        (12, count, "0001"),
+    ]
+    .into_iter()
+    .map(|(time, handle, text)| (time, handle, text.to_string()))
+    .collect();
+    assert_eq!(changes, expected);
+
+    let count_at = |reader: &mut wavefst::FstReader<_>, time| -> Result<String> {
+        let value = reader.value_at(count, time)?.expect("count has a value");
+        Ok(value.to_ascii_vector().expect("bit-valued value"))
+    };
+    assert_eq!(count_at(&mut reader, 11)?, "0101");
+    assert_eq!(count_at(&mut reader, 12)?, "0001");
+
+    Ok(())
+}
+
+#[test]
+fn add_variable_checks_geometry_against_var_type() -> Result<()> {
+    let mut writer = FstWriter::builder(Cursor::new(Vec::new())).build()?;
+    writer.begin_scope(ScopeType::VcdModule, "top", None)?;
+    writer.add_variable(VarType::SvInt, VarDir::Implicit, "i", GeomEntry::Fixed(32))?;
+    writer.add_variable(VarType::SvByte, VarDir::Implicit, "b", GeomEntry::Fixed(8))?;
+    writer.add_variable(VarType::VcdReal, VarDir::Implicit, "r", GeomEntry::Real)?;
+    writer.add_variable(
+        VarType::SvShortReal,
+        VarDir::Implicit,
+        "s",
+        GeomEntry::ShortReal,
+    )?;
+
+    let mismatches = [
+        (VarType::SvInt, GeomEntry::Fixed(16)),
+        (VarType::SvByte, GeomEntry::Real),
+        (VarType::VcdReal, GeomEntry::Fixed(64)),
+        (VarType::VcdRealtime, GeomEntry::Variable),
+        (VarType::SvShortReal, GeomEntry::Fixed(32)),
+    ];
+    for (var_type, geometry) in mismatches {
+        let err = writer
+            .add_variable(var_type, VarDir::Implicit, "bad", geometry)
+            .expect_err("inconsistent geometry should be rejected");
+        assert!(matches!(err, wavefst::Error::InvalidData(_)), "{err:?}");
+    }
+    writer.end_scope()?;
+    writer.write_header(Header::default())?;
+
+    let reader = ReaderBuilder::new(Cursor::new(writer.finish()?.into_inner())).build()?;
+    assert_eq!(reader.header().var_count, 4);
+
+    Ok(())
+}